@@ -0,0 +1,163 @@
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::SystemTime;
+
+use base64ct::{Base64, Encoding};
+use reqwest::Response;
+use reqwest::header::{AUTHORIZATION, HeaderValue, WWW_AUTHENTICATE};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::{Error, Result};
+
+/// Credentials used by a [`Client`](crate::Client) to authenticate its requests.
+#[derive(Clone, Default)]
+pub enum Authentication {
+    #[default]
+    None,
+    /// HTTP basic authentication with a username and password.
+    Basic { user: String, pass: String },
+    /// An OBS API token, which is only accepted by the `/trigger/*` endpoints.
+    Token(String),
+    /// The SSH signature scheme used by osc: requests are answered with a
+    /// `Signature` challenge, which is signed using `ssh-keygen` and the given
+    /// private key.
+    Signature { user: String, key: PathBuf },
+}
+
+impl std::fmt::Debug for Authentication {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Authentication::None => f.write_str("None"),
+            Authentication::Basic { user, .. } => f
+                .debug_struct("Basic")
+                .field("user", user)
+                .field("pass", &"[redacted]")
+                .finish(),
+            Authentication::Token(_) => f.debug_tuple("Token").field(&"[redacted]").finish(),
+            Authentication::Signature { user, key } => f
+                .debug_struct("Signature")
+                .field("user", user)
+                .field("key", key)
+                .finish(),
+        }
+    }
+}
+
+impl Authentication {
+    /// The user signing requests, if they are authenticated with signatures.
+    pub(crate) fn signature_user(&self) -> Option<&str> {
+        match self {
+            Authentication::Signature { user, .. } => Some(user),
+            _ => None,
+        }
+    }
+
+    /// Adds the authorization header to the request. Returns false if that's
+    /// not possible yet, because the signature realm is unknown until the
+    /// server sent a challenge.
+    pub(crate) async fn authorize(
+        &self,
+        request: &mut reqwest::Request,
+        realm: Option<&str>,
+    ) -> Result<bool> {
+        let value = match self {
+            Authentication::None => return Ok(true),
+            Authentication::Basic { user, pass } => {
+                format!(
+                    "Basic {}",
+                    Base64::encode_string(format!("{user}:{pass}").as_bytes())
+                )
+            }
+            Authentication::Token(token) => format!("Token {token}"),
+            Authentication::Signature { user, key } => {
+                let Some(realm) = realm else {
+                    return Ok(false);
+                };
+                signature_authorization(user, key, realm).await?
+            }
+        };
+
+        let mut value =
+            HeaderValue::try_from(value).map_err(|e| Error::AuthenticationError(e.to_string()))?;
+        value.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, value);
+        Ok(true)
+    }
+}
+
+/// Returns the realm of the `Signature` challenge sent with a 401 response.
+pub(crate) fn signature_challenge_realm(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get_all(WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|value| value.strip_prefix("Signature "))
+        .find_map(|params| {
+            params
+                .split(',')
+                .filter_map(|param| param.trim().split_once('='))
+                .find(|(key, _)| *key == "realm")
+                .map(|(_, value)| value.trim_matches('"').to_owned())
+        })
+}
+
+async fn signature_authorization(user: &str, key: &std::path::Path, realm: &str) -> Result<String> {
+    let created = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_err(|e| Error::AuthenticationError(e.to_string()))?
+        .as_secs();
+    let signature = ssh_sign(format!("(created): {created}").as_bytes(), realm, key).await?;
+    Ok(format!(
+        r#"Signature keyId="{user}",algorithm="ssh",headers="(created)",created={created},signature="{signature}""#
+    ))
+}
+
+// Signs the data the same way osc does, by shelling out to ssh-keygen (which
+// can also use a key held by ssh-agent if given its public key), returning the
+// base64 signature blob without its armor.
+async fn ssh_sign(data: &[u8], namespace: &str, key: &std::path::Path) -> Result<String> {
+    let mut child = Command::new("ssh-keygen")
+        .args(["-Y", "sign", "-q", "-n", namespace, "-f"])
+        .arg(key)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| Error::AuthenticationError(format!("Failed to run ssh-keygen: {e}")))?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin
+        .write_all(data)
+        .await
+        .map_err(|e| Error::AuthenticationError(format!("Failed to write to ssh-keygen: {e}")))?;
+    drop(stdin);
+
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| Error::AuthenticationError(format!("Failed to run ssh-keygen: {e}")))?;
+    if !output.status.success() {
+        return Err(Error::AuthenticationError(format!(
+            "ssh-keygen failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    let armored = String::from_utf8_lossy(&output.stdout);
+    let signature: String = armored
+        .lines()
+        .skip_while(|line| *line != "-----BEGIN SSH SIGNATURE-----")
+        .skip(1)
+        .take_while(|line| *line != "-----END SSH SIGNATURE-----")
+        .collect();
+    if signature.is_empty() {
+        return Err(Error::AuthenticationError(
+            "ssh-keygen returned no signature".to_owned(),
+        ));
+    }
+
+    Ok(signature)
+}
@@ -0,0 +1,566 @@
+//! A synchronous mirror of the async API, for code that doesn't otherwise use
+//! an async runtime.
+//!
+//! Each [`Client`] drives its requests on its own single-threaded tokio
+//! runtime, so it must not be used from within an async context.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use bytes::Bytes;
+use futures::prelude::*;
+use reqwest::Body;
+use tokio::runtime::Runtime;
+use url::Url;
+
+use crate::{
+    BinaryList, BranchOptions, BranchStatus, BsRequest, BuildHistory, BuildStatus, BulkOptions,
+    CommentList, CommitDirectoryOptions, CommitFileEntry, CommitFileList, CommitOptions,
+    CommitResult, CreatedToken, Directory, Group, Involvement, JobHistList, JobHistoryFilters,
+    JobStatus, KeyInfo, PackageListOptions, PackageLogStreamOptions, PackageMeta, PackageResults,
+    Patchinfo, PatchinfoOptions, Person, ProjectListOptions, ProjectMeta, RebuildFilters, Result,
+    ResultList, RevisionList, Role, RoleHolder, Scm, SourceDirectory, SourceInfo, SourceInfoList,
+    SourceInfoOptions, TokenKind, TokenList, TokenOptions, TriggerOptions, UploadOptions,
+    WorkflowRun, WorkflowRunList, WorkflowRunListOptions,
+};
+
+#[derive(Clone, Debug)]
+pub struct Client {
+    client: crate::Client,
+    runtime: Arc<Runtime>,
+}
+
+impl Client {
+    /// Creates a client using HTTP basic authentication and default settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client or the runtime can't be initialized.
+    pub fn new(url: Url, user: String, pass: String) -> Self {
+        Self::from_async(crate::Client::new(url, user, pass))
+    }
+
+    /// Wraps an async client, e.g. one created using
+    /// [`Client::builder`](crate::Client::builder).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime can't be initialized.
+    pub fn from_async(client: crate::Client) -> Self {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("Failed to initialize tokio runtime");
+        Self {
+            client,
+            runtime: Arc::new(runtime),
+        }
+    }
+
+    pub fn as_async(&self) -> &crate::Client {
+        &self.client
+    }
+
+    pub fn url(&self) -> &Url {
+        self.client.url()
+    }
+
+    pub fn list_projects(&self, options: &ProjectListOptions) -> Result<Directory> {
+        self.block_on(self.client.list_projects(options))
+    }
+
+    pub fn project(&self, project: String) -> ProjectBuilder<'_> {
+        ProjectBuilder {
+            client: self,
+            inner: self.client.project(project),
+        }
+    }
+
+    pub fn bs_request(&self, id: u64) -> BsRequestBuilder<'_> {
+        BsRequestBuilder {
+            client: self,
+            inner: self.client.bs_request(id),
+        }
+    }
+
+    pub fn delete_comment(&self, id: u64) -> Result<()> {
+        self.block_on(self.client.delete_comment(id))
+    }
+
+    pub fn person(&self, login: &str) -> Result<Person> {
+        self.block_on(self.client.person(login))
+    }
+
+    pub fn group(&self, title: &str) -> Result<Group> {
+        self.block_on(self.client.group(title))
+    }
+
+    pub fn mbranch_plan(
+        &self,
+        package: &str,
+        options: &BranchOptions,
+    ) -> Result<Vec<BranchStatus>> {
+        self.block_on(self.client.mbranch_plan(package, options))
+    }
+
+    pub fn mbranch(&self, package: &str, options: &BranchOptions) -> Result<String> {
+        self.block_on(self.client.mbranch(package, options))
+    }
+
+    pub fn involved(&self, holder: &RoleHolder, role: Option<Role>) -> Result<Involvement> {
+        self.block_on(self.client.involved(holder, role))
+    }
+
+    pub fn tokens(&self, login: &str) -> Result<TokenList> {
+        self.block_on(self.client.tokens(login))
+    }
+
+    pub fn create_token(
+        &self,
+        login: &str,
+        kind: TokenKind,
+        options: &TokenOptions,
+    ) -> Result<CreatedToken> {
+        self.block_on(self.client.create_token(login, kind, options))
+    }
+
+    pub fn delete_token(&self, login: &str, id: u64) -> Result<()> {
+        self.block_on(self.client.delete_token(login, id))
+    }
+
+    pub fn workflow_runs(
+        &self,
+        token_id: u64,
+        options: &WorkflowRunListOptions,
+    ) -> Result<WorkflowRunList> {
+        self.block_on(self.client.workflow_runs(token_id, options))
+    }
+
+    pub fn workflow_run(&self, id: u64) -> Result<WorkflowRun> {
+        self.block_on(self.client.workflow_run(id))
+    }
+
+    fn block_on<F: Future>(&self, future: F) -> F::Output {
+        self.runtime.block_on(future)
+    }
+}
+
+/// Iterator over the chunks of a response body.
+pub struct BytesIter<'a> {
+    client: &'a Client,
+    stream: stream::BoxStream<'a, Result<Bytes>>,
+}
+
+impl<'a> BytesIter<'a> {
+    fn new(client: &'a Client, stream: impl Stream<Item = Result<Bytes>> + Send + 'a) -> Self {
+        Self {
+            client,
+            stream: stream.boxed(),
+        }
+    }
+}
+
+impl Iterator for BytesIter<'_> {
+    type Item = Result<Bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.client.block_on(self.stream.next())
+    }
+}
+
+/// Iterator version of [`crate::PackageLogStream`].
+pub struct PackageLogStream<'a> {
+    client: &'a Client,
+    inner: crate::PackageLogStream<'a>,
+}
+
+impl Iterator for PackageLogStream<'_> {
+    type Item = Result<Bytes>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.client.block_on(self.inner.next())
+    }
+}
+
+pub struct PackageLog<'a> {
+    client: &'a Client,
+    inner: crate::PackageLog<'a>,
+}
+
+impl<'a> PackageLog<'a> {
+    pub fn stream(&self, options: PackageLogStreamOptions) -> Result<PackageLogStream<'a>> {
+        Ok(PackageLogStream {
+            client: self.client,
+            inner: self.inner.stream(options)?,
+        })
+    }
+
+    /// Returns size and mtime
+    pub fn entry(&self) -> Result<(usize, u64)> {
+        self.client.block_on(self.inner.entry())
+    }
+}
+
+pub struct PackageBuilder<'a> {
+    client: &'a Client,
+    inner: crate::PackageBuilder<'a>,
+}
+
+impl<'a> PackageBuilder<'a> {
+    pub fn jobstatus(&self, repository: &str, arch: &str) -> Result<JobStatus> {
+        self.client.block_on(self.inner.jobstatus(repository, arch))
+    }
+
+    pub fn history(&self, repository: &str, arch: &str) -> Result<BuildHistory> {
+        self.client.block_on(self.inner.history(repository, arch))
+    }
+
+    pub fn status(&self, repository: &str, arch: &str) -> Result<BuildStatus> {
+        self.client.block_on(self.inner.status(repository, arch))
+    }
+
+    pub fn binary_file(&self, repository: &str, arch: &str, file: &str) -> Result<BytesIter<'a>> {
+        let stream = self
+            .client
+            .block_on(self.inner.binary_file(repository, arch, file))?;
+        Ok(BytesIter::new(self.client, stream))
+    }
+
+    pub fn binaries(&self, repository: &str, arch: &str) -> Result<BinaryList> {
+        self.client.block_on(self.inner.binaries(repository, arch))
+    }
+
+    pub fn rebuild(&self) -> Result<()> {
+        self.client.block_on(self.inner.rebuild())
+    }
+
+    pub fn log(&self, repository: &str, arch: &str) -> PackageLog<'a> {
+        PackageLog {
+            client: self.client,
+            inner: self.inner.log(repository, arch),
+        }
+    }
+
+    pub fn create(&self) -> Result<()> {
+        self.client.block_on(self.inner.create())
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        self.client.block_on(self.inner.delete())
+    }
+
+    pub fn revisions(&self) -> Result<RevisionList> {
+        self.client.block_on(self.inner.revisions())
+    }
+
+    pub fn list(&self, rev: Option<&str>) -> Result<SourceDirectory> {
+        self.client.block_on(self.inner.list(rev))
+    }
+
+    pub fn list_meta(&self, rev: Option<&str>) -> Result<SourceDirectory> {
+        self.client.block_on(self.inner.list_meta(rev))
+    }
+
+    pub fn info(&self, options: &SourceInfoOptions) -> Result<SourceInfo> {
+        self.client.block_on(self.inner.info(options))
+    }
+
+    pub fn meta(&self) -> Result<PackageMeta> {
+        self.client.block_on(self.inner.meta())
+    }
+
+    pub fn source_file(&self, file: &str) -> Result<BytesIter<'a>> {
+        let stream = self.client.block_on(self.inner.source_file(file))?;
+        Ok(BytesIter::new(self.client, stream))
+    }
+
+    pub fn upload_for_commit<T: Into<Body>>(&self, file: &str, data: T) -> Result<()> {
+        self.client
+            .block_on(self.inner.upload_for_commit(file, data))
+    }
+
+    pub fn upload_path_for_commit<P: AsRef<Path>>(
+        &self,
+        file: &str,
+        path: P,
+        options: &UploadOptions,
+    ) -> Result<CommitFileEntry> {
+        self.client
+            .block_on(self.inner.upload_path_for_commit(file, path, options))
+    }
+
+    pub fn commit(
+        &self,
+        filelist: &CommitFileList,
+        options: &CommitOptions,
+    ) -> Result<CommitResult> {
+        self.client.block_on(self.inner.commit(filelist, options))
+    }
+
+    pub fn commit_directory<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &CommitDirectoryOptions,
+    ) -> Result<SourceDirectory> {
+        self.client
+            .block_on(self.inner.commit_directory(path, options))
+    }
+
+    pub fn branch(&self, options: &BranchOptions) -> Result<BranchStatus> {
+        self.client.block_on(self.inner.branch(options))
+    }
+
+    pub fn result(&self) -> Result<ResultList> {
+        self.client.block_on(self.inner.result())
+    }
+
+    pub fn comments(&self) -> Result<CommentList> {
+        self.client.block_on(self.inner.comments())
+    }
+
+    pub fn create_comment(&self, body: &str, parent: Option<u64>) -> Result<()> {
+        self.client
+            .block_on(self.inner.create_comment(body, parent))
+    }
+
+    pub fn add_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client.block_on(self.inner.add_role(holder, role))
+    }
+
+    pub fn remove_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client.block_on(self.inner.remove_role(holder, role))
+    }
+
+    pub fn patchinfo(&self) -> Result<Patchinfo> {
+        self.client.block_on(self.inner.patchinfo())
+    }
+
+    pub fn update_patchinfo(&self) -> Result<()> {
+        self.client.block_on(self.inner.update_patchinfo())
+    }
+
+    pub fn lock(&self, comment: Option<&str>) -> Result<()> {
+        self.client.block_on(self.inner.lock(comment))
+    }
+
+    pub fn unlock(&self, comment: &str) -> Result<bool> {
+        self.client.block_on(self.inner.unlock(comment))
+    }
+}
+
+pub struct ProjectBuilder<'a> {
+    client: &'a Client,
+    inner: crate::ProjectBuilder<'a>,
+}
+
+impl<'a> ProjectBuilder<'a> {
+    pub fn package(self, package: String) -> PackageBuilder<'a> {
+        PackageBuilder {
+            client: self.client,
+            inner: self.inner.package(package),
+        }
+    }
+
+    pub fn package_sources(
+        &self,
+        options: &BulkOptions,
+    ) -> Result<PackageResults<SourceDirectory>> {
+        self.client.block_on(self.inner.package_sources(options))
+    }
+
+    pub fn package_metas(&self, options: &BulkOptions) -> Result<PackageResults<PackageMeta>> {
+        self.client.block_on(self.inner.package_metas(options))
+    }
+
+    pub fn package_results(&self, options: &BulkOptions) -> Result<PackageResults<ResultList>> {
+        self.client.block_on(self.inner.package_results(options))
+    }
+
+    pub fn delete(&self) -> Result<()> {
+        self.client.block_on(self.inner.delete())
+    }
+
+    pub fn list_packages(&self) -> Result<Directory> {
+        self.client.block_on(self.inner.list_packages())
+    }
+
+    pub fn list_packages_with(&self, options: &PackageListOptions) -> Result<Directory> {
+        self.client.block_on(self.inner.list_packages_with(options))
+    }
+
+    pub fn source_info(&self, options: &SourceInfoOptions) -> Result<SourceInfoList> {
+        self.client.block_on(self.inner.source_info(options))
+    }
+
+    pub fn meta(&self) -> Result<ProjectMeta> {
+        self.client.block_on(self.inner.meta())
+    }
+
+    pub fn config(&self) -> Result<String> {
+        self.client.block_on(self.inner.config())
+    }
+
+    pub fn set_config(&self, config: &str, comment: Option<&str>) -> Result<()> {
+        self.client.block_on(self.inner.set_config(config, comment))
+    }
+
+    pub fn pubkey(&self) -> Result<String> {
+        self.client.block_on(self.inner.pubkey())
+    }
+
+    #[cfg(feature = "verify")]
+    pub fn keyring(&self) -> Result<crate::verify::Keyring> {
+        self.client.block_on(self.inner.keyring())
+    }
+
+    pub fn keyinfo(&self) -> Result<KeyInfo> {
+        self.client.block_on(self.inner.keyinfo())
+    }
+
+    pub fn create_signkey(&self) -> Result<()> {
+        self.client.block_on(self.inner.create_signkey())
+    }
+
+    pub fn extendkey(&self, days: Option<u32>) -> Result<()> {
+        self.client.block_on(self.inner.extendkey(days))
+    }
+
+    pub fn result(&self) -> Result<ResultList> {
+        self.client.block_on(self.inner.result())
+    }
+
+    pub fn repositories(&self) -> Result<Vec<String>> {
+        self.client.block_on(self.inner.repositories())
+    }
+
+    pub fn arches(&self, repository: &str) -> Result<Vec<String>> {
+        self.client.block_on(self.inner.arches(repository))
+    }
+
+    pub fn rebuild(&self, filters: &RebuildFilters) -> Result<()> {
+        self.client.block_on(self.inner.rebuild(filters))
+    }
+
+    pub fn jobhistory(
+        &self,
+        repository: &str,
+        arch: &str,
+        filters: &JobHistoryFilters,
+    ) -> Result<JobHistList> {
+        self.client
+            .block_on(self.inner.jobhistory(repository, arch, filters))
+    }
+
+    pub fn comments(&self) -> Result<CommentList> {
+        self.client.block_on(self.inner.comments())
+    }
+
+    pub fn create_comment(&self, body: &str, parent: Option<u64>) -> Result<()> {
+        self.client
+            .block_on(self.inner.create_comment(body, parent))
+    }
+
+    pub fn add_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client.block_on(self.inner.add_role(holder, role))
+    }
+
+    pub fn remove_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client.block_on(self.inner.remove_role(holder, role))
+    }
+
+    pub fn create_maintenance_incident(&self) -> Result<String> {
+        self.client
+            .block_on(self.inner.create_maintenance_incident())
+    }
+
+    pub fn maintenance_incidents(&self) -> Result<Vec<ProjectMeta>> {
+        self.client.block_on(self.inner.maintenance_incidents())
+    }
+
+    pub fn create_maintenance_release_request(&self, description: &str) -> Result<BsRequest> {
+        self.client
+            .block_on(self.inner.create_maintenance_release_request(description))
+    }
+
+    pub fn create_patchinfo(&self, options: &PatchinfoOptions) -> Result<String> {
+        self.client.block_on(self.inner.create_patchinfo(options))
+    }
+
+    pub fn lock(&self, comment: Option<&str>) -> Result<()> {
+        self.client.block_on(self.inner.lock(comment))
+    }
+
+    pub fn unlock(&self, comment: &str) -> Result<bool> {
+        self.client.block_on(self.inner.unlock(comment))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TriggerClient {
+    client: Client,
+    inner: crate::TriggerClient,
+}
+
+impl TriggerClient {
+    /// Creates a client using the given token and default settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime can't be initialized.
+    pub fn new(url: Url, token: String) -> Result<Self> {
+        Ok(Self::from_async(crate::TriggerClient::new(url, token)?))
+    }
+
+    /// Wraps an async trigger client.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the runtime can't be initialized.
+    pub fn from_async(inner: crate::TriggerClient) -> Self {
+        Self {
+            client: Client::from_async(inner.client().clone()),
+            inner,
+        }
+    }
+
+    pub fn as_async(&self) -> &crate::TriggerClient {
+        &self.inner
+    }
+
+    pub fn runservice(&self, options: &TriggerOptions) -> Result<()> {
+        self.client.block_on(self.inner.runservice(options))
+    }
+
+    pub fn rebuild(&self, options: &TriggerOptions) -> Result<()> {
+        self.client.block_on(self.inner.rebuild(options))
+    }
+
+    pub fn release(&self, options: &TriggerOptions) -> Result<()> {
+        self.client.block_on(self.inner.release(options))
+    }
+
+    pub fn workflow(&self, token_id: u64, scm: Scm, event: &str, payload: String) -> Result<()> {
+        self.client
+            .block_on(self.inner.workflow(token_id, scm, event, payload))
+    }
+}
+
+pub struct BsRequestBuilder<'a> {
+    client: &'a Client,
+    inner: crate::BsRequestBuilder<'a>,
+}
+
+impl BsRequestBuilder<'_> {
+    pub fn id(&self) -> u64 {
+        self.inner.id()
+    }
+
+    pub fn comments(&self) -> Result<CommentList> {
+        self.client.block_on(self.inner.comments())
+    }
+
+    pub fn create_comment(&self, body: &str, parent: Option<u64>) -> Result<()> {
+        self.client
+            .block_on(self.inner.create_comment(body, parent))
+    }
+}
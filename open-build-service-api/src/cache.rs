@@ -0,0 +1,200 @@
+//! On-disk cache of downloaded source files and binaries, see
+//! [`ClientBuilder::cache`](crate::ClientBuilder::cache).
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use futures::prelude::*;
+use md5::{Digest, Md5};
+use tokio::io::AsyncWriteExt;
+
+use crate::upload::read_chunks;
+use crate::{Error, Result};
+
+/// A content-addressed cache directory, which can be shared between
+/// processes. Source files are keyed by their md5, binaries by their path,
+/// mtime and size.
+///
+/// Once the cached files take up more than the size limit, the least
+/// recently used ones are evicted.
+#[derive(Debug)]
+pub struct Cache {
+    dir: PathBuf,
+    max_size: u64,
+    /// Running total of the size of the cached files, `None` until the
+    /// directory was scanned. Files cached by other processes are only
+    /// counted once it's scanned again, which happens whenever the total
+    /// goes over the limit.
+    size: tokio::sync::Mutex<Option<u64>>,
+    counter: AtomicU64,
+}
+
+fn file_stream(file: tokio::fs::File) -> impl Stream<Item = Result<Bytes>> + Send + 'static {
+    read_chunks(file).map_err(Error::from)
+}
+
+impl Cache {
+    pub fn new<P: Into<PathBuf>>(dir: P, max_size: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size,
+            size: tokio::sync::Mutex::new(None),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn max_size(&self) -> u64 {
+        self.max_size
+    }
+
+    /// The path for a source file, or `None` if `md5` doesn't look like one,
+    /// so whatever the server lists can't point outside the cache or make
+    /// different files share an entry.
+    pub(crate) fn source_path(&self, md5: &str) -> Option<PathBuf> {
+        let valid = md5.len() == 32 && md5.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'));
+        valid.then(|| self.dir.join("sources").join(md5))
+    }
+
+    pub(crate) fn binary_path(&self, path: &str, mtime: u64, size: u64) -> PathBuf {
+        let key = Md5::digest(format!("{path}\n{mtime}\n{size}"));
+        self.dir
+            .join("binaries")
+            .join(base16ct::lower::encode_string(&key))
+    }
+
+    /// Returns the cached contents, if any, marking them as recently used.
+    pub(crate) async fn get(
+        &self,
+        path: &Path,
+    ) -> Result<Option<impl Stream<Item = Result<Bytes>> + Send + 'static + use<>>> {
+        let file = match tokio::fs::File::open(path).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        // The mtime tracks when an entry was last used; failing to update it
+        // only makes it more likely to be evicted.
+        if let Ok(touch) = tokio::fs::File::options().append(true).open(path).await {
+            let _ = touch.into_std().await.set_modified(SystemTime::now());
+        }
+
+        Ok(Some(file_stream(file)))
+    }
+
+    /// Stores the contents of `stream` and returns them from the cache. If
+    /// `md5` is given, contents not matching it are rejected with
+    /// [`Error::ChecksumMismatch`] rather than stored.
+    pub(crate) async fn insert<S>(
+        &self,
+        path: &Path,
+        stream: S,
+        md5: Option<&str>,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + Send + 'static + use<S>>
+    where
+        S: Stream<Item = Result<Bytes>>,
+    {
+        let parent = path.parent().ok_or(Error::UnexpectedResult)?;
+        tokio::fs::create_dir_all(parent).await?;
+
+        // Other processes may be filling in the same entry, so write to a
+        // unique temporary file first and move it in place once complete.
+        let tmp = parent.join(format!(
+            ".tmp-{}-{}",
+            std::process::id(),
+            self.counter.fetch_add(1, Ordering::Relaxed)
+        ));
+        let written = async {
+            let mut file = tokio::fs::File::create(&tmp).await?;
+            let mut digest = Md5::new();
+            let mut len = 0;
+            let mut stream = std::pin::pin!(stream);
+            while let Some(chunk) = stream.try_next().await? {
+                digest.update(&chunk);
+                len += chunk.len() as u64;
+                file.write_all(&chunk).await?;
+            }
+            file.flush().await?;
+            // Set explicitly rather than left to the filesystem, whose clock
+            // can lag behind the one used for marking entries as used.
+            file.into_std().await.set_modified(SystemTime::now())?;
+
+            if let Some(md5) = md5 {
+                let actual = base16ct::lower::encode_string(&digest.finalize());
+                if md5 != actual {
+                    return Err(Error::ChecksumMismatch(md5.to_owned(), actual));
+                }
+            }
+            tokio::fs::rename(&tmp, path).await?;
+            Ok(len)
+        }
+        .await;
+        let len = match written {
+            Ok(len) => len,
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&tmp).await;
+                return Err(e);
+            }
+        };
+
+        let file = tokio::fs::File::open(path).await?;
+        self.added(len).await?;
+        Ok(file_stream(file))
+    }
+
+    /// Counts a newly cached file, evicting others if that may have taken
+    /// the cache over the limit.
+    async fn added(&self, len: u64) -> Result<()> {
+        let mut size = self.size.lock().await;
+        *size = match *size {
+            Some(total) if total + len <= self.max_size => Some(total + len),
+            _ => Some(self.evict().await?),
+        };
+        Ok(())
+    }
+
+    /// Scans the cache and evicts the least recently used files until it's
+    /// within the limit, returning the size left.
+    async fn evict(&self) -> Result<u64> {
+        let mut entries = Vec::new();
+        let mut total = 0;
+        for kind in ["sources", "binaries"] {
+            let mut dir = match tokio::fs::read_dir(self.dir.join(kind)).await {
+                Ok(dir) => dir,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            while let Some(entry) = dir.next_entry().await? {
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata().await else {
+                    continue;
+                };
+                if metadata.is_file() {
+                    total += metadata.len();
+                    let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    entries.push((used, metadata.len(), entry.path()));
+                }
+            }
+        }
+
+        entries.sort();
+        for (_, size, path) in entries {
+            if total <= self.max_size {
+                break;
+            }
+            // Another process may have evicted it already.
+            let _ = tokio::fs::remove_file(path).await;
+            total -= size;
+        }
+
+        Ok(total)
+    }
+}
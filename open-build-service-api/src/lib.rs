@@ -7,16 +7,67 @@ use md5::{Digest, Md5};
 use quick_xml::SeError;
 use quick_xml::name::QName;
 use quick_xml::{de::DeError, events::Event};
-use reqwest::{Body, Method, RequestBuilder, Response, header::CONTENT_TYPE};
+use reqwest::cookie::{CookieStore, Jar};
+use reqwest::header::{CONTENT_TYPE, COOKIE, HeaderMap, HeaderName, HeaderValue, SET_COOKIE};
+use reqwest::{Body, Method, RequestBuilder, Response, StatusCode};
 use serde::de::IntoDeserializer;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::Duration;
 use strum_macros::Display;
 use thiserror::Error;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 use url::Url;
 
+mod auth;
+pub use auth::Authentication;
+
+mod cache;
+pub use cache::Cache;
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod transport;
+pub use transport::Transport;
+
+mod upload;
+use upload::{HashingBody, matches_pattern, md5_path};
+pub use upload::{UploadOptions, UploadProgress};
+
+pub mod osc;
+
+pub mod prjconf;
+
+mod trigger;
+pub use trigger::{Scm, TriggerClient, TriggerOptions};
+
+mod patchinfo;
+pub use patchinfo::{
+    PATCHINFO_FILE, PatchCategory, PatchIssue, PatchRating, Patchinfo, PatchinfoOptions,
+    ReleaseTarget,
+};
+
+mod meta;
+
+mod traits;
+pub use traits::{BuildApi, ObsApi, PackageApi, ProjectApi};
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+#[cfg(feature = "verify")]
+pub mod verify;
+
+#[cfg(feature = "workflow")]
+pub mod workflow;
+
+pub use reqwest;
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("Request failed: {0}")]
@@ -26,29 +77,164 @@ pub enum Error {
     #[error("Request serialization failed: {0}")]
     SeError(#[from] SeError),
     #[error("{0}")]
-    ApiError(ApiError),
+    ApiError(Box<ApiError>),
+    #[error("{0}")]
+    HttpError(Box<HttpError>),
+    #[error("I/O error: {0}")]
+    IoError(#[from] std::io::Error),
     #[error("Unexpected result")]
     UnexpectedResult,
     #[error("Invalid client url")]
     InvalidUrl,
+    #[error("Authentication failed: {0}")]
+    AuthenticationError(String),
+    #[error("The meta kept being changed concurrently")]
+    Conflict,
+    #[error("File '{0}' to keep is no longer on the server")]
+    MissingRemoteFile(String),
+    #[error("Files still missing after the last commit attempt: {}", .0.join(", "))]
+    CommitIncomplete(Vec<String>),
+    #[error("Checksum mismatch: expected md5 {0}, got {1}")]
+    ChecksumMismatch(String, String),
+    #[error("Working copy belongs to {0}, not to {1}")]
+    ApiUrlMismatch(String, String),
+    #[error("Invalid file name '{0}'")]
+    InvalidFileName(String),
+    /// A failed signature verification. This is always there, so the
+    /// variants don't depend on the features enabled; with the `verify`
+    /// feature, the source is a [`verify::VerifyError`].
+    #[error("Signature verification failed: {0}")]
+    Verify(Box<dyn std::error::Error + Send + Sync>),
+}
+
+#[cfg(feature = "verify")]
+impl From<verify::VerifyError> for Error {
+    fn from(e: verify::VerifyError) -> Self {
+        Error::Verify(Box::new(e))
+    }
+}
+
+impl Error {
+    /// HTTP status of the response that caused the error, if any.
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Error::ApiError(e) => Some(e.status),
+            Error::HttpError(e) => Some(e.status),
+            Error::RequestError(e) => e.status(),
+            _ => None,
+        }
+    }
+
+    /// The error reported by OBS in the response body, if any.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self {
+            Error::ApiError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    pub fn is_not_found(&self) -> bool {
+        self.status() == Some(StatusCode::NOT_FOUND)
+    }
+
+    pub fn is_permission_denied(&self) -> bool {
+        self.status() == Some(StatusCode::FORBIDDEN)
+    }
+
+    /// Whether the error is likely transient, so the request could succeed
+    /// when retried.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::RequestError(e) if retry::is_transient_error(e) => true,
+            _ => self.status().is_some_and(retry::is_transient_status),
+        }
+    }
 }
 
 #[derive(Clone, Deserialize, Debug)]
-pub struct ApiErrorSummary {
-    #[serde(rename = "$value")]
-    pub summary: String,
+pub struct ApiErrorData {
+    #[serde(rename = "@name")]
+    pub name: String,
+    #[serde(rename = "$text", default)]
+    pub value: String,
 }
 
-#[derive(Clone, Deserialize, Debug)]
-pub struct ApiError {
+// The <status> document OBS sends along with errors.
+#[derive(Deserialize, Debug)]
+struct ApiStatus {
     #[serde(rename = "@code")]
+    code: String,
+    #[serde(default)]
+    summary: String,
+    details: Option<String>,
+    #[serde(default)]
+    data: Vec<ApiErrorData>,
+}
+
+impl ApiStatus {
+    /// Value of the `<data>` element with the given name, which successful
+    /// commands use to return results.
+    fn data(&self, name: &str) -> Result<&str> {
+        self.data
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.value.as_str())
+            .ok_or(Error::UnexpectedResult)
+    }
+}
+
+/// An error response with a `<status>` body.
+#[derive(Clone, Debug)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub method: Method,
+    pub url: Url,
     pub code: String,
-    pub summary: ApiErrorSummary,
+    pub summary: String,
+    pub details: Option<String>,
+    pub data: Vec<ApiErrorData>,
+}
+
+impl ApiError {
+    /// Value of the `<data>` element with the given name.
+    pub fn data(&self, name: &str) -> Option<&str> {
+        self.data
+            .iter()
+            .find(|d| d.name == name)
+            .map(|d| d.value.as_str())
+    }
 }
 
 impl std::fmt::Display for ApiError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
-        write!(f, "{}: {}", self.code, self.summary.summary)
+        write!(
+            f,
+            "{} {} failed with {}: {}: {}",
+            self.method, self.url, self.status, self.code, self.summary
+        )?;
+        if let Some(details) = &self.details {
+            write!(f, " ({details})")?;
+        }
+        Ok(())
+    }
+}
+
+/// An error response without a (valid) `<status>` body, e.g. from a proxy.
+#[derive(Clone, Debug)]
+pub struct HttpError {
+    pub status: StatusCode,
+    pub method: Method,
+    pub url: Url,
+    pub body: String,
+}
+
+impl std::fmt::Display for HttpError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{} {} failed with {}",
+            self.method, self.url, self.status
+        )
     }
 }
 
@@ -87,10 +273,77 @@ pub struct RepositoryMeta {
     pub arches: Vec<String>,
 }
 
+#[derive(Clone, Deserialize, Debug)]
+pub struct PersonRole {
+    #[serde(rename = "@userid")]
+    pub userid: String,
+    #[serde(rename = "@role")]
+    pub role: Role,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct GroupRole {
+    #[serde(rename = "@groupid")]
+    pub groupid: String,
+    #[serde(rename = "@role")]
+    pub role: Role,
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Default, Eq, PartialEq, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ProjectKind {
+    #[default]
+    Standard,
+    /// A project maintenance incidents are created below, see
+    /// [`ProjectBuilder::create_maintenance_incident`].
+    Maintenance,
+    MaintenanceIncident,
+    /// A project updates are released to.
+    MaintenanceRelease,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct MaintainedProject {
+    #[serde(rename = "@project")]
+    pub project: String,
+}
+
+/// The projects maintained using a maintenance project.
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct MaintenanceMeta {
+    #[serde(default, rename = "maintains")]
+    pub maintains: Vec<MaintainedProject>,
+}
+
+/// Reads the `lock` flag of project or package meta, which is set by
+/// `<lock><enable/></lock>`.
+fn deserialize_lock<'de, D>(deserializer: D) -> std::result::Result<bool, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    struct LockFlag {
+        enable: Option<()>,
+    }
+
+    Ok(LockFlag::deserialize(deserializer)?.enable.is_some())
+}
+
 #[derive(Deserialize, Debug)]
 pub struct ProjectMeta {
     #[serde(rename = "@name")]
     pub name: String,
+    #[serde(default, rename = "@kind")]
+    pub kind: ProjectKind,
+    pub maintenance: Option<MaintenanceMeta>,
+    /// Whether the project is locked, see [`ProjectBuilder::lock`].
+    #[serde(default, rename = "lock", deserialize_with = "deserialize_lock")]
+    pub locked: bool,
+    #[serde(default, rename = "person")]
+    pub persons: Vec<PersonRole>,
+    #[serde(default, rename = "group")]
+    pub groups: Vec<GroupRole>,
     #[serde(default, rename = "repository")]
     pub repositories: Vec<RepositoryMeta>,
 }
@@ -364,6 +617,47 @@ pub struct CommitOptions {
     pub comment: Option<String>,
 }
 
+/// What [`PackageBuilder::commit_directory`] does with files in the package
+/// that aren't in the local directory.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteFiles {
+    Keep,
+    Delete,
+}
+
+#[derive(Clone, Debug)]
+pub struct CommitDirectoryOptions {
+    pub comment: Option<String>,
+    /// Patterns (supporting `*` and `?`) file names must match one of to be
+    /// committed; all files are included if empty.
+    pub include: Vec<String>,
+    /// Patterns of file names to skip, even if included.
+    pub exclude: Vec<String>,
+    pub remote_files: RemoteFiles,
+    pub upload: UploadOptions,
+    /// Number of commits to try before giving up on files the server still
+    /// reports as missing.
+    pub max_attempts: usize,
+}
+
+impl CommitDirectoryOptions {
+    pub fn new(remote_files: RemoteFiles) -> Self {
+        Self {
+            comment: None,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            remote_files,
+            upload: UploadOptions::default(),
+            max_attempts: 3,
+        }
+    }
+
+    fn includes(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| matches_pattern(p, name)))
+            && !self.exclude.iter().any(|p| matches_pattern(p, name))
+    }
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct BranchOptions {
     pub target_project: Option<String>,
@@ -376,6 +670,33 @@ pub struct BranchOptions {
     pub add_repositories_block: Option<BlockMode>,
 }
 
+impl BranchOptions {
+    fn append_query(&self, u: &mut Url) {
+        let mut query = u.query_pairs_mut();
+        if let Some(target_project) = &self.target_project {
+            query.append_pair("target_project", target_project);
+        }
+        if let Some(target_package) = &self.target_package {
+            query.append_pair("target_package", target_package);
+        }
+        if let Some(comment) = &self.comment {
+            query.append_pair("comment", comment);
+        }
+        if let Some(rebuild) = &self.add_repositories_rebuild {
+            query.append_pair("add_repositories_rebuild", &rebuild.to_string());
+        }
+        if let Some(block) = &self.add_repositories_block {
+            query.append_pair("add_repositories_block", &block.to_string());
+        }
+        if self.force {
+            query.append_pair("force", "1");
+        }
+        if self.missingok {
+            query.append_pair("missingok", "1");
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct BranchStatus {
     pub source_project: String,
@@ -426,6 +747,50 @@ impl<'de> Deserialize<'de> for BranchStatus {
     }
 }
 
+#[derive(Deserialize, Debug)]
+struct MaintenanceBranchTarget {
+    #[serde(rename = "@project")]
+    project: String,
+    #[serde(rename = "@package")]
+    package: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct MaintenanceBranch {
+    #[serde(rename = "@project")]
+    project: String,
+    #[serde(rename = "@package")]
+    package: String,
+    target: MaintenanceBranchTarget,
+}
+
+// The packages a dry run of a maintenance branch would branch.
+#[derive(Deserialize, Debug)]
+struct MaintenanceBranchCollection {
+    #[serde(default, rename = "package")]
+    packages: Vec<MaintenanceBranch>,
+}
+
+#[derive(Serialize)]
+struct NewBsRequestSource<'a> {
+    #[serde(rename = "@project")]
+    project: &'a str,
+}
+
+#[derive(Serialize)]
+struct NewBsRequestAction<'a> {
+    #[serde(rename = "@type")]
+    action_type: String,
+    source: NewBsRequestSource<'a>,
+}
+
+#[derive(Serialize)]
+#[serde(rename = "request")]
+struct NewBsRequest<'a> {
+    action: NewBsRequestAction<'a>,
+    description: &'a str,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct PackageBuildMetaDisable {
     #[serde(default, rename = "@repository")]
@@ -446,6 +811,15 @@ pub struct PackageMeta {
     pub name: String,
     #[serde(rename = "@project")]
     pub project: String,
+    #[serde(default, rename = "person")]
+    pub persons: Vec<PersonRole>,
+    #[serde(default, rename = "group")]
+    pub groups: Vec<GroupRole>,
+    /// Whether the package itself is locked, see [`PackageBuilder::lock`].
+    /// This is false for a package that is only locked because its project
+    /// is; check [`ProjectMeta::locked`] for that.
+    #[serde(default, rename = "lock", deserialize_with = "deserialize_lock")]
+    pub locked: bool,
     #[serde(default)]
     pub build: PackageBuildMeta,
 }
@@ -500,6 +874,10 @@ pub struct BinaryList {
 pub struct DirectoryEntry {
     #[serde(rename = "@name")]
     pub name: String,
+    /// Project the package comes from, for packages of linked projects in
+    /// expanded listings.
+    #[serde(rename = "@originproject")]
+    pub originproject: Option<String>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -508,132 +886,599 @@ pub struct Directory {
     pub entries: Vec<DirectoryEntry>,
 }
 
-#[derive(Clone, Debug)]
-pub struct RebuildFilters {
-    packages: Vec<String>,
+#[derive(Clone, Debug, Default)]
+pub struct ProjectListOptions {
+    /// Only list projects whose name starts with this. The filtering is done
+    /// locally, as OBS always returns the full list.
+    pub prefix: Option<String>,
 }
 
-impl RebuildFilters {
-    pub fn empty() -> Self {
-        RebuildFilters {
-            packages: Vec::new(),
-        }
-    }
-
-    pub fn only_package(package: String) -> Self {
-        RebuildFilters::empty().package(package)
-    }
-
-    pub fn add_package(&mut self, package: String) {
-        self.packages.push(package);
-    }
-
-    pub fn package(mut self, package: String) -> Self {
-        self.add_package(package);
-        self
-    }
+#[derive(Clone, Debug, Default)]
+pub struct PackageListOptions {
+    /// Include packages of the projects this one links to.
+    pub expand: bool,
+    /// List deleted packages instead of existing ones.
+    pub deleted: bool,
 }
 
 #[derive(Clone, Debug, Default)]
-pub struct JobHistoryFilters {
-    packages: Vec<String>,
-    codes: Vec<PackageCode>,
-    limit: Option<usize>,
+pub struct SourceInfoOptions {
+    /// Parse the build recipes, to fill in name, version and release.
+    pub parse: bool,
+    /// Include packages of the projects this one links to. Only applies to
+    /// project listings.
+    pub expand: bool,
+    /// Repository and architecture to pick the build recipe for, for
+    /// packages with several of them.
+    pub repository: Option<String>,
+    pub arch: Option<String>,
 }
 
-impl JobHistoryFilters {
-    pub fn empty() -> Self {
-        Self::default()
-    }
-
-    pub fn only_package(package: String) -> Self {
-        JobHistoryFilters::empty().package(package)
-    }
-
-    pub fn add_package(&mut self, package: String) {
-        self.packages.push(package);
-    }
-
-    pub fn add_code(&mut self, code: PackageCode) {
-        self.codes.push(code);
-    }
-
-    pub fn set_limit(&mut self, limit: Option<usize>) {
-        self.limit = limit;
-    }
-
-    pub fn package(mut self, package: String) -> Self {
-        self.add_package(package);
-        self
-    }
-
-    pub fn code(mut self, code: PackageCode) -> Self {
-        self.add_code(code);
-        self
+impl SourceInfoOptions {
+    fn append_to(&self, u: &mut Url) {
+        let mut query = u.query_pairs_mut();
+        query.append_pair("view", "info");
+        if self.parse {
+            query.append_pair("parse", "1");
+        }
+        if let Some(repository) = &self.repository {
+            query.append_pair("repository", repository);
+        }
+        if let Some(arch) = &self.arch {
+            query.append_pair("arch", arch);
+        }
     }
+}
 
-    pub fn limit(mut self, limit: Option<usize>) -> Self {
-        self.set_limit(limit);
-        self
-    }
+#[derive(Clone, Deserialize, Debug)]
+pub struct SourceInfoLink {
+    #[serde(rename = "@project")]
+    pub project: String,
+    #[serde(rename = "@package")]
+    pub package: String,
 }
 
-#[derive(Deserialize, Debug)]
-pub struct JobHist {
+#[derive(Clone, Deserialize, Debug)]
+pub struct SourceInfo {
     #[serde(rename = "@package")]
     pub package: String,
     #[serde(rename = "@rev")]
-    pub rev: String,
+    pub rev: Option<String>,
+    #[serde(rename = "@vrev")]
+    pub vrev: Option<String>,
     #[serde(rename = "@srcmd5")]
-    pub srcmd5: String,
-    #[serde(rename = "@versrel")]
-    pub versrel: String,
-    #[serde(rename = "@bcnt")]
-    pub bcnt: String,
-    #[serde(rename = "@readytime")]
-    pub readytime: u64,
-    #[serde(rename = "@starttime")]
-    pub starttime: u64,
-    #[serde(rename = "@endtime")]
-    pub endtime: u64,
-    #[serde(rename = "@code")]
-    pub code: PackageCode,
-    #[serde(rename = "@uri")]
-    pub uri: String,
-    #[serde(rename = "@workerid")]
-    pub workerid: String,
-    #[serde(rename = "@hostarch")]
-    pub hostarch: String,
-    #[serde(rename = "@reason")]
-    pub reason: String,
+    pub srcmd5: Option<String>,
+    #[serde(rename = "@lsrcmd5")]
+    pub lsrcmd5: Option<String>,
     #[serde(rename = "@verifymd5")]
-    pub verifymd5: String,
-}
-
-#[derive(Deserialize, Debug)]
-pub struct JobHistList {
-    #[serde(default)]
-    pub jobhist: Vec<JobHist>,
+    pub verifymd5: Option<String>,
+    /// Build recipe, e.g. the spec or dsc file.
+    pub filename: Option<String>,
+    /// Why the sources couldn't be expanded or parsed, e.g. for broken links.
+    pub error: Option<String>,
+    pub name: Option<String>,
+    pub version: Option<String>,
+    pub release: Option<String>,
+    /// Packages this one links to, directly or indirectly.
+    #[serde(default, rename = "linked")]
+    pub linked: Vec<SourceInfoLink>,
 }
 
-#[derive(Deserialize, Debug)]
-struct LogEntryEntry {
-    #[serde(rename = "@size")]
-    size: usize,
-    #[serde(rename = "@mtime")]
-    mtime: u64,
+#[derive(Clone, Deserialize, Debug)]
+pub struct SourceInfoList {
+    #[serde(default, rename = "sourceinfo")]
+    pub sourceinfos: Vec<SourceInfo>,
 }
 
-#[derive(Deserialize, Debug)]
-struct LogEntry {
-    #[serde(rename = "entry")]
-    pub entries: Vec<LogEntryEntry>,
+#[derive(Clone, Deserialize, Debug)]
+pub struct PubKeyInfo {
+    #[serde(rename = "@keyid")]
+    pub keyid: String,
+    #[serde(rename = "@userid")]
+    pub userid: String,
+    #[serde(rename = "@algo")]
+    pub algo: String,
+    #[serde(rename = "@keysize")]
+    pub keysize: Option<u32>,
+    /// Curve of elliptic curve keys.
+    #[serde(rename = "@curve")]
+    pub curve: Option<String>,
+    /// Expiry as a unix timestamp, if the key expires.
+    #[serde(rename = "@expires")]
+    pub expires: Option<u64>,
+    #[serde(rename = "@fingerprint")]
+    pub fingerprint: String,
+    /// The ASCII armored key.
+    #[serde(rename = "$text", default)]
+    pub key: String,
 }
 
-enum PackageLogRequest {
-    Initial,
-    Request(BoxFuture<'static, Result<Response>>),
-    Stream((BoxStream<'static, reqwest::Result<Bytes>>, bool)),
+#[derive(Clone, Deserialize, Debug)]
+pub struct SslCertInfo {
+    #[serde(rename = "@serial")]
+    pub serial: String,
+    #[serde(rename = "@issuer")]
+    pub issuer: String,
+    #[serde(rename = "@subject")]
+    pub subject: String,
+    #[serde(rename = "@notbefore")]
+    pub notbefore: Option<String>,
+    #[serde(rename = "@notafter")]
+    pub notafter: Option<String>,
+    #[serde(rename = "@keyid")]
+    pub keyid: Option<String>,
+    #[serde(rename = "@fingerprint")]
+    pub fingerprint: Option<String>,
+    /// The PEM encoded certificate.
+    #[serde(rename = "$text", default)]
+    pub cert: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct KeyInfo {
+    #[serde(rename = "@project")]
+    pub project: String,
+    pub pubkey: Option<PubKeyInfo>,
+    pub sslcert: Option<SslCertInfo>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct Comment {
+    #[serde(rename = "@id")]
+    pub id: u64,
+    #[serde(rename = "@who")]
+    pub who: String,
+    /// Creation time as formatted by OBS, e.g. `2024-04-03 10:11:12 UTC`.
+    #[serde(rename = "@when")]
+    pub when: String,
+    /// The comment this one replies to.
+    #[serde(rename = "@parent")]
+    pub parent: Option<u64>,
+    #[serde(rename = "$text", default)]
+    pub body: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct CommentList {
+    #[serde(default, rename = "comment")]
+    pub comments: Vec<Comment>,
+}
+
+#[derive(Clone, Deserialize, Debug, Eq, PartialEq, Display)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum BsRequestActionType {
+    Submit,
+    Delete,
+    ChangeDevel,
+    AddRole,
+    SetBugowner,
+    MaintenanceIncident,
+    MaintenanceRelease,
+    Release,
+    Group,
+    /// An action type not listed here, e.g. one added in a newer OBS.
+    #[serde(untagged)]
+    #[strum(to_string = "{0}")]
+    Other(String),
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Eq, PartialEq, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum BsRequestStateName {
+    New,
+    Review,
+    Accepted,
+    Declined,
+    Revoked,
+    Superseded,
+    Deleted,
+}
+
+/// The source or target of a request action.
+#[derive(Clone, Deserialize, Debug)]
+pub struct BsRequestEndpoint {
+    #[serde(rename = "@project")]
+    pub project: String,
+    #[serde(rename = "@package")]
+    pub package: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct BsRequestAction {
+    #[serde(rename = "@type")]
+    pub action_type: BsRequestActionType,
+    pub source: Option<BsRequestEndpoint>,
+    pub target: Option<BsRequestEndpoint>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct BsRequestState {
+    #[serde(rename = "@name")]
+    pub name: BsRequestStateName,
+    #[serde(rename = "@who")]
+    pub who: String,
+    #[serde(rename = "@when")]
+    pub when: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct BsRequest {
+    #[serde(rename = "@id")]
+    pub id: u64,
+    #[serde(rename = "@creator")]
+    pub creator: String,
+    #[serde(default, rename = "action")]
+    pub actions: Vec<BsRequestAction>,
+    pub state: BsRequestState,
+    #[serde(default)]
+    pub description: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct Person {
+    pub login: String,
+    pub email: Option<String>,
+    pub realname: Option<String>,
+    pub state: Option<String>,
+    #[serde(default, rename = "globalrole")]
+    pub globalroles: Vec<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct GroupUser {
+    #[serde(rename = "@userid")]
+    pub userid: String,
+}
+
+#[derive(Clone, Deserialize, Debug, Default)]
+pub struct GroupMembers {
+    #[serde(default, rename = "person")]
+    pub persons: Vec<GroupUser>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct Group {
+    pub title: String,
+    pub email: Option<String>,
+    #[serde(default, rename = "maintainer")]
+    pub maintainers: Vec<GroupUser>,
+    #[serde(default, rename = "person")]
+    pub members: GroupMembers,
+}
+
+#[derive(Clone, Deserialize, Debug, Eq, PartialEq, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum Role {
+    Maintainer,
+    Bugowner,
+    Reviewer,
+    Downloader,
+    Reader,
+    /// A role not listed here, e.g. one defined by the OBS instance.
+    #[serde(untagged)]
+    #[strum(to_string = "{0}")]
+    Other(String),
+}
+
+/// A user or group roles are given to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum RoleHolder {
+    User(String),
+    Group(String),
+}
+
+impl RoleHolder {
+    /// Element, attribute and value the holder is given by in meta.
+    fn xml(&self) -> (&'static str, &'static str, &str) {
+        match self {
+            RoleHolder::User(user) => ("person", "userid", user),
+            RoleHolder::Group(group) => ("group", "groupid", group),
+        }
+    }
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct ProjectId {
+    #[serde(rename = "@name")]
+    pub name: String,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct PackageId {
+    #[serde(rename = "@project")]
+    pub project: String,
+    #[serde(rename = "@name")]
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProjectIdCollection {
+    #[serde(default, rename = "project")]
+    projects: Vec<ProjectId>,
+}
+
+#[derive(Deserialize, Debug)]
+struct PackageIdCollection {
+    #[serde(default, rename = "package")]
+    packages: Vec<PackageId>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ProjectMetaCollection {
+    #[serde(default, rename = "project")]
+    projects: Vec<ProjectMeta>,
+}
+
+/// Quotes `value` as an XPath string literal. XPath has no escapes, so a
+/// value containing both kinds of quotes is pieced together with `concat()`.
+fn xpath_string(value: &str) -> String {
+    if !value.contains('\'') {
+        format!("'{value}'")
+    } else if !value.contains('"') {
+        format!("\"{value}\"")
+    } else {
+        let parts: Vec<_> = value.split('\'').map(|part| format!("'{part}'")).collect();
+        format!("concat({})", parts.join(", \"'\", "))
+    }
+}
+
+/// Projects and packages a user or group has roles in.
+#[derive(Clone, Debug)]
+pub struct Involvement {
+    pub projects: Vec<ProjectId>,
+    pub packages: Vec<PackageId>,
+}
+
+/// What an API token can be used for.
+#[derive(Clone, Deserialize, Debug, Eq, PartialEq, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum TokenKind {
+    Runservice,
+    Rebuild,
+    Release,
+    Workflow,
+    /// Tokens for the RSS feed of a user, which can't be created using
+    /// [`Client::create_token`].
+    Rss,
+    /// A kind not listed here, e.g. one added in a newer OBS version.
+    #[serde(untagged)]
+    #[strum(to_string = "{0}")]
+    Other(String),
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct Token {
+    #[serde(rename = "@id")]
+    pub id: u64,
+    #[serde(rename = "@string")]
+    pub string: String,
+    #[serde(rename = "@kind")]
+    pub kind: TokenKind,
+    #[serde(default, rename = "@description")]
+    pub description: String,
+    #[serde(rename = "@triggered_at")]
+    pub triggered_at: Option<String>,
+    #[serde(rename = "@project")]
+    pub project: Option<String>,
+    #[serde(rename = "@package")]
+    pub package: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct TokenList {
+    #[serde(default, rename = "entry")]
+    pub tokens: Vec<Token>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct TokenOptions {
+    /// Scopes the token to a package, which requires both the project and
+    /// the package to be given.
+    pub project: Option<String>,
+    pub package: Option<String>,
+    pub description: Option<String>,
+    /// The token workflows use to report back to the SCM, required for
+    /// [`TokenKind::Workflow`].
+    pub scm_token: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CreatedToken {
+    pub id: u64,
+    pub string: String,
+}
+
+#[derive(Clone, Copy, Deserialize, Debug, Eq, PartialEq, Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum WorkflowRunStatus {
+    Running,
+    Success,
+    Fail,
+}
+
+/// A run of the workflows, as triggered by a webhook.
+#[derive(Clone, Deserialize, Debug)]
+pub struct WorkflowRun {
+    #[serde(rename = "@id")]
+    pub id: u64,
+    pub status: WorkflowRunStatus,
+    pub hook_event: Option<String>,
+    pub hook_action: Option<String>,
+    pub generic_event_type: Option<String>,
+    pub scm_vendor: Option<String>,
+    pub repository_name: Option<String>,
+    pub repository_owner: Option<String>,
+    /// The pull request number or commit the run is for.
+    pub event_source_name: Option<String>,
+    pub request_headers: Option<String>,
+    pub request_payload: Option<String>,
+    /// What OBS reported back to the SCM.
+    pub response_body: Option<String>,
+    pub workflow_configuration_path: Option<String>,
+    pub workflow_configuration_url: Option<String>,
+}
+
+#[derive(Clone, Deserialize, Debug)]
+pub struct WorkflowRunList {
+    #[serde(default, rename = "workflow_run")]
+    pub runs: Vec<WorkflowRun>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct WorkflowRunListOptions {
+    pub status: Option<WorkflowRunStatus>,
+    /// The generic event type, i.e. `pull_request`, `push` or `tag_push`.
+    pub event_type: Option<String>,
+    pub pr_mr: Option<String>,
+    pub commit: Option<String>,
+}
+
+const META_UPDATE_ATTEMPTS: usize = 3;
+
+const DEFAULT_BULK_CONCURRENCY: usize = 8;
+
+#[derive(Clone, Debug, Default)]
+pub struct BulkOptions {
+    /// Packages to operate on, defaulting to all packages in the project.
+    pub packages: Option<Vec<String>>,
+    /// Maximum number of packages to operate on at once, 8 by default.
+    pub concurrency: Option<usize>,
+}
+
+/// Per-package results of a bulk operation, which fail individually.
+pub type PackageResults<T> = BTreeMap<String, Result<T>>;
+
+#[derive(Clone, Debug)]
+pub struct RebuildFilters {
+    packages: Vec<String>,
+}
+
+impl RebuildFilters {
+    pub fn empty() -> Self {
+        RebuildFilters {
+            packages: Vec::new(),
+        }
+    }
+
+    pub fn only_package(package: String) -> Self {
+        RebuildFilters::empty().package(package)
+    }
+
+    pub fn add_package(&mut self, package: String) {
+        self.packages.push(package);
+    }
+
+    pub fn package(mut self, package: String) -> Self {
+        self.add_package(package);
+        self
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JobHistoryFilters {
+    packages: Vec<String>,
+    codes: Vec<PackageCode>,
+    limit: Option<usize>,
+}
+
+impl JobHistoryFilters {
+    pub fn empty() -> Self {
+        Self::default()
+    }
+
+    pub fn only_package(package: String) -> Self {
+        JobHistoryFilters::empty().package(package)
+    }
+
+    pub fn add_package(&mut self, package: String) {
+        self.packages.push(package);
+    }
+
+    pub fn add_code(&mut self, code: PackageCode) {
+        self.codes.push(code);
+    }
+
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+    }
+
+    pub fn package(mut self, package: String) -> Self {
+        self.add_package(package);
+        self
+    }
+
+    pub fn code(mut self, code: PackageCode) -> Self {
+        self.add_code(code);
+        self
+    }
+
+    pub fn limit(mut self, limit: Option<usize>) -> Self {
+        self.set_limit(limit);
+        self
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JobHist {
+    #[serde(rename = "@package")]
+    pub package: String,
+    #[serde(rename = "@rev")]
+    pub rev: String,
+    #[serde(rename = "@srcmd5")]
+    pub srcmd5: String,
+    #[serde(rename = "@versrel")]
+    pub versrel: String,
+    #[serde(rename = "@bcnt")]
+    pub bcnt: String,
+    #[serde(rename = "@readytime")]
+    pub readytime: u64,
+    #[serde(rename = "@starttime")]
+    pub starttime: u64,
+    #[serde(rename = "@endtime")]
+    pub endtime: u64,
+    #[serde(rename = "@code")]
+    pub code: PackageCode,
+    #[serde(rename = "@uri")]
+    pub uri: String,
+    #[serde(rename = "@workerid")]
+    pub workerid: String,
+    #[serde(rename = "@hostarch")]
+    pub hostarch: String,
+    #[serde(rename = "@reason")]
+    pub reason: String,
+    #[serde(rename = "@verifymd5")]
+    pub verifymd5: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct JobHistList {
+    #[serde(default)]
+    pub jobhist: Vec<JobHist>,
+}
+
+#[derive(Deserialize, Debug)]
+struct LogEntryEntry {
+    #[serde(rename = "@size")]
+    size: usize,
+    #[serde(rename = "@mtime")]
+    mtime: u64,
+}
+
+#[derive(Deserialize, Debug)]
+struct LogEntry {
+    #[serde(rename = "entry")]
+    pub entries: Vec<LogEntryEntry>,
+}
+
+enum PackageLogRequest<'a> {
+    Initial,
+    Request(BoxFuture<'a, Result<Response>>),
+    Stream((BoxStream<'static, reqwest::Result<Bytes>>, bool)),
 }
 
 #[derive(Default)]
@@ -647,7 +1492,7 @@ pub struct PackageLogStream<'a> {
     url: Url,
     offset: usize,
     options: PackageLogStreamOptions,
-    request: PackageLogRequest,
+    request: PackageLogRequest<'a>,
 }
 
 impl<'a> PackageLogStream<'a> {
@@ -687,7 +1532,7 @@ impl Stream for PackageLogStream<'_> {
                         Err(e) => return Poll::Ready(Some(Err(e))),
                     };
                     let r = me.client.authenticated_request(Method::GET, u);
-                    let r = Client::send_with_error(r).boxed();
+                    let r = me.client.send_with_error(r).boxed();
                     me.request = PackageLogRequest::Request(r);
                 }
                 PackageLogRequest::Request(ref mut r) => match ready!(r.as_mut().poll(cx)) {
@@ -713,6 +1558,13 @@ impl Stream for PackageLogStream<'_> {
                             if !gotdata || matches!(me.options.end, Some(end) if me.offset >= end) {
                                 return Poll::Ready(None);
                             }
+
+                            #[cfg(feature = "tracing")]
+                            tracing::debug!(
+                                offset = me.offset,
+                                url = me.url.path(),
+                                "reconnecting build log stream"
+                            );
                         }
                     }
                 }
@@ -781,6 +1633,37 @@ impl AsRef<str> for BuildCommand<'_> {
     }
 }
 
+/// Reads the response body, holding on to the request limit permit that
+/// came with the response until it's read.
+async fn response_text(mut response: Response) -> reqwest::Result<String> {
+    let _permit = response
+        .extensions_mut()
+        .remove::<Arc<OwnedSemaphorePermit>>();
+    response.text().await
+}
+
+/// The parameters of a lock command, with the comment only if there is one.
+fn lock_params(comment: Option<&str>) -> Option<(&str, &str)> {
+    comment.map(|comment| ("comment", comment))
+}
+
+/// Turns the result of an unlock command into whether there was a lock. OBS
+/// refuses to unlock what isn't locked, which is checked with `locked`; if
+/// that fails as well, the unlock error is returned.
+async fn unlock_result(
+    result: Result<()>,
+    locked: impl std::future::Future<Output = Result<bool>>,
+) -> Result<bool> {
+    match result {
+        Ok(()) => Ok(true),
+        Err(e) if e.status() == Some(StatusCode::BAD_REQUEST) => match locked.await {
+            Ok(false) => Ok(false),
+            _ => Err(e),
+        },
+        Err(e) => Err(e),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PackageBuilder<'a> {
     pub client: &'a Client,
@@ -832,13 +1715,14 @@ impl<'a> PackageBuilder<'a> {
             u.query_pairs_mut().append_pair("rev", rev);
         }
 
-        Client::send_with_error(
-            self.client
-                .authenticated_request(Method::PUT, u)
-                .header(CONTENT_TYPE, "application/octet-stream")
-                .body(data),
-        )
-        .await?;
+        self.client
+            .send_with_error(
+                self.client
+                    .authenticated_request(Method::PUT, u)
+                    .header(CONTENT_TYPE, "application/octet-stream")
+                    .body(data),
+            )
+            .await?;
 
         Ok(())
     }
@@ -864,14 +1748,44 @@ impl<'a> PackageBuilder<'a> {
         arch: &str,
         file: &str,
     ) -> Result<impl Stream<Item = Result<Bytes>> + use<>> {
-        let u = self.full_request(repository, arch, Some(BuildCommand::DownloadBinary(file)))?;
-        Ok(
-            Client::send_with_error(self.client.authenticated_request(Method::GET, u))
-                .await?
-                .bytes_stream()
-                .map_err(|e| e.into()),
-        )
-    }
+        if let Some(cache) = &self.client.cache {
+            let binaries = self.binaries(repository, arch).await?;
+            if let Some(binary) = binaries.binaries.iter().find(|b| b.filename == file)
+                && binary.size <= cache.max_size()
+            {
+                let key = format!(
+                    "{}/{repository}/{arch}/{}/{file}",
+                    self.project, self.package
+                );
+                let path = cache.binary_path(&key, binary.mtime, binary.size);
+                if let Some(cached) = cache.get(&path).await? {
+                    return Ok(cached.boxed());
+                }
+                let stream = self.download_binary_file(repository, arch, file).await?;
+                return Ok(cache.insert(&path, stream, None).await?.boxed());
+            }
+        }
+
+        Ok(self
+            .download_binary_file(repository, arch, file)
+            .await?
+            .boxed())
+    }
+
+    async fn download_binary_file(
+        &self,
+        repository: &str,
+        arch: &str,
+        file: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + use<>> {
+        let u = self.full_request(repository, arch, Some(BuildCommand::DownloadBinary(file)))?;
+        Ok(self
+            .client
+            .send_with_error(self.client.authenticated_request(Method::GET, u))
+            .await?
+            .bytes_stream()
+            .map_err(|e| e.into()))
+    }
 
     pub async fn binaries(&self, repository: &str, arch: &str) -> Result<BinaryList> {
         let u = self.full_request(repository, arch, None)?;
@@ -888,7 +1802,9 @@ impl<'a> PackageBuilder<'a> {
         u.query_pairs_mut().append_pair("cmd", "rebuild");
         u.query_pairs_mut().append_pair("package", &self.package);
 
-        Client::send_with_error(self.client.authenticated_request(Method::POST, u)).await?;
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::POST, u))
+            .await?;
 
         Ok(())
     }
@@ -924,7 +1840,9 @@ impl<'a> PackageBuilder<'a> {
             .push(&self.project)
             .push(&self.package);
 
-        Client::send_with_error(self.client.authenticated_request(Method::DELETE, u)).await?;
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::DELETE, u))
+            .await?;
 
         Ok(())
     }
@@ -966,18 +1884,56 @@ impl<'a> PackageBuilder<'a> {
         self.client.request(u).await
     }
 
-    pub async fn meta(&self) -> Result<PackageMeta> {
-        let mut u = self.client.base.clone();
-        u.path_segments_mut()
-            .map_err(|_| Error::InvalidUrl)?
-            .push("source")
-            .push(&self.project)
-            .push(&self.package)
-            .push("_meta");
+    /// Source information of the latest revision, without downloading any
+    /// files.
+    pub async fn info(&self, options: &SourceInfoOptions) -> Result<SourceInfo> {
+        let mut u = self.list_url(None)?;
+        options.append_to(&mut u);
         self.client.request(u).await
     }
 
-    pub async fn source_file(&self, file: &str) -> Result<impl Stream<Item = Result<Bytes>>> {
+    pub async fn meta(&self) -> Result<PackageMeta> {
+        self.client.request(self.meta_url()?).await
+    }
+
+    pub async fn source_file(
+        &self,
+        file: &str,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + use<>> {
+        self.source_file_at(file, None).await
+    }
+
+    async fn source_file_at(
+        &self,
+        file: &str,
+        rev: Option<&str>,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + use<>> {
+        if let Some(cache) = &self.client.cache {
+            let directory = self.list(rev).await?;
+            if let Some(entry) = directory.entries.iter().find(|e| e.name == file)
+                && entry.size <= cache.max_size()
+                && let Some(path) = cache.source_path(&entry.md5)
+            {
+                if let Some(cached) = cache.get(&path).await? {
+                    return Ok(cached.boxed());
+                }
+                // Pin the download to the listed revision, so it matches the
+                // md5 even if a commit happens in between.
+                let stream = self
+                    .download_source_file(file, Some(&directory.srcmd5))
+                    .await?;
+                return Ok(cache.insert(&path, stream, Some(&entry.md5)).await?.boxed());
+            }
+        }
+
+        Ok(self.download_source_file(file, rev).await?.boxed())
+    }
+
+    async fn download_source_file(
+        &self,
+        file: &str,
+        rev: Option<&str>,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + use<>> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
@@ -985,12 +1941,15 @@ impl<'a> PackageBuilder<'a> {
             .push(&self.project)
             .push(&self.package)
             .push(file);
-        Ok(
-            Client::send_with_error(self.client.authenticated_request(Method::GET, u))
-                .await?
-                .bytes_stream()
-                .map_err(|e| e.into()),
-        )
+        if let Some(rev) = rev {
+            u.query_pairs_mut().append_pair("rev", rev);
+        }
+        Ok(self
+            .client
+            .send_with_error(self.client.authenticated_request(Method::GET, u))
+            .await?
+            .bytes_stream()
+            .map_err(|e| e.into()))
     }
 
     pub async fn upload_for_commit<T: Into<Body>>(&self, file: &str, data: T) -> Result<()> {
@@ -1005,6 +1964,104 @@ impl<'a> PackageBuilder<'a> {
         Ok(())
     }
 
+    /// Streams `reader` to the server for a later commit, computing the md5
+    /// of the contents on the way rather than buffering them in memory.
+    pub async fn upload_reader_for_commit<R: tokio::io::AsyncRead + Send + 'static>(
+        &self,
+        file: &str,
+        reader: R,
+        options: &UploadOptions,
+    ) -> Result<CommitFileEntry> {
+        let (hashing, body) = HashingBody::new(reader, None, options);
+        self.upload_file(file, Some("repository"), body).await?;
+        Ok(hashing.finish(file.to_owned()))
+    }
+
+    /// Like [`upload_reader_for_commit`](Self::upload_reader_for_commit), but
+    /// streams the contents of a local file, reporting its size as the total
+    /// in progress updates.
+    pub async fn upload_path_for_commit<P: AsRef<Path>>(
+        &self,
+        file: &str,
+        path: P,
+        options: &UploadOptions,
+    ) -> Result<CommitFileEntry> {
+        let local = tokio::fs::File::open(path).await?;
+        let size = local.metadata().await?.len();
+        let (hashing, body) = HashingBody::new(local, Some(size), options);
+        self.upload_file(file, Some("repository"), body).await?;
+        Ok(hashing.finish(file.to_owned()))
+    }
+
+    /// Commits the regular files directly inside `path`, uploading only the
+    /// ones the server doesn't already have.
+    pub async fn commit_directory<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: &CommitDirectoryOptions,
+    ) -> Result<SourceDirectory> {
+        let path = path.as_ref();
+
+        let mut local = BTreeMap::new();
+        let mut dir = tokio::fs::read_dir(path).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if !options.includes(&name) || !tokio::fs::metadata(entry.path()).await?.is_file() {
+                continue;
+            }
+            let md5 = md5_path(&entry.path()).await?;
+            local.insert(name, md5);
+        }
+
+        let mut remote = BTreeMap::new();
+        if options.remote_files == RemoteFiles::Keep {
+            for entry in self.list(None).await?.entries {
+                if !local.contains_key(&entry.name) {
+                    remote.insert(entry.name, entry.md5);
+                }
+            }
+        }
+
+        let commit_options = CommitOptions {
+            comment: options.comment.clone(),
+        };
+        let mut attempts = options.max_attempts.max(1);
+        loop {
+            let mut filelist = CommitFileList::new();
+            let files: BTreeMap<_, _> = local.iter().chain(&remote).collect();
+            for (name, md5) in files {
+                filelist.add_file_md5(name.clone(), md5.clone());
+            }
+
+            let missing = match self.commit(&filelist, &commit_options).await? {
+                CommitResult::Success(directory) => return Ok(directory),
+                CommitResult::MissingEntries(missing) => missing,
+            };
+            attempts -= 1;
+            if attempts == 0 {
+                return Err(Error::CommitIncomplete(
+                    missing.entries.into_iter().map(|e| e.name).collect(),
+                ));
+            }
+            for entry in missing.entries {
+                // Only local files can be uploaded; kept remote files going
+                // missing means the server lost them.
+                if !local.contains_key(&entry.name) {
+                    return Err(Error::MissingRemoteFile(entry.name));
+                }
+                let file = path.join(&entry.name);
+                let uploaded = self
+                    .upload_path_for_commit(&entry.name, &file, &options.upload)
+                    .await?;
+                // Commit whatever was uploaded, in case the file changed since
+                // it was hashed.
+                local.insert(uploaded.name, uploaded.md5);
+            }
+        }
+    }
+
     pub async fn commit(
         &self,
         filelist: &CommitFileList,
@@ -1025,14 +2082,16 @@ impl<'a> PackageBuilder<'a> {
         let mut body = String::new();
         quick_xml::se::to_writer(&mut body, filelist)?;
 
-        let response = Client::send_with_error(
+        let response = response_text(
             self.client
-                .authenticated_request(Method::POST, u)
-                .header(CONTENT_TYPE, "application/xml")
-                .body(body),
+                .send_with_error(
+                    self.client
+                        .authenticated_request(Method::POST, u)
+                        .header(CONTENT_TYPE, "application/xml")
+                        .body(body),
+                )
+                .await?,
         )
-        .await?
-        .text()
         .await?;
 
         // We determine whether or not there were missing entries by the
@@ -1068,7 +2127,15 @@ impl<'a> PackageBuilder<'a> {
             }
 
             Ok(if is_missing {
-                CommitResult::MissingEntries(quick_xml::de::from_str(&response)?)
+                let missing: MissingEntries = quick_xml::de::from_str(&response)?;
+                #[cfg(feature = "tracing")]
+                tracing::debug!(
+                    project = self.project,
+                    package = self.package,
+                    missing = missing.entries.len(),
+                    "commit is missing entries"
+                );
+                CommitResult::MissingEntries(missing)
             } else {
                 CommitResult::Success(quick_xml::de::from_str(&response)?)
             })
@@ -1085,51 +2152,116 @@ impl<'a> PackageBuilder<'a> {
             .push(&self.project)
             .push(&self.package);
         u.query_pairs_mut().append_pair("cmd", "branch");
+        options.append_query(&mut u);
 
-        if let Some(target_project) = &options.target_project {
-            u.query_pairs_mut()
-                .append_pair("target_project", target_project);
-        }
+        self.client.post_request(u).await
+    }
 
-        if let Some(target_package) = &options.target_package {
-            u.query_pairs_mut()
-                .append_pair("target_package", target_package);
-        }
+    pub async fn result(&self) -> Result<ResultList> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("build")
+            .push(&self.project)
+            .push("_result");
+        u.query_pairs_mut().append_pair("package", &self.package);
+        self.client.request(u).await
+    }
 
-        if let Some(comment) = &options.comment {
-            u.query_pairs_mut().append_pair("comment", comment);
-        }
+    pub async fn comments(&self) -> Result<CommentList> {
+        self.client
+            .comments(&["package", &self.project, &self.package])
+            .await
+    }
 
-        if let Some(rebuild) = &options.add_repositories_rebuild {
-            u.query_pairs_mut()
-                .append_pair("add_repositories_rebuild", &rebuild.to_string());
-        }
+    /// Comments on the package, optionally as a reply to the comment with
+    /// the given id.
+    pub async fn create_comment(&self, body: &str, parent: Option<u64>) -> Result<()> {
+        self.client
+            .create_comment(&["package", &self.project, &self.package], body, parent)
+            .await
+    }
 
-        if let Some(block) = &options.add_repositories_block {
-            u.query_pairs_mut()
-                .append_pair("add_repositories_block", &block.to_string());
-        }
+    /// Gives a role in the package, returning whether the meta had to be
+    /// changed for it.
+    pub async fn add_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client
+            .update_meta(self.meta_url()?, |meta| {
+                meta::add_role(meta, meta::PACKAGE_HEADER, holder, &role)
+            })
+            .await
+    }
 
-        if options.force {
-            u.query_pairs_mut().append_pair("force", "1");
-        }
+    /// Takes away a role in the package, returning whether the meta had to be
+    /// changed for it.
+    pub async fn remove_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client
+            .update_meta(self.meta_url()?, |meta| {
+                meta::remove_role(meta, holder, &role)
+            })
+            .await
+    }
 
-        if options.missingok {
-            u.query_pairs_mut().append_pair("missingok", "1");
-        }
+    /// The patchinfo of this `_patchinfo` package.
+    pub async fn patchinfo(&self) -> Result<Patchinfo> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project)
+            .push(&self.package)
+            .push(PATCHINFO_FILE);
+        Patchinfo::from_xml(&self.client.request_text(u).await?)
+    }
 
-        self.client.post_request(u).await
+    /// Makes OBS refresh the patchinfo from the packages of the incident,
+    /// e.g. adding the issues mentioned in their changes.
+    pub async fn update_patchinfo(&self) -> Result<()> {
+        self.source_command("updatepatchinfo", &[]).await
     }
 
-    pub async fn result(&self) -> Result<ResultList> {
+    /// Locks the package, so it can't be changed until unlocked. Packages
+    /// can't be changed while their project is locked either.
+    pub async fn lock(&self, comment: Option<&str>) -> Result<()> {
+        self.source_command("lock", lock_params(comment).as_slice())
+            .await
+    }
+
+    /// Unlocks the package, returning whether it was locked. Unlocking a
+    /// package that isn't locked succeeds, so cleaning up after a partially
+    /// failed release can simply unlock everything.
+    pub async fn unlock(&self, comment: &str) -> Result<bool> {
+        let result = self.source_command("unlock", &[("comment", comment)]).await;
+        unlock_result(result, async { Ok(self.meta().await?.locked) }).await
+    }
+
+    async fn source_command(&self, cmd: &str, params: &[(&str, &str)]) -> Result<()> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
-            .push("build")
+            .push("source")
             .push(&self.project)
-            .push("_result");
-        u.query_pairs_mut().append_pair("package", &self.package);
-        self.client.request(u).await
+            .push(&self.package);
+        u.query_pairs_mut()
+            .append_pair("cmd", cmd)
+            .extend_pairs(params);
+
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::POST, u))
+            .await?;
+
+        Ok(())
+    }
+
+    fn meta_url(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project)
+            .push(&self.package)
+            .push("_meta");
+        Ok(u)
     }
 }
 
@@ -1147,6 +2279,67 @@ impl<'a> ProjectBuilder<'a> {
         }
     }
 
+    async fn for_each_package<T, F, Fut>(
+        &self,
+        options: &BulkOptions,
+        f: F,
+    ) -> Result<PackageResults<T>>
+    where
+        F: Fn(PackageBuilder<'a>) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let packages = match &options.packages {
+            Some(packages) => packages.clone(),
+            None => self
+                .list_packages()
+                .await?
+                .entries
+                .into_iter()
+                .map(|entry| entry.name)
+                .collect(),
+        };
+
+        let concurrency = options.concurrency.unwrap_or(DEFAULT_BULK_CONCURRENCY);
+        Ok(stream::iter(packages)
+            .map(|package| {
+                let builder = PackageBuilder {
+                    client: self.client,
+                    project: self.project.clone(),
+                    package: package.clone(),
+                };
+                f(builder).map(move |result| (package, result))
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect()
+            .await)
+    }
+
+    /// Source listings of the packages in the project.
+    pub async fn package_sources(
+        &self,
+        options: &BulkOptions,
+    ) -> Result<PackageResults<SourceDirectory>> {
+        self.for_each_package(options, |package| async move { package.list(None).await })
+            .await
+    }
+
+    pub async fn package_metas(
+        &self,
+        options: &BulkOptions,
+    ) -> Result<PackageResults<PackageMeta>> {
+        self.for_each_package(options, |package| async move { package.meta().await })
+            .await
+    }
+
+    /// Build results of the packages in the project.
+    pub async fn package_results(
+        &self,
+        options: &BulkOptions,
+    ) -> Result<PackageResults<ResultList>> {
+        self.for_each_package(options, |package| async move { package.result().await })
+            .await
+    }
+
     pub async fn delete(&self) -> Result<()> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
@@ -1154,30 +2347,168 @@ impl<'a> ProjectBuilder<'a> {
             .push("source")
             .push(&self.project);
 
-        Client::send_with_error(self.client.authenticated_request(Method::DELETE, u)).await?;
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::DELETE, u))
+            .await?;
 
         Ok(())
     }
 
     pub async fn list_packages(&self) -> Result<Directory> {
+        self.list_packages_with(&PackageListOptions::default())
+            .await
+    }
+
+    pub async fn list_packages_with(&self, options: &PackageListOptions) -> Result<Directory> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project);
+        if options.expand {
+            u.query_pairs_mut().append_pair("expand", "1");
+        }
+        if options.deleted {
+            u.query_pairs_mut().append_pair("deleted", "1");
+        }
+        self.client.request(u).await
+    }
+
+    /// Source information of all packages in the project.
+    pub async fn source_info(&self, options: &SourceInfoOptions) -> Result<SourceInfoList> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
             .push("source")
             .push(&self.project);
+        options.append_to(&mut u);
+        if options.expand {
+            u.query_pairs_mut().append_pair("expand", "1");
+        }
         self.client.request(u).await
     }
 
     pub async fn meta(&self) -> Result<ProjectMeta> {
+        self.client.request(self.meta_url()?).await
+    }
+
+    /// The project configuration, which can be parsed using
+    /// [`ProjectConfig::parse`](prjconf::ProjectConfig::parse).
+    pub async fn config(&self) -> Result<String> {
+        let u = self.config_url()?;
+        self.client.request_text(u).await
+    }
+
+    pub async fn set_config(&self, config: &str, comment: Option<&str>) -> Result<()> {
+        let mut u = self.config_url()?;
+        if let Some(comment) = comment {
+            u.query_pairs_mut().append_pair("comment", comment);
+        }
+        self.client
+            .send_with_error(
+                self.client
+                    .authenticated_request(Method::PUT, u)
+                    .body(config.to_owned()),
+            )
+            .await?;
+        Ok(())
+    }
+
+    fn config_url(&self) -> Result<Url> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
             .map_err(|_| Error::InvalidUrl)?
             .push("source")
             .push(&self.project)
-            .push("_meta");
+            .push("_config");
+        Ok(u)
+    }
+
+    /// The ASCII armored key the project's packages and repositories are
+    /// signed with.
+    pub async fn pubkey(&self) -> Result<String> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project)
+            .push("_pubkey");
+        self.client.request_text(u).await
+    }
+
+    /// The project's signing key, for verifying its repositories and
+    /// packages.
+    #[cfg(feature = "verify")]
+    pub async fn keyring(&self) -> Result<verify::Keyring> {
+        let pubkey = self.pubkey().await?;
+        Ok(verify::Keyring::from_armored(&pubkey)?)
+    }
+
+    /// Details of the project's signing key and its SSL certificate, if it
+    /// has one.
+    pub async fn keyinfo(&self) -> Result<KeyInfo> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project)
+            .push("_keyinfo");
+        // Without donotcreatecert, OBS would create a missing certificate
+        // as a side effect of reading.
+        u.query_pairs_mut()
+            .append_pair("withsslcert", "1")
+            .append_pair("donotcreatecert", "1");
         self.client.request(u).await
     }
 
+    /// Creates a new signing key for the project, replacing any existing one.
+    pub async fn create_signkey(&self) -> Result<()> {
+        self.source_command("createkey", &[]).await
+    }
+
+    /// Extends the expiry of the project's signing key, by the given number
+    /// of days or the OBS default.
+    pub async fn extendkey(&self, days: Option<u32>) -> Result<()> {
+        match days {
+            Some(days) => {
+                self.source_command("extendkey", &[("days", &days.to_string())])
+                    .await
+            }
+            None => self.source_command("extendkey", &[]).await,
+        }
+    }
+
+    /// Locks the project, so neither it nor its packages can be changed
+    /// until unlocked.
+    pub async fn lock(&self, comment: Option<&str>) -> Result<()> {
+        self.source_command("lock", lock_params(comment).as_slice())
+            .await
+    }
+
+    /// Unlocks the project, returning whether it was locked, like
+    /// [`PackageBuilder::unlock`].
+    pub async fn unlock(&self, comment: &str) -> Result<bool> {
+        let result = self.source_command("unlock", &[("comment", comment)]).await;
+        unlock_result(result, async { Ok(self.meta().await?.locked) }).await
+    }
+
+    async fn source_command(&self, cmd: &str, params: &[(&str, &str)]) -> Result<()> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project);
+        u.query_pairs_mut()
+            .append_pair("cmd", cmd)
+            .extend_pairs(params);
+
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::POST, u))
+            .await?;
+
+        Ok(())
+    }
+
     pub async fn result(&self) -> Result<ResultList> {
         let mut u = self.client.base.clone();
         u.path_segments_mut()
@@ -1233,7 +2564,9 @@ impl<'a> ProjectBuilder<'a> {
             u.query_pairs_mut().append_pair("package", package);
         }
 
-        Client::send_with_error(self.client.authenticated_request(Method::POST, u)).await?;
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::POST, u))
+            .await?;
 
         Ok(())
     }
@@ -1267,44 +2600,464 @@ impl<'a> ProjectBuilder<'a> {
 
         self.client.request(u).await
     }
-}
 
-#[derive(Clone)]
-pub struct Client {
-    base: Url,
-    user: String,
-    pass: String,
-    client: reqwest::Client,
-}
+    pub async fn comments(&self) -> Result<CommentList> {
+        self.client.comments(&["project", &self.project]).await
+    }
 
-impl std::fmt::Debug for Client {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("Client")
-            .field("base", &format_args!("{:?}", self.base))
-            .field("user", &self.user)
-            .field("pass", &"[redacted]")
-            .field("client", &format_args!("{:?}", self.client))
-            .finish()
+    /// Comments on the project, optionally as a reply to the comment with
+    /// the given id.
+    pub async fn create_comment(&self, body: &str, parent: Option<u64>) -> Result<()> {
+        self.client
+            .create_comment(&["project", &self.project], body, parent)
+            .await
     }
-}
 
-impl Client {
-    pub fn new(url: Url, user: String, pass: String) -> Self {
-        Client {
-            base: url,
-            user,
-            pass,
-            client: reqwest::ClientBuilder::new()
-                .user_agent(concat!("open-build-service-rs/", env!("CARGO_PKG_VERSION")))
-                .build()
-                .unwrap(),
-        }
+    /// Gives a role in the project, returning whether the meta had to be
+    /// changed for it. Fails with [`Error::Conflict`] if the meta keeps
+    /// being changed by others while doing so.
+    pub async fn add_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client
+            .update_meta(self.meta_url()?, |meta| {
+                meta::add_role(meta, meta::PROJECT_HEADER, holder, &role)
+            })
+            .await
     }
 
-    pub fn url(&self) -> &Url {
+    /// Takes away a role in the project, returning whether the meta had to be
+    /// changed for it.
+    pub async fn remove_role(&self, holder: &RoleHolder, role: Role) -> Result<bool> {
+        self.client
+            .update_meta(self.meta_url()?, |meta| {
+                meta::remove_role(meta, holder, &role)
+            })
+            .await
+    }
+
+    /// Creates a new incident below this maintenance project, returning the
+    /// name of the incident project.
+    pub async fn create_maintenance_incident(&self) -> Result<String> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project);
+        u.query_pairs_mut()
+            .append_pair("cmd", "createmaintenanceincident");
+
+        let status: ApiStatus = self.client.post_request(u).await?;
+        Ok(status.data("targetproject")?.to_owned())
+    }
+
+    /// The meta of the incident projects below this maintenance project.
+    pub async fn maintenance_incidents(&self) -> Result<Vec<ProjectMeta>> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("search")
+            .push("project");
+        u.query_pairs_mut().append_pair(
+            "match",
+            &format!(
+                "@kind='{}' and starts-with(@name,{})",
+                ProjectKind::MaintenanceIncident,
+                xpath_string(&format!("{}:", self.project))
+            ),
+        );
+
+        let collection: ProjectMetaCollection = self.client.request(u).await?;
+        Ok(collection.projects)
+    }
+
+    /// Requests releasing this maintenance incident to the projects it
+    /// maintains.
+    pub async fn create_maintenance_release_request(&self, description: &str) -> Result<BsRequest> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("request");
+        u.query_pairs_mut().append_pair("cmd", "create");
+
+        let request = NewBsRequest {
+            action: NewBsRequestAction {
+                action_type: BsRequestActionType::MaintenanceRelease.to_string(),
+                source: NewBsRequestSource {
+                    project: &self.project,
+                },
+            },
+            description,
+        };
+        let mut body = String::new();
+        quick_xml::se::to_writer(&mut body, &request)?;
+
+        let response = response_text(
+            self.client
+                .send_with_error(
+                    self.client
+                        .authenticated_request(Method::POST, u)
+                        .header(CONTENT_TYPE, "application/xml")
+                        .body(body),
+                )
+                .await?,
+        )
+        .await?;
+        Ok(quick_xml::de::from_str(&response)?)
+    }
+
+    /// Creates a package holding a new patchinfo, returning the name of the
+    /// package.
+    pub async fn create_patchinfo(&self, options: &PatchinfoOptions) -> Result<String> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project);
+        {
+            let mut query = u.query_pairs_mut();
+            query.append_pair("cmd", "createpatchinfo");
+            if let Some(name) = &options.name {
+                query.append_pair("name", name);
+            }
+            if let Some(comment) = &options.comment {
+                query.append_pair("comment", comment);
+            }
+            if options.force {
+                query.append_pair("force", "1");
+            }
+        }
+
+        let status: ApiStatus = self.client.post_request(u).await?;
+        Ok(status.data("targetpackage")?.to_owned())
+    }
+
+    fn meta_url(&self) -> Result<Url> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source")
+            .push(&self.project)
+            .push("_meta");
+        Ok(u)
+    }
+}
+
+/// Operations on a request like a submit request, called a BsRequest in
+/// OBS to tell it apart from HTTP requests.
+pub struct BsRequestBuilder<'a> {
+    client: &'a Client,
+    id: u64,
+}
+
+impl BsRequestBuilder<'_> {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub async fn comments(&self) -> Result<CommentList> {
+        self.client
+            .comments(&["request", &self.id.to_string()])
+            .await
+    }
+
+    /// Comments on the request, optionally as a reply to the comment with
+    /// the given id.
+    pub async fn create_comment(&self, body: &str, parent: Option<u64>) -> Result<()> {
+        self.client
+            .create_comment(&["request", &self.id.to_string()], body, parent)
+            .await
+    }
+}
+
+#[derive(Clone)]
+pub struct Client {
+    base: Url,
+    auth: Authentication,
+    client: reqwest::Client,
+    transport: Arc<dyn Transport>,
+    cookies: Option<Arc<Jar>>,
+    signature_realm: Arc<Mutex<Option<String>>>,
+    timeout: Option<Duration>,
+    headers: HeaderMap,
+    retry_policy: Option<RetryPolicy>,
+    request_limit: Option<Arc<Semaphore>>,
+    cache: Option<Arc<Cache>>,
+}
+
+impl std::fmt::Debug for Client {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Client")
+            .field("base", &format_args!("{:?}", self.base))
+            .field("auth", &self.auth)
+            .field("client", &format_args!("{:?}", self.client))
+            .field("session_cookies", &self.cookies.is_some())
+            .field("timeout", &self.timeout)
+            .field("headers", &self.headers)
+            .field("retry_policy", &self.retry_policy)
+            .field("request_limit", &self.request_limit)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+/// Builder for a [`Client`], created using [`Client::builder`].
+///
+/// The proxy, root certificate and connect timeout settings are used to
+/// construct the underlying [`reqwest::Client`], so they have no effect when a
+/// pre-built client is passed to [`ClientBuilder::http_client`], and only the
+/// user agent applies to requests sent using a custom [`Transport`]. The
+/// request timeout and extra headers are applied to every request either way,
+/// though whether the timeout is honored is up to the transport.
+pub struct ClientBuilder {
+    base: Url,
+    auth: Authentication,
+    session_cookies: bool,
+    user_agent: String,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    proxies: Vec<reqwest::Proxy>,
+    no_proxy: bool,
+    root_certificates: Vec<reqwest::Certificate>,
+    headers: HeaderMap,
+    http_client: Option<reqwest::Client>,
+    transport: Option<Arc<dyn Transport>>,
+    retry_policy: Option<RetryPolicy>,
+    max_concurrent_requests: Option<usize>,
+    cache: Option<Cache>,
+}
+
+impl std::fmt::Debug for ClientBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientBuilder")
+            .field("base", &format_args!("{:?}", self.base))
+            .field("auth", &self.auth)
+            .field("session_cookies", &self.session_cookies)
+            .field("user_agent", &self.user_agent)
+            .field("timeout", &self.timeout)
+            .field("connect_timeout", &self.connect_timeout)
+            .field("proxies", &self.proxies)
+            .field("no_proxy", &self.no_proxy)
+            .field("root_certificates", &self.root_certificates.len())
+            .field("headers", &self.headers)
+            .field("http_client", &self.http_client)
+            .field("custom_transport", &self.transport.is_some())
+            .field("retry_policy", &self.retry_policy)
+            .field("max_concurrent_requests", &self.max_concurrent_requests)
+            .field("cache", &self.cache)
+            .finish()
+    }
+}
+
+impl ClientBuilder {
+    fn new(url: Url) -> Self {
+        ClientBuilder {
+            base: url,
+            auth: Authentication::None,
+            session_cookies: false,
+            user_agent: concat!("open-build-service-rs/", env!("CARGO_PKG_VERSION")).to_owned(),
+            timeout: None,
+            connect_timeout: None,
+            proxies: Vec::new(),
+            no_proxy: false,
+            root_certificates: Vec::new(),
+            headers: HeaderMap::new(),
+            http_client: None,
+            transport: None,
+            retry_policy: None,
+            max_concurrent_requests: None,
+            cache: None,
+        }
+    }
+
+    pub fn authentication(mut self, auth: Authentication) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn basic_auth(self, user: String, pass: String) -> Self {
+        self.authentication(Authentication::Basic { user, pass })
+    }
+
+    pub fn token_auth(self, token: String) -> Self {
+        self.authentication(Authentication::Token(token))
+    }
+
+    /// Authenticate using osc's SSH signature scheme, signing with the given
+    /// private key.
+    pub fn signature_auth(self, user: String, key: PathBuf) -> Self {
+        self.authentication(Authentication::Signature { user, key })
+    }
+
+    /// Keep the session cookie handed out by OBS and use it instead of
+    /// re-authenticating every request. Only requests that can be retried
+    /// with credentials (i.e. that don't have a streaming body) rely on the
+    /// cookie.
+    pub fn session_cookies(mut self, enable: bool) -> Self {
+        self.session_cookies = enable;
+        self
+    }
+
+    pub fn user_agent(mut self, user_agent: String) -> Self {
+        self.user_agent = user_agent;
+        self
+    }
+
+    /// Timeout for each request, from connecting until the response body has
+    /// been read completely.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    pub fn proxy(mut self, proxy: reqwest::Proxy) -> Self {
+        self.proxies.push(proxy);
+        self
+    }
+
+    /// Don't use any proxies, including the ones set in the environment.
+    pub fn no_proxy(mut self) -> Self {
+        self.no_proxy = true;
+        self
+    }
+
+    /// Trust the given certificate in addition to the system roots, e.g. for
+    /// instances using a private CA.
+    pub fn add_root_certificate(mut self, certificate: reqwest::Certificate) -> Self {
+        self.root_certificates.push(certificate);
+        self
+    }
+
+    /// Header to add to every request.
+    pub fn header(mut self, name: HeaderName, value: HeaderValue) -> Self {
+        self.headers.append(name, value);
+        self
+    }
+
+    pub fn headers(mut self, headers: HeaderMap) -> Self {
+        self.headers.extend(headers);
+        self
+    }
+
+    /// Use an already configured [`reqwest::Client`] instead of building one.
+    pub fn http_client(mut self, client: reqwest::Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Send requests using the given transport instead of over HTTP.
+    pub fn transport(mut self, transport: impl Transport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Retry requests that failed due to transient errors. By default
+    /// requests are not retried.
+    pub fn retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(policy);
+        self
+    }
+
+    /// Limit the number of requests in flight at once, across all clones of
+    /// the client. A request counts until its response body was read, e.g.
+    /// by the bulk helpers. Response bodies that are streamed, like source
+    /// files and binaries, only count until the response headers arrived,
+    /// so holding on to a download doesn't keep other requests from being
+    /// made.
+    pub fn max_concurrent_requests(mut self, limit: usize) -> Self {
+        self.max_concurrent_requests = Some(limit);
+        self
+    }
+
+    /// Serve source files and binaries from the given cache when possible,
+    /// adding them on download. This costs an extra request per download to
+    /// look up the file's md5 or mtime and size.
+    pub fn cache(mut self, cache: Cache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        let cookies = self.session_cookies.then(|| Arc::new(Jar::default()));
+        let client = match self.http_client {
+            Some(client) => client,
+            None => {
+                let mut builder = reqwest::ClientBuilder::new()
+                    .user_agent(self.user_agent)
+                    .tls_certs_merge(self.root_certificates);
+
+                if let Some(timeout) = self.connect_timeout {
+                    builder = builder.connect_timeout(timeout);
+                }
+
+                if self.no_proxy {
+                    builder = builder.no_proxy();
+                }
+
+                for proxy in self.proxies {
+                    builder = builder.proxy(proxy);
+                }
+
+                builder.build()?
+            }
+        };
+        let transport = self.transport.unwrap_or_else(|| Arc::new(client.clone()));
+
+        Ok(Client {
+            base: self.base,
+            auth: self.auth,
+            client,
+            transport,
+            cookies,
+            signature_realm: Arc::new(Mutex::new(None)),
+            timeout: self.timeout,
+            headers: self.headers,
+            retry_policy: self.retry_policy,
+            request_limit: self
+                .max_concurrent_requests
+                .map(|limit| Arc::new(Semaphore::new(limit.max(1)))),
+            cache: self.cache.map(Arc::new),
+        })
+    }
+}
+
+impl Client {
+    /// Creates a client using HTTP basic authentication and default settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the underlying HTTP client can't be initialized, use
+    /// [`Client::builder`] to handle that error instead.
+    pub fn new(url: Url, user: String, pass: String) -> Self {
+        Client::builder(url)
+            .basic_auth(user, pass)
+            .build()
+            .expect("Failed to initialize HTTP client")
+    }
+
+    pub fn builder(url: Url) -> ClientBuilder {
+        ClientBuilder::new(url)
+    }
+
+    pub fn url(&self) -> &Url {
         &self.base
     }
 
+    pub async fn list_projects(&self, options: &ProjectListOptions) -> Result<Directory> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source");
+        let mut directory: Directory = self.request(u).await?;
+        if let Some(prefix) = &options.prefix {
+            directory.entries.retain(|e| e.name.starts_with(prefix));
+        }
+        Ok(directory)
+    }
+
     pub fn project(&self, project: String) -> ProjectBuilder<'_> {
         ProjectBuilder {
             client: self,
@@ -1312,46 +3065,521 @@ impl Client {
         }
     }
 
-    fn authenticated_request(&self, method: Method, url: Url) -> RequestBuilder {
-        self.client
-            .request(method, url)
-            .basic_auth(&self.user, Some(&self.pass))
+    pub fn bs_request(&self, id: u64) -> BsRequestBuilder<'_> {
+        BsRequestBuilder { client: self, id }
+    }
+
+    pub async fn person(&self, login: &str) -> Result<Person> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("person")
+            .push(login);
+        self.request(u).await
+    }
+
+    pub async fn group(&self, title: &str) -> Result<Group> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("group")
+            .push(title);
+        self.request(u).await
+    }
+
+    fn mbranch_url(&self, package: &str, options: &BranchOptions) -> Result<Url> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("source");
+        u.query_pairs_mut()
+            .append_pair("cmd", "branch")
+            .append_pair("package", package);
+        options.append_query(&mut u);
+        Ok(u)
+    }
+
+    /// Lists the branches [`mbranch`](Self::mbranch) would currently create,
+    /// without creating them. The actual branch may differ, e.g. when the
+    /// maintained projects change in the meantime.
+    pub async fn mbranch_plan(
+        &self,
+        package: &str,
+        options: &BranchOptions,
+    ) -> Result<Vec<BranchStatus>> {
+        let mut u = self.mbranch_url(package, options)?;
+        u.query_pairs_mut().append_pair("dryrun", "1");
+        let branches: MaintenanceBranchCollection = self.post_request(u).await?;
+        Ok(branches
+            .packages
+            .into_iter()
+            .map(|branch| BranchStatus {
+                source_project: branch.project,
+                source_package: branch.package,
+                target_project: branch.target.project,
+                target_package: branch.target.package,
+            })
+            .collect())
+    }
+
+    /// Branches `package` from all maintained projects, i.e. those with the
+    /// `OBS:Maintained` attribute, into one project, like `osc mbranch`.
+    /// `target_package` is used as the base name of the branches, which are
+    /// suffixed with the project they were branched from, and `missingok`
+    /// doesn't apply.
+    ///
+    /// Returns the target project, which is all OBS reports about the
+    /// branches; use [`mbranch_plan`](Self::mbranch_plan) to see which ones
+    /// would be created.
+    pub async fn mbranch(&self, package: &str, options: &BranchOptions) -> Result<String> {
+        let status: ApiStatus = self
+            .post_request(self.mbranch_url(package, options)?)
+            .await?;
+        Ok(status.data("targetproject")?.to_owned())
+    }
+
+    /// Projects and packages in which the user or group has the given role,
+    /// or any role.
+    pub async fn involved(&self, holder: &RoleHolder, role: Option<Role>) -> Result<Involvement> {
+        let (element, attribute, id) = holder.xml();
+        let id = xpath_string(id);
+        let xpath = match role {
+            Some(role) => format!(
+                "{element}[@{attribute}={id} and @role={}]",
+                xpath_string(&role.to_string())
+            ),
+            None => format!("{element}[@{attribute}={id}]"),
+        };
+
+        let search_url = |kind| -> Result<Url> {
+            let mut u = self.base.clone();
+            u.path_segments_mut()
+                .map_err(|_| Error::InvalidUrl)?
+                .push("search")
+                .push(kind)
+                .push("id");
+            u.query_pairs_mut().append_pair("match", &xpath);
+            Ok(u)
+        };
+
+        let projects: ProjectIdCollection = self.request(search_url("project")?).await?;
+        let packages: PackageIdCollection = self.request(search_url("package")?).await?;
+        Ok(Involvement {
+            projects: projects.projects,
+            packages: packages.packages,
+        })
+    }
+
+    /// Applies `edit` to the meta at `url`, which returns `None` if nothing
+    /// needs to change. The meta is read again right before writing it, and
+    /// if it changed in the meantime, the edit is redone on the current meta,
+    /// giving up with [`Error::Conflict`] after a few attempts. OBS has no
+    /// conditional writes of meta, so this only narrows the window in which
+    /// concurrent changes are lost, it can't close it.
+    async fn update_meta<F>(&self, url: Url, edit: F) -> Result<bool>
+    where
+        F: Fn(&str) -> Result<Option<String>>,
+    {
+        let mut meta = self.request_text(url.clone()).await?;
+        for _ in 0..META_UPDATE_ATTEMPTS {
+            let Some(updated) = edit(&meta)? else {
+                return Ok(false);
+            };
+
+            let current = self.request_text(url.clone()).await?;
+            if current != meta {
+                meta = current;
+                continue;
+            }
+
+            self.send_with_error(
+                self.authenticated_request(Method::PUT, url.clone())
+                    .body(updated),
+            )
+            .await?;
+            return Ok(true);
+        }
+
+        Err(Error::Conflict)
     }
 
-    async fn send_with_error(request: RequestBuilder) -> Result<Response> {
-        let response = request.send().await?;
+    pub async fn tokens(&self, login: &str) -> Result<TokenList> {
+        self.request(self.tokens_url(login)?).await
+    }
+
+    pub async fn create_token(
+        &self,
+        login: &str,
+        kind: TokenKind,
+        options: &TokenOptions,
+    ) -> Result<CreatedToken> {
+        let mut u = self.tokens_url(login)?;
+        {
+            let mut query = u.query_pairs_mut();
+            query.append_pair("cmd", "create");
+            query.append_pair("operation", &kind.to_string());
+            if let Some(project) = &options.project {
+                query.append_pair("project", project);
+            }
+            if let Some(package) = &options.package {
+                query.append_pair("package", package);
+            }
+            if let Some(description) = &options.description {
+                query.append_pair("description", description);
+            }
+            if let Some(scm_token) = &options.scm_token {
+                query.append_pair("scm_token", scm_token);
+            }
+        }
+
+        let status: ApiStatus = self.post_request(u).await?;
+        Ok(CreatedToken {
+            id: status
+                .data("id")?
+                .parse()
+                .map_err(|_| Error::UnexpectedResult)?,
+            string: status.data("token")?.to_owned(),
+        })
+    }
+
+    pub async fn delete_token(&self, login: &str, id: u64) -> Result<()> {
+        let mut u = self.tokens_url(login)?;
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push(&id.to_string());
+
+        self.send_with_error(self.authenticated_request(Method::DELETE, u))
+            .await?;
+
+        Ok(())
+    }
+
+    /// Runs of the workflows triggered using the given workflow token.
+    pub async fn workflow_runs(
+        &self,
+        token_id: u64,
+        options: &WorkflowRunListOptions,
+    ) -> Result<WorkflowRunList> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("workflow_runs");
+        {
+            let mut query = u.query_pairs_mut();
+            query.append_pair("workflow_token_id", &token_id.to_string());
+            if let Some(status) = options.status {
+                query.append_pair("status", &status.to_string());
+            }
+            if let Some(event_type) = &options.event_type {
+                query.append_pair("event_type", event_type);
+            }
+            if let Some(pr_mr) = &options.pr_mr {
+                query.append_pair("pr_mr", pr_mr);
+            }
+            if let Some(commit) = &options.commit {
+                query.append_pair("commit", commit);
+            }
+        }
+        self.request(u).await
+    }
+
+    pub async fn workflow_run(&self, id: u64) -> Result<WorkflowRun> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("workflow_runs")
+            .push(&id.to_string());
+        self.request(u).await
+    }
+
+    fn tokens_url(&self, login: &str) -> Result<Url> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("person")
+            .push(login)
+            .push("token");
+        Ok(u)
+    }
 
-        match response.error_for_status_ref() {
-            Ok(_) => Ok(response),
+    /// Deletes a comment on a project, package or request.
+    pub async fn delete_comment(&self, id: u64) -> Result<()> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("comment")
+            .push(&id.to_string());
+
+        self.send_with_error(self.authenticated_request(Method::DELETE, u))
+            .await?;
+
+        Ok(())
+    }
+
+    fn comments_url(&self, target: &[&str]) -> Result<Url> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("comments")
+            .extend(target);
+        Ok(u)
+    }
+
+    async fn comments(&self, target: &[&str]) -> Result<CommentList> {
+        self.request(self.comments_url(target)?).await
+    }
+
+    async fn create_comment(&self, target: &[&str], body: &str, parent: Option<u64>) -> Result<()> {
+        let mut u = self.comments_url(target)?;
+        if let Some(parent) = parent {
+            u.query_pairs_mut()
+                .append_pair("parent_id", &parent.to_string());
+        }
+
+        self.send_with_error(
+            self.authenticated_request(Method::POST, u)
+                .body(body.to_owned()),
+        )
+        .await?;
+
+        Ok(())
+    }
+
+    fn authenticated_request(&self, method: Method, url: Url) -> RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, url)
+            .headers(self.headers.clone());
+        if let Some(timeout) = self.timeout {
+            request = request.timeout(timeout);
+        }
+        request
+    }
+
+    fn has_session_cookie(&self, url: &Url) -> bool {
+        self.cookies
+            .as_ref()
+            .is_some_and(|cookies| cookies.cookies(url).is_some())
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    async fn send_with_error(&self, request: RequestBuilder) -> Result<Response> {
+        self.send_request(request.build()?).await
+    }
+
+    // Only the method and path are recorded, as the query may carry
+    // credentials in some cases.
+    #[cfg(feature = "tracing")]
+    async fn send_with_error(&self, request: RequestBuilder) -> Result<Response> {
+        use tracing::{Instrument, field::Empty};
+
+        let request = request.build()?;
+        let span = tracing::info_span!(
+            "obs_request",
+            method = %request.method(),
+            path = request.url().path(),
+            status = Empty,
+            duration_ms = Empty,
+            size = Empty,
+        );
+
+        let start = std::time::Instant::now();
+        let result = self.send_request(request).instrument(span.clone()).await;
+        span.record("duration_ms", start.elapsed().as_millis() as u64);
+        match &result {
+            Ok(response) => {
+                span.record("status", response.status().as_u16());
+                if let Some(size) = response.content_length() {
+                    span.record("size", size);
+                }
+            }
             Err(e) => {
                 if let Some(status) = e.status() {
-                    if status.is_client_error() {
-                        let data = response.text().await?;
-                        let error = quick_xml::de::from_str(&data)?;
-                        Err(Error::ApiError(error))
-                    } else {
-                        Err(e.into())
-                    }
-                } else {
-                    Err(e.into())
+                    span.record("status", status.as_u16());
                 }
+                tracing::debug!(parent: &span, error = %e, "OBS request failed");
             }
         }
+
+        result
     }
 
-    async fn request<T: DeserializeOwned + std::fmt::Debug>(&self, url: Url) -> Result<T> {
-        let data = Self::send_with_error(self.authenticated_request(Method::GET, url))
-            .await?
-            .text()
+    async fn send_request(&self, request: reqwest::Request) -> Result<Response> {
+        let method = request.method().clone();
+        let url = request.url().clone();
+        let response = match &self.retry_policy {
+            Some(policy) => self.send_with_retry(request, policy).await?,
+            None => self.send_authenticated(request).await?,
+        };
+
+        let status = response.status();
+        if !status.is_client_error() && !status.is_server_error() {
+            return Ok(response);
+        }
+
+        let body = response_text(response).await?;
+        match quick_xml::de::from_str::<ApiStatus>(&body) {
+            Ok(api_status) => Err(Error::ApiError(Box::new(ApiError {
+                status,
+                method,
+                url,
+                code: api_status.code,
+                summary: api_status.summary,
+                details: api_status.details,
+                data: api_status.data,
+            }))),
+            Err(_) => Err(Error::HttpError(Box::new(HttpError {
+                status,
+                method,
+                url,
+                body,
+            }))),
+        }
+    }
+
+    async fn send_with_retry(
+        &self,
+        request: reqwest::Request,
+        policy: &RetryPolicy,
+    ) -> Result<Response> {
+        let method = request.method().clone();
+        let mut request = Some(request);
+        let mut attempt = 1;
+        loop {
+            let current = request.take().expect("request is kept for each attempt");
+            if policy.allows_retry(&method, attempt) {
+                request = current.try_clone();
+            }
+
+            let delay = match self.send_authenticated(current).await {
+                Ok(response) => match policy.delay_for_response(&response, attempt) {
+                    Some(delay) if request.is_some() => delay,
+                    _ => return Ok(response),
+                },
+                Err(Error::RequestError(e))
+                    if request.is_some() && retry::is_transient_error(&e) =>
+                {
+                    policy.backoff(attempt)
+                }
+                Err(e) => return Err(e),
+            };
+
+            #[cfg(feature = "tracing")]
+            tracing::debug!(attempt, ?delay, "retrying OBS request");
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn send_authenticated(&self, mut request: reqwest::Request) -> Result<Response> {
+        let permit = match &self.request_limit {
+            Some(limit) => Some(
+                limit
+                    .clone()
+                    .acquire_owned()
+                    .await
+                    .expect("semaphore is never closed"),
+            ),
+            None => None,
+        };
+
+        // Requests that can be replayed are first sent without credentials if
+        // there is a session to reuse or if the server has yet to tell us how
+        // to sign them, and retried with credentials if that was rejected.
+        let mut retry = request.try_clone();
+        if retry.is_none() || !self.has_session_cookie(request.url()) {
+            let mut realm = self.signature_realm.lock().unwrap().clone();
+            // Requests that can't be replayed have to be signed right away,
+            // so the challenge is fetched first if need be.
+            if retry.is_none()
+                && realm.is_none()
+                && let Some(user) = self.auth.signature_user()
+            {
+                realm = self.fetch_signature_realm(user).await?;
+            }
+            if self.auth.authorize(&mut request, realm.as_deref()).await? {
+                retry = None;
+            }
+        }
+
+        let mut response = self.execute(request).await?;
+        if response.status() == StatusCode::UNAUTHORIZED
+            && let Some(mut retry) = retry
+        {
+            let realm = auth::signature_challenge_realm(&response);
+            if realm.is_some() {
+                self.signature_realm.lock().unwrap().clone_from(&realm);
+            }
+
+            if self.auth.authorize(&mut retry, realm.as_deref()).await? {
+                response = self.execute(retry).await?;
+            }
+        }
+
+        // Kept with the response, so reading its body with `response_text`
+        // counts towards the limit. Otherwise it's released along with the
+        // response headers, e.g. when turning the body into a stream.
+        if let Some(permit) = permit {
+            response.extensions_mut().insert(Arc::new(permit));
+        }
+        Ok(response)
+    }
+
+    /// Learns the signature realm from the challenge to a small request sent
+    /// without credentials.
+    async fn fetch_signature_realm(&self, user: &str) -> Result<Option<String>> {
+        let mut u = self.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("person")
+            .push(user);
+        let response = self
+            .execute(self.authenticated_request(Method::GET, u).build()?)
             .await?;
+        let realm = auth::signature_challenge_realm(&response);
+        if realm.is_some() {
+            self.signature_realm.lock().unwrap().clone_from(&realm);
+        }
+        Ok(realm)
+    }
+
+    // Session cookies are handled here rather than by the reqwest client, so
+    // they work with any transport.
+    async fn execute(&self, mut request: reqwest::Request) -> Result<Response> {
+        let Some(cookies) = &self.cookies else {
+            return self.transport.execute(request).await;
+        };
+
+        if let Some(cookie) = cookies.cookies(request.url()) {
+            request.headers_mut().insert(COOKIE, cookie);
+        }
+        let url = request.url().clone();
+        let response = self.transport.execute(request).await?;
+        let mut set_cookies = response.headers().get_all(SET_COOKIE).iter().peekable();
+        if set_cookies.peek().is_some() {
+            cookies.set_cookies(&mut set_cookies, &url);
+        }
+        Ok(response)
+    }
+
+    async fn request<T: DeserializeOwned + std::fmt::Debug>(&self, url: Url) -> Result<T> {
+        let data = self.request_text(url).await?;
         quick_xml::de::from_str(&data).map_err(|e| e.into())
     }
 
+    async fn request_text(&self, url: Url) -> Result<String> {
+        let response = self
+            .send_with_error(self.authenticated_request(Method::GET, url))
+            .await?;
+        Ok(response_text(response).await?)
+    }
+
     async fn post_request<T: DeserializeOwned + std::fmt::Debug>(&self, url: Url) -> Result<T> {
-        let data = Self::send_with_error(self.authenticated_request(Method::POST, url))
-            .await?
-            .text()
+        let response = self
+            .send_with_error(self.authenticated_request(Method::POST, url))
             .await?;
+        let data = response_text(response).await?;
         quick_xml::de::from_str(&data).map_err(|e| e.into())
     }
 }
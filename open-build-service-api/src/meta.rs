@@ -0,0 +1,203 @@
+//! Edits of project and package meta done on the XML text, so that
+//! everything the models don't cover, including formatting, is kept as is.
+//! Also used to keep the parts of other XML files the models don't cover.
+
+use quick_xml::events::Event;
+use quick_xml::{DeError, Reader};
+
+use crate::{Error, Result, Role, RoleHolder};
+
+/// Elements coming before the roles in project meta.
+pub(crate) const PROJECT_HEADER: &[&str] = &[
+    "title",
+    "description",
+    "url",
+    "lock",
+    "mountproject",
+    "remoteurl",
+    "remoteproxy",
+    "scmsync",
+    "devel",
+];
+
+/// Elements coming before the roles in package meta.
+pub(crate) const PACKAGE_HEADER: &[&str] = &[
+    "title",
+    "description",
+    "devel",
+    "releasename",
+    "bcntsynctag",
+    "url",
+    "scmsync",
+];
+
+/// A child element of the root element.
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    /// Start of the whitespace before the element.
+    leading: usize,
+    start: usize,
+    end: usize,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn is_role(&self, holder: &RoleHolder, role: &Role) -> bool {
+        let (element, attribute, id) = holder.xml();
+        self.name == element
+            && self.attribute(attribute) == Some(id)
+            && self.attribute("role") == Some(role.to_string().as_str())
+    }
+}
+
+/// Returns the children of the root element, and where its contents start
+/// unless it's an empty element.
+fn children(meta: &str) -> Result<(Vec<Element>, Option<usize>)> {
+    let mut reader = Reader::from_str(meta);
+    let mut children = Vec::new();
+    let mut contents_start = None;
+    let mut depth = 0;
+    let mut whitespace_start = None;
+    let mut current: Option<Element> = None;
+
+    loop {
+        let start = reader.buffer_position() as usize;
+        let event = reader.read_event().map_err(DeError::from)?;
+        let end = reader.buffer_position() as usize;
+
+        let child = |e: &quick_xml::events::BytesStart| -> Result<Element> {
+            let attributes = e
+                .attributes()
+                .map(|attr| {
+                    let attr = attr.map_err(DeError::from)?;
+                    let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+                    let value = attr.unescape_value().map_err(DeError::from)?.into_owned();
+                    Ok((key, value))
+                })
+                .collect::<Result<_>>()?;
+            Ok(Element {
+                name: String::from_utf8_lossy(e.name().as_ref()).into_owned(),
+                attributes,
+                leading: whitespace_start.unwrap_or(start),
+                start,
+                end,
+            })
+        };
+
+        match event {
+            Event::Start(ref e) => {
+                if depth == 0 {
+                    contents_start = Some(end);
+                } else if depth == 1 {
+                    current = Some(child(e)?);
+                }
+                depth += 1;
+            }
+            Event::Empty(ref e) if depth == 1 => children.push(child(e)?),
+            Event::End(_) => {
+                depth -= 1;
+                if depth == 1
+                    && let Some(mut element) = current.take()
+                {
+                    element.end = end;
+                    children.push(element);
+                }
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+
+        whitespace_start = match event {
+            Event::Text(ref text) if text.iter().all(u8::is_ascii_whitespace) => {
+                whitespace_start.or(Some(start))
+            }
+            _ => None,
+        };
+    }
+
+    Ok((children, contents_start))
+}
+
+/// The raw XML of the children of the root element not named in `known`.
+pub(crate) fn unknown_children<'a>(xml: &'a str, known: &[&str]) -> Result<Vec<&'a str>> {
+    let (children, _) = children(xml)?;
+    Ok(children
+        .into_iter()
+        .filter(|e| !known.contains(&e.name.as_str()))
+        .map(|e| &xml[e.start..e.end])
+        .collect())
+}
+
+/// Adds the role after the existing ones, returning `None` if it's already
+/// there.
+pub(crate) fn add_role(
+    meta: &str,
+    header: &[&str],
+    holder: &RoleHolder,
+    role: &Role,
+) -> Result<Option<String>> {
+    let (children, contents_start) = children(meta)?;
+    // Meta without any contents, like `<package/>`, isn't returned by OBS.
+    let contents_start = contents_start.ok_or(Error::UnexpectedResult)?;
+    if children.iter().any(|e| e.is_role(holder, role)) {
+        return Ok(None);
+    }
+
+    // Persons come before groups, and both after the header elements.
+    let (element, attribute, id) = holder.xml();
+    let anchor = children
+        .iter()
+        .rev()
+        .find(|e| e.name == element)
+        .or_else(|| {
+            (element == "group")
+                .then(|| children.iter().rev().find(|e| e.name == "person"))
+                .flatten()
+        })
+        .or_else(|| {
+            children
+                .iter()
+                .rev()
+                .find(|e| header.contains(&e.name.as_str()))
+        });
+
+    let (position, indent) = match anchor {
+        Some(anchor) => (anchor.end, &meta[anchor.leading..anchor.start]),
+        None => (
+            contents_start,
+            children
+                .first()
+                .map_or("", |first| &meta[first.leading..first.start]),
+        ),
+    };
+
+    let mut updated = meta.to_owned();
+    updated.insert_str(
+        position,
+        &format!(
+            "{indent}<{element} {attribute}=\"{}\" role=\"{}\"/>",
+            quick_xml::escape::escape(id),
+            quick_xml::escape::escape(role.to_string())
+        ),
+    );
+    Ok(Some(updated))
+}
+
+/// Removes the role, returning `None` if it isn't there.
+pub(crate) fn remove_role(meta: &str, holder: &RoleHolder, role: &Role) -> Result<Option<String>> {
+    let (children, _) = children(meta)?;
+    let mut updated = meta.to_owned();
+    let mut removed = false;
+    for element in children.iter().rev().filter(|e| e.is_role(holder, role)) {
+        updated.replace_range(element.leading..element.end, "");
+        removed = true;
+    }
+    Ok(removed.then_some(updated))
+}
@@ -0,0 +1,435 @@
+//! Package working copies compatible with osc, i.e. checkouts keeping their
+//! metadata in a `.osc` directory.
+//!
+//! Pristine copies of the checked out files are kept in the store the way
+//! osc does, so either tool can be used on the same checkout.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Component, Path, PathBuf};
+
+use futures::prelude::*;
+use reqwest::Url;
+use tokio::io::AsyncWriteExt;
+
+use crate::upload::md5_path;
+use crate::{Client, Error, PackageBuilder, Result, SourceDirectory};
+
+const STORE: &str = ".osc";
+const STORE_VERSION: &str = "1.0";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FileStatus {
+    Unchanged,
+    Modified,
+    Added,
+    Deleted,
+    Conflicted,
+    /// Tracked, but gone from the working copy.
+    Missing,
+    /// In the working copy, but not tracked.
+    Unversioned,
+}
+
+#[derive(Debug, Default)]
+pub struct UpdateResult {
+    pub updated: Vec<String>,
+    pub added: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Files changed both locally and on the server. The local file is left
+    /// alone and the server's version is written next to it as
+    /// `<name>.r<rev>`.
+    pub conflicted: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct WorkingCopy {
+    path: PathBuf,
+    pristine: PathBuf,
+    apiurl: Url,
+    project: String,
+    package: String,
+    files: SourceDirectory,
+    to_be_added: Vec<String>,
+    to_be_deleted: Vec<String>,
+    in_conflict: Vec<String>,
+}
+
+async fn read_store_file(store: &Path, name: &str) -> Result<Option<String>> {
+    match tokio::fs::read_to_string(store.join(name)).await {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn read_store_list(store: &Path, name: &str) -> Result<Vec<String>> {
+    Ok(read_store_file(store, name)
+        .await?
+        .map(|contents| contents.lines().map(str::to_owned).collect())
+        .unwrap_or_default())
+}
+
+async fn write_store_list(store: &Path, name: &str, list: &[String]) -> Result<()> {
+    let file = store.join(name);
+    if list.is_empty() {
+        match tokio::fs::remove_file(file).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => return Ok(()),
+        }
+    }
+
+    let contents: String = list.iter().map(|name| format!("{name}\n")).collect();
+    tokio::fs::write(file, contents).await?;
+    Ok(())
+}
+
+async fn download(package: &PackageBuilder<'_>, name: &str, rev: &str, dest: &Path) -> Result<()> {
+    let mut stream = std::pin::pin!(package.source_file_at(name, Some(rev)).await?);
+    let mut file = tokio::fs::File::create(dest).await?;
+    while let Some(chunk) = stream.try_next().await? {
+        file.write_all(&chunk).await?;
+    }
+    file.flush().await?;
+    Ok(())
+}
+
+async fn package_meta_xml(package: &PackageBuilder<'_>) -> Result<String> {
+    let mut u = package.client.base.clone();
+    u.path_segments_mut()
+        .map_err(|_| Error::InvalidUrl)?
+        .push("source")
+        .push(&package.project)
+        .push(&package.package)
+        .push("_meta");
+    package.client.request_text(u).await
+}
+
+/// The API url as written to `_apiurl`, without a trailing slash.
+fn api_url_str(url: &Url) -> &str {
+    url.as_str().trim_end_matches('/')
+}
+
+/// Makes sure every file name is a plain file name, so a file list from the
+/// server can't make files be written outside the working copy.
+fn check_file_names(files: &SourceDirectory) -> Result<()> {
+    for entry in &files.entries {
+        let mut components = Path::new(&entry.name).components();
+        let plain = matches!(components.next(), Some(Component::Normal(_)))
+            && components.next().is_none()
+            && !entry.name.contains(['/', '\\']);
+        if !plain {
+            return Err(Error::InvalidFileName(entry.name.clone()));
+        }
+    }
+    Ok(())
+}
+
+fn remove(list: &mut Vec<String>, name: &str) -> bool {
+    let len = list.len();
+    list.retain(|n| n != name);
+    list.len() != len
+}
+
+impl WorkingCopy {
+    /// Opens an existing working copy, as created by osc or
+    /// [`checkout`](Self::checkout).
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let store = path.join(STORE);
+
+        let required = |name: &'static str, contents: Option<String>| {
+            contents.ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!(
+                        "{} is not an osc package working copy: missing {name}",
+                        path.display()
+                    ),
+                )
+            })
+        };
+        let apiurl = required("_apiurl", read_store_file(&store, "_apiurl").await?)?;
+        let project = required("_project", read_store_file(&store, "_project").await?)?;
+        let package = required("_package", read_store_file(&store, "_package").await?)?;
+        let files = required("_files", read_store_file(&store, "_files").await?)?;
+
+        // Newer osc versions keep the pristine copies in a subdirectory.
+        let pristine = match read_store_file(&store, "_osclib_version").await? {
+            Some(version) if version.trim() == "2.0" => store.join("sources"),
+            _ => store.clone(),
+        };
+
+        let files = quick_xml::de::from_str(&files)?;
+        check_file_names(&files)?;
+
+        Ok(Self {
+            apiurl: Url::parse(apiurl.trim()).map_err(|_| Error::InvalidUrl)?,
+            project: project.trim().to_owned(),
+            package: package.trim().to_owned(),
+            files,
+            to_be_added: read_store_list(&store, "_to_be_added").await?,
+            to_be_deleted: read_store_list(&store, "_to_be_deleted").await?,
+            in_conflict: read_store_list(&store, "_in_conflict").await?,
+            pristine,
+            path,
+        })
+    }
+
+    /// Checks out `package` at `rev`, or the latest revision, into `path`,
+    /// which must not be a working copy already.
+    pub async fn checkout<P: AsRef<Path>>(
+        package: &PackageBuilder<'_>,
+        path: P,
+        rev: Option<&str>,
+    ) -> Result<Self> {
+        let files_xml = package.client.request_text(package.list_url(rev)?).await?;
+        let files: SourceDirectory = quick_xml::de::from_str(&files_xml)?;
+        check_file_names(&files)?;
+        let meta_xml = package_meta_xml(package).await?;
+
+        let path = path.as_ref().to_owned();
+        let store = path.join(STORE);
+        tokio::fs::create_dir_all(&path).await?;
+        tokio::fs::create_dir(&store).await?;
+
+        let apiurl = package.client.url().clone();
+        tokio::fs::write(store.join("_apiurl"), format!("{}\n", api_url_str(&apiurl))).await?;
+        tokio::fs::write(store.join("_project"), format!("{}\n", package.project)).await?;
+        tokio::fs::write(store.join("_package"), format!("{}\n", package.package)).await?;
+        tokio::fs::write(store.join("_osclib_version"), format!("{STORE_VERSION}\n")).await?;
+        tokio::fs::write(store.join("_meta"), meta_xml).await?;
+
+        for entry in &files.entries {
+            let pristine = store.join(&entry.name);
+            download(package, &entry.name, &files.srcmd5, &pristine).await?;
+            tokio::fs::copy(&pristine, path.join(&entry.name)).await?;
+        }
+        // Written last, so an interrupted checkout can't be mistaken for a
+        // complete one.
+        tokio::fs::write(store.join("_files"), files_xml).await?;
+
+        Ok(Self {
+            pristine: store,
+            apiurl,
+            project: package.project.clone(),
+            package: package.package.clone(),
+            files,
+            to_be_added: Vec::new(),
+            to_be_deleted: Vec::new(),
+            in_conflict: Vec::new(),
+            path,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn apiurl(&self) -> &Url {
+        &self.apiurl
+    }
+
+    pub fn project(&self) -> &str {
+        &self.project
+    }
+
+    pub fn package(&self) -> &str {
+        &self.package
+    }
+
+    /// The checked out revision's file list.
+    pub fn files(&self) -> &SourceDirectory {
+        &self.files
+    }
+
+    pub fn to_be_added(&self) -> &[String] {
+        &self.to_be_added
+    }
+
+    pub fn to_be_deleted(&self) -> &[String] {
+        &self.to_be_deleted
+    }
+
+    /// Computes the status of every tracked file and every file in the
+    /// working copy, comparing contents by md5.
+    pub async fn status(&self) -> Result<BTreeMap<String, FileStatus>> {
+        let mut status = BTreeMap::new();
+        for entry in &self.files.entries {
+            let file = self.path.join(&entry.name);
+            let file_status = if self.to_be_deleted.contains(&entry.name) {
+                FileStatus::Deleted
+            } else if self.in_conflict.contains(&entry.name) {
+                FileStatus::Conflicted
+            } else if !tokio::fs::try_exists(&file).await? {
+                FileStatus::Missing
+            } else if md5_path(&file).await? != entry.md5 {
+                FileStatus::Modified
+            } else {
+                FileStatus::Unchanged
+            };
+            status.insert(entry.name.clone(), file_status);
+        }
+
+        let mut dir = tokio::fs::read_dir(&self.path).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if status.contains_key(&name) || !entry.file_type().await?.is_file() {
+                continue;
+            }
+            let file_status = if self.to_be_added.contains(&name) {
+                FileStatus::Added
+            } else {
+                FileStatus::Unversioned
+            };
+            status.insert(name, file_status);
+        }
+
+        for name in &self.to_be_added {
+            status.entry(name.clone()).or_insert(FileStatus::Missing);
+        }
+
+        Ok(status)
+    }
+
+    /// Schedules a file in the working copy to be added.
+    pub async fn add(&mut self, name: &str) -> Result<()> {
+        tokio::fs::metadata(self.path.join(name)).await?;
+        if !remove(&mut self.to_be_deleted, name)
+            && !self.files.entries.iter().any(|e| e.name == name)
+            && !self.to_be_added.iter().any(|n| n == name)
+        {
+            self.to_be_added.push(name.to_owned());
+        }
+        self.save_lists().await
+    }
+
+    /// Removes a file from the working copy, scheduling it to be deleted if
+    /// it's tracked.
+    pub async fn delete(&mut self, name: &str) -> Result<()> {
+        match tokio::fs::remove_file(self.path.join(name)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+            _ => (),
+        }
+        if !remove(&mut self.to_be_added, name)
+            && self.files.entries.iter().any(|e| e.name == name)
+            && !self.to_be_deleted.iter().any(|n| n == name)
+        {
+            self.to_be_deleted.push(name.to_owned());
+        }
+        remove(&mut self.in_conflict, name);
+        self.save_lists().await
+    }
+
+    /// Marks a conflicted file as resolved.
+    pub async fn resolved(&mut self, name: &str) -> Result<()> {
+        remove(&mut self.in_conflict, name);
+        self.save_lists().await
+    }
+
+    /// Updates the working copy to `rev`, or the latest revision, keeping
+    /// local changes. `client` has to be for the instance the working copy
+    /// was checked out from.
+    pub async fn update(&mut self, client: &Client, rev: Option<&str>) -> Result<UpdateResult> {
+        if api_url_str(client.url()) != api_url_str(&self.apiurl) {
+            return Err(Error::ApiUrlMismatch(
+                api_url_str(&self.apiurl).to_owned(),
+                api_url_str(client.url()).to_owned(),
+            ));
+        }
+
+        let package = client
+            .project(self.project.clone())
+            .package(self.package.clone());
+        let files_xml = client.request_text(package.list_url(rev)?).await?;
+        let files: SourceDirectory = quick_xml::de::from_str(&files_xml)?;
+        check_file_names(&files)?;
+        let meta_xml = package_meta_xml(&package).await?;
+        let new_rev = files.rev.as_deref().unwrap_or(&files.srcmd5);
+
+        let status = self.status().await?;
+        let old: HashMap<&str, &str> = self
+            .files
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.md5.as_str()))
+            .collect();
+
+        let mut result = UpdateResult::default();
+        for entry in &files.entries {
+            if old.get(entry.name.as_str()) == Some(&entry.md5.as_str()) {
+                continue;
+            }
+
+            let file = self.path.join(&entry.name);
+            let pristine = self.pristine.join(&entry.name);
+            download(&package, &entry.name, &files.srcmd5, &pristine).await?;
+
+            match status.get(&entry.name) {
+                None | Some(FileStatus::Unchanged) | Some(FileStatus::Missing) => {
+                    tokio::fs::copy(&pristine, &file).await?;
+                    remove(&mut self.to_be_added, &entry.name);
+                    if old.contains_key(entry.name.as_str()) {
+                        result.updated.push(entry.name.clone());
+                    } else {
+                        result.added.push(entry.name.clone());
+                    }
+                }
+                // Stays scheduled for deletion against the new revision.
+                Some(FileStatus::Deleted) => (),
+                Some(_) if md5_path(&file).await? == entry.md5 => {
+                    remove(&mut self.to_be_added, &entry.name);
+                    remove(&mut self.in_conflict, &entry.name);
+                }
+                Some(_) => {
+                    let theirs = self.path.join(format!("{}.r{new_rev}", entry.name));
+                    tokio::fs::copy(&pristine, theirs).await?;
+                    remove(&mut self.to_be_added, &entry.name);
+                    if !self.in_conflict.contains(&entry.name) {
+                        self.in_conflict.push(entry.name.clone());
+                    }
+                    result.conflicted.push(entry.name.clone());
+                }
+            }
+        }
+
+        for entry in &self.files.entries {
+            if files.entries.iter().any(|e| e.name == entry.name) {
+                continue;
+            }
+
+            match status.get(&entry.name) {
+                Some(FileStatus::Unchanged) => {
+                    tokio::fs::remove_file(self.path.join(&entry.name)).await?;
+                    result.deleted.push(entry.name.clone());
+                }
+                Some(FileStatus::Missing) => result.deleted.push(entry.name.clone()),
+                Some(FileStatus::Deleted) => {
+                    remove(&mut self.to_be_deleted, &entry.name);
+                }
+                // Local changes are kept as an unversioned file.
+                _ => (),
+            }
+            match tokio::fs::remove_file(self.pristine.join(&entry.name)).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => (),
+            }
+        }
+
+        let store = self.path.join(STORE);
+        tokio::fs::write(store.join("_meta"), meta_xml).await?;
+        tokio::fs::write(store.join("_files"), files_xml).await?;
+        self.files = files;
+        self.save_lists().await?;
+
+        Ok(result)
+    }
+
+    async fn save_lists(&self) -> Result<()> {
+        let store = self.path.join(STORE);
+        write_store_list(&store, "_to_be_added", &self.to_be_added).await?;
+        write_store_list(&store, "_to_be_deleted", &self.to_be_deleted).await?;
+        write_store_list(&store, "_in_conflict", &self.in_conflict).await
+    }
+}
@@ -0,0 +1,218 @@
+//! The `_patchinfo` file describing a maintenance update, as created by
+//! [`ProjectBuilder::create_patchinfo`](crate::ProjectBuilder::create_patchinfo).
+//!
+//! A changed [`Patchinfo`] is written back by uploading the output of
+//! [`Patchinfo::to_xml`] as [`PATCHINFO_FILE`] and committing it using
+//! [`PackageBuilder::commit`](crate::PackageBuilder::commit).
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use strum_macros::{Display, EnumString};
+
+use crate::{Result, meta};
+
+/// Name of the file holding the patchinfo in its package.
+pub const PATCHINFO_FILE: &str = "_patchinfo";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq, Display, EnumString)]
+#[serde(from = "String", into = "String")]
+#[strum(serialize_all = "lowercase")]
+pub enum PatchCategory {
+    #[default]
+    Recommended,
+    Security,
+    Optional,
+    Feature,
+    /// A program temporary fix, i.e. a fix for a single customer.
+    Ptf,
+    /// A category not listed here, e.g. one added in a newer OBS.
+    #[strum(default, to_string = "{0}")]
+    Other(String),
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq, Display, EnumString)]
+#[serde(from = "String", into = "String")]
+#[strum(serialize_all = "lowercase")]
+pub enum PatchRating {
+    #[default]
+    Low,
+    Moderate,
+    Important,
+    Critical,
+    /// A rating not listed here, like `unspecified`.
+    #[strum(default, to_string = "{0}")]
+    Other(String),
+}
+
+// Parsed by strum rather than serde, as the untagged fallback variant can't
+// be deserialized from element text.
+macro_rules! string_enum {
+    ($name:ident) => {
+        impl From<String> for $name {
+            fn from(value: String) -> Self {
+                // Infallible thanks to the default variant.
+                value.parse().unwrap()
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> Self {
+                value.to_string()
+            }
+        }
+    };
+}
+
+string_enum!(PatchCategory);
+string_enum!(PatchRating);
+
+/// An issue fixed by the update, e.g. a bug or a CVE.
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct PatchIssue {
+    /// The issue tracker, e.g. `bnc` or `cve`.
+    #[serde(rename = "@tracker")]
+    pub tracker: String,
+    #[serde(rename = "@id")]
+    pub id: String,
+    #[serde(rename = "$text", default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize, Eq, PartialEq)]
+pub struct ReleaseTarget {
+    #[serde(rename = "@project")]
+    pub project: String,
+    #[serde(rename = "@repository", skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+}
+
+/// (De)serializes flags which are set by the presence of an empty element,
+/// like `<reboot_needed/>`.
+mod flag {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        _value: &bool,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        // Only called for set flags, see `skip_serializing_if`.
+        serializer.serialize_unit()
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<bool, D::Error> {
+        <()>::deserialize(deserializer)?;
+        Ok(true)
+    }
+
+    pub fn is_unset(value: &bool) -> bool {
+        !value
+    }
+}
+
+/// Elements modelled by [`Patchinfo`].
+const KNOWN_ELEMENTS: &[&str] = &[
+    "packager",
+    "category",
+    "rating",
+    "summary",
+    "description",
+    "issue",
+    "package",
+    "binary",
+    "releasetarget",
+    "name",
+    "message",
+    "reboot_needed",
+    "relogin_needed",
+    "zypp_restart_needed",
+];
+
+/// The contents of a `_patchinfo` file.
+#[derive(Clone, Debug, Default, Deserialize, Serialize, Eq, PartialEq)]
+#[serde(rename = "patchinfo")]
+pub struct Patchinfo {
+    #[serde(rename = "@incident", skip_serializing_if = "Option::is_none")]
+    pub incident: Option<String>,
+    /// Login of the user preparing the update.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packager: Option<String>,
+    #[serde(default)]
+    pub category: PatchCategory,
+    #[serde(default)]
+    pub rating: PatchRating,
+    #[serde(default)]
+    pub summary: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, rename = "issue")]
+    pub issues: Vec<PatchIssue>,
+    /// Packages the update is limited to, rather than all the packages of
+    /// the incident.
+    #[serde(default, rename = "package")]
+    pub packages: Vec<String>,
+    /// Binaries the update is limited to.
+    #[serde(default, rename = "binary")]
+    pub binaries: Vec<String>,
+    #[serde(default, rename = "releasetarget")]
+    pub release_targets: Vec<ReleaseTarget>,
+    /// Name of the update, rather than one derived from the packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// A message shown to users before installing the update.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, with = "flag", skip_serializing_if = "flag::is_unset")]
+    pub reboot_needed: bool,
+    #[serde(default, with = "flag", skip_serializing_if = "flag::is_unset")]
+    pub relogin_needed: bool,
+    #[serde(default, with = "flag", skip_serializing_if = "flag::is_unset")]
+    pub zypp_restart_needed: bool,
+    /// Elements not covered by the fields above, like `<embargo_date>` or
+    /// `<stopped>`, as raw XML. They are written back as they are, after
+    /// the others.
+    #[serde(skip)]
+    pub other_elements: Vec<String>,
+}
+
+impl Patchinfo {
+    pub fn from_xml(xml: &str) -> Result<Self> {
+        let mut patchinfo: Self = quick_xml::de::from_str(xml)?;
+        patchinfo.other_elements = meta::unknown_children(xml, KNOWN_ELEMENTS)?
+            .into_iter()
+            .map(str::to_owned)
+            .collect();
+        Ok(patchinfo)
+    }
+
+    pub fn to_xml(&self) -> Result<String> {
+        let mut xml = String::new();
+        let mut serializer = quick_xml::se::Serializer::new(&mut xml);
+        serializer.indent(' ', 2);
+        self.serialize(serializer)?;
+
+        // The summary and description are always written, so there is a
+        // closing tag.
+        if let Some(end) = xml.rfind("</patchinfo>") {
+            let other: String = self
+                .other_elements
+                .iter()
+                .map(|element| format!("  {element}\n"))
+                .collect();
+            xml.insert_str(end, &other);
+        }
+        xml.push('\n');
+        Ok(xml)
+    }
+}
+
+/// Options for [`ProjectBuilder::create_patchinfo`](crate::ProjectBuilder::create_patchinfo).
+#[derive(Clone, Debug, Default)]
+pub struct PatchinfoOptions {
+    /// Name of the package to create, `patchinfo` by default.
+    pub name: Option<String>,
+    /// Used as the summary of the new patchinfo.
+    pub comment: Option<String>,
+    /// Replace the patchinfo if the package already exists.
+    pub force: bool,
+}
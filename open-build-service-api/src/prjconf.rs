@@ -0,0 +1,306 @@
+//! Parser for project configurations (prjconf), as returned by
+//! [`ProjectBuilder::config`](crate::ProjectBuilder::config).
+//!
+//! Every line is kept verbatim, so a parsed configuration is written back
+//! exactly as it was read, apart from the lines that were edited.
+
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineKind {
+    Blank,
+    Comment,
+    /// A `Key: value` line, e.g. `Preinstall: bash` or `Type: dsc`.
+    Directive {
+        key: String,
+        value: String,
+    },
+    /// A line of `%if`, `%ifarch`, `%else`, `%endif` etc.
+    Conditional,
+    /// The `Macros:` line opening a macros block.
+    MacrosStart,
+    /// The `:Macros` line closing a macros block.
+    MacrosEnd,
+    /// A macro definition inside a macros block, kept as is.
+    Macro,
+    /// Anything else, e.g. a `%define` outside a macros block.
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Line {
+    raw: String,
+    kind: LineKind,
+    conditions: Vec<String>,
+}
+
+impl Line {
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    pub fn kind(&self) -> &LineKind {
+        &self.kind
+    }
+
+    /// The `%if` style lines this line is nested in, outermost first. For
+    /// lines in an `%else` branch, that line is the last one.
+    pub fn conditions(&self) -> &[String] {
+        &self.conditions
+    }
+
+    pub fn key(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::Directive { key, .. } => Some(key),
+            _ => None,
+        }
+    }
+
+    pub fn value(&self) -> Option<&str> {
+        match &self.kind {
+            LineKind::Directive { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    fn has_key(&self, key: &str) -> bool {
+        self.key().is_some_and(|k| k.eq_ignore_ascii_case(key))
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProjectConfig {
+    lines: Vec<Line>,
+    trailing_newline: bool,
+}
+
+fn parse_directive(line: &str) -> Option<(String, String)> {
+    let (key, value) = line.split_once(':')?;
+    let valid = key.chars().next()?.is_ascii_alphabetic()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then(|| (key.to_owned(), value.trim().to_owned()))
+}
+
+fn conditional_keyword(line: &str) -> Option<&str> {
+    let keyword = line.trim_start().strip_prefix('%')?;
+    let keyword = keyword
+        .split(|c: char| c.is_whitespace() || c == '(' || c == '{')
+        .next()?;
+    matches!(
+        keyword,
+        "if" | "ifarch" | "ifnarch" | "ifos" | "ifnos" | "elif" | "elifarch" | "else" | "endif"
+    )
+    .then_some(keyword)
+}
+
+impl ProjectConfig {
+    pub fn parse(config: &str) -> Self {
+        let mut lines = Vec::new();
+        let mut conditions: Vec<String> = Vec::new();
+        let mut in_macros = false;
+
+        // Split by hand rather than using lines(), to keep any \r.
+        let body = config.strip_suffix('\n').unwrap_or(config);
+        for raw in body.split('\n').filter(|_| !config.is_empty()) {
+            let trimmed = raw.trim();
+            let line_conditions = conditions.clone();
+            let kind = if in_macros {
+                if trimmed.eq_ignore_ascii_case(":macros") {
+                    in_macros = false;
+                    LineKind::MacrosEnd
+                } else {
+                    LineKind::Macro
+                }
+            } else if let Some(keyword) = conditional_keyword(raw) {
+                match keyword {
+                    "endif" => {
+                        conditions.pop();
+                    }
+                    "else" | "elif" | "elifarch" => {
+                        conditions.pop();
+                        conditions.push(trimmed.to_owned());
+                    }
+                    _ => conditions.push(trimmed.to_owned()),
+                }
+                LineKind::Conditional
+            } else if trimmed.is_empty() {
+                LineKind::Blank
+            } else if trimmed.starts_with('#') {
+                LineKind::Comment
+            } else if trimmed.eq_ignore_ascii_case("macros:") {
+                in_macros = true;
+                LineKind::MacrosStart
+            } else if let Some((key, value)) = parse_directive(trimmed) {
+                LineKind::Directive { key, value }
+            } else {
+                LineKind::Other
+            };
+
+            lines.push(Line {
+                raw: raw.to_owned(),
+                kind,
+                conditions: line_conditions,
+            });
+        }
+
+        Self {
+            lines,
+            trailing_newline: config.ends_with('\n'),
+        }
+    }
+
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Directive lines for `key`, compared case-insensitively like OBS does,
+    /// in all conditional branches.
+    pub fn directives<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a Line> + 'a {
+        self.lines.iter().filter(move |line| line.has_key(key))
+    }
+
+    /// The whitespace separated values of all `key` directives.
+    pub fn values(&self, key: &str) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|line| line.has_key(key))
+            .flat_map(|line| line.value().unwrap_or_default().split_whitespace())
+            .collect()
+    }
+
+    /// The value of the last `key` directive, which is the one taking effect
+    /// for single-valued keys like `Type`.
+    pub fn value(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .rfind(|line| line.has_key(key))
+            .and_then(Line::value)
+    }
+
+    pub fn type_(&self) -> Option<&str> {
+        self.value("Type")
+    }
+
+    pub fn repotype(&self) -> Vec<&str> {
+        self.values("Repotype")
+    }
+
+    pub fn preinstall(&self) -> Vec<&str> {
+        self.values("Preinstall")
+    }
+
+    pub fn support(&self) -> Vec<&str> {
+        self.values("Support")
+    }
+
+    pub fn prefer(&self) -> Vec<&str> {
+        self.values("Prefer")
+    }
+
+    /// `Substitute` directives, as the substituted dependency and its
+    /// replacements.
+    pub fn substitutions(&self) -> Vec<(&str, Vec<&str>)> {
+        self.lines
+            .iter()
+            .filter(|line| line.has_key("Substitute"))
+            .filter_map(|line| {
+                let mut words = line.value()?.split_whitespace();
+                Some((words.next()?, words.collect()))
+            })
+            .collect()
+    }
+
+    /// The lines of all macros blocks.
+    pub fn macros(&self) -> Vec<&str> {
+        self.lines
+            .iter()
+            .filter(|line| line.kind == LineKind::Macro)
+            .map(|line| line.raw.as_str())
+            .collect()
+    }
+
+    /// Sets the value of the first `key` directive outside of conditionals,
+    /// or [adds](Self::add) one if there is none.
+    pub fn set(&mut self, key: &str, value: &str) {
+        let existing = self
+            .lines
+            .iter_mut()
+            .find(|line| line.conditions.is_empty() && line.has_key(key));
+        match existing {
+            Some(line) => {
+                let LineKind::Directive { key, .. } = &line.kind else {
+                    unreachable!()
+                };
+                let key = key.clone();
+                line.raw = format!("{key}: {value}");
+                line.kind = LineKind::Directive {
+                    key,
+                    value: value.to_owned(),
+                };
+            }
+            None => self.add(key, value),
+        }
+    }
+
+    /// Where new directives go: before the first macros block, as a block
+    /// that isn't closed by `:Macros` extends to the end of the
+    /// configuration. The directive is kept out of any conditional around the
+    /// block, and before the blank lines leading up to it.
+    fn insert_position(&self) -> usize {
+        let Some(macros) = self
+            .lines
+            .iter()
+            .position(|line| line.kind == LineKind::MacrosStart)
+        else {
+            return self.lines.len();
+        };
+
+        let mut position = self.lines[..=macros]
+            .iter()
+            .rposition(|line| line.conditions.is_empty())
+            .unwrap_or(0);
+        while position > 0 && self.lines[position - 1].kind == LineKind::Blank {
+            position -= 1;
+        }
+        position
+    }
+
+    /// Adds a `key` directive outside of conditionals, at the end of the
+    /// configuration or right before its macros.
+    pub fn add(&mut self, key: &str, value: &str) {
+        let position = self.insert_position();
+        self.lines.insert(
+            position,
+            Line {
+                raw: format!("{key}: {value}"),
+                kind: LineKind::Directive {
+                    key: key.to_owned(),
+                    value: value.to_owned(),
+                },
+                conditions: Vec::new(),
+            },
+        );
+    }
+
+    /// Removes all `key` directives, returning how many there were.
+    pub fn remove(&mut self, key: &str) -> usize {
+        let len = self.lines.len();
+        self.lines.retain(|line| !line.has_key(key));
+        len - self.lines.len()
+    }
+}
+
+impl fmt::Display for ProjectConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i > 0 {
+                f.write_str("\n")?;
+            }
+            f.write_str(&line.raw)?;
+        }
+        if self.trailing_newline {
+            f.write_str("\n")?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::{Method, Response, StatusCode};
+
+/// Policy for retrying requests that failed due to transient errors, i.e.
+/// connection failures, timeouts and 429/502/503/504 responses.
+///
+/// Retries use exponential backoff with jitter, unless the server asked for
+/// a specific delay using `Retry-After`. Requests with a streaming body can't
+/// be replayed and are never retried.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts, also applied to delays
+    /// requested using `Retry-After`.
+    pub max_backoff: Duration,
+    /// Randomize each backoff between half and all of its nominal value.
+    pub jitter: bool,
+    /// Also retry requests that aren't idempotent, i.e. `POST` commands.
+    pub retry_non_idempotent: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            jitter: true,
+            retry_non_idempotent: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    pub fn initial_backoff(mut self, backoff: Duration) -> Self {
+        self.initial_backoff = backoff;
+        self
+    }
+
+    pub fn max_backoff(mut self, backoff: Duration) -> Self {
+        self.max_backoff = backoff;
+        self
+    }
+
+    pub fn jitter(mut self, jitter: bool) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn retry_non_idempotent(mut self, retry: bool) -> Self {
+        self.retry_non_idempotent = retry;
+        self
+    }
+
+    pub(crate) fn allows_retry(&self, method: &Method, attempt: u32) -> bool {
+        attempt < self.max_attempts
+            && (self.retry_non_idempotent
+                || matches!(
+                    *method,
+                    Method::GET
+                        | Method::HEAD
+                        | Method::PUT
+                        | Method::DELETE
+                        | Method::OPTIONS
+                        | Method::TRACE
+                ))
+    }
+
+    /// Delay before the next attempt if the response indicates a transient
+    /// failure.
+    pub(crate) fn delay_for_response(&self, response: &Response, attempt: u32) -> Option<Duration> {
+        if !is_transient_status(response.status()) {
+            return None;
+        }
+
+        Some(
+            retry_after(response)
+                .map(|delay| delay.min(self.max_backoff))
+                .unwrap_or_else(|| self.backoff(attempt)),
+        )
+    }
+
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.max_backoff);
+
+        if self.jitter {
+            let random = RandomState::new().hash_one(attempt);
+            backoff / 2 + backoff.mul_f64((random % 1000) as f64 / 2000.0)
+        } else {
+            backoff
+        }
+    }
+}
+
+pub(crate) fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+pub(crate) fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout() || error.is_request()
+}
+
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.trim().parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
+}
@@ -0,0 +1,457 @@
+use bytes::Bytes;
+use futures::prelude::*;
+use futures::stream::BoxStream;
+
+use crate::{
+    BinaryList, BranchOptions, BranchStatus, BuildHistory, BuildStatus, Client, CommitFileList,
+    CommitOptions, CommitResult, Directory, JobHistList, JobHistoryFilters, JobStatus,
+    PackageLogStreamOptions, PackageMeta, ProjectMeta, RebuildFilters, Result, ResultList,
+    RevisionList, SourceDirectory,
+};
+
+/// Project operations, as exposed by [`ProjectBuilder`](crate::ProjectBuilder).
+pub trait ProjectApi: Send + Sync {
+    fn project_meta(&self, project: &str) -> impl Future<Output = Result<ProjectMeta>> + Send;
+
+    fn delete_project(&self, project: &str) -> impl Future<Output = Result<()>> + Send;
+
+    fn list_packages(&self, project: &str) -> impl Future<Output = Result<Directory>> + Send;
+
+    fn repositories(&self, project: &str) -> impl Future<Output = Result<Vec<String>>> + Send;
+
+    fn arches(
+        &self,
+        project: &str,
+        repository: &str,
+    ) -> impl Future<Output = Result<Vec<String>>> + Send;
+}
+
+/// Package source operations, as exposed by
+/// [`PackageBuilder`](crate::PackageBuilder).
+pub trait PackageApi: Send + Sync {
+    fn create_package(
+        &self,
+        project: &str,
+        package: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn delete_package(
+        &self,
+        project: &str,
+        package: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn package_meta(
+        &self,
+        project: &str,
+        package: &str,
+    ) -> impl Future<Output = Result<PackageMeta>> + Send;
+
+    fn revisions(
+        &self,
+        project: &str,
+        package: &str,
+    ) -> impl Future<Output = Result<RevisionList>> + Send;
+
+    fn list(
+        &self,
+        project: &str,
+        package: &str,
+        rev: Option<&str>,
+    ) -> impl Future<Output = Result<SourceDirectory>> + Send;
+
+    fn list_meta(
+        &self,
+        project: &str,
+        package: &str,
+        rev: Option<&str>,
+    ) -> impl Future<Output = Result<SourceDirectory>> + Send;
+
+    fn source_file(
+        &self,
+        project: &str,
+        package: &str,
+        file: &str,
+    ) -> impl Future<Output = Result<BoxStream<'static, Result<Bytes>>>> + Send;
+
+    fn upload_for_commit(
+        &self,
+        project: &str,
+        package: &str,
+        file: &str,
+        data: Bytes,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn commit(
+        &self,
+        project: &str,
+        package: &str,
+        filelist: &CommitFileList,
+        options: &CommitOptions,
+    ) -> impl Future<Output = Result<CommitResult>> + Send;
+
+    fn branch(
+        &self,
+        project: &str,
+        package: &str,
+        options: &BranchOptions,
+    ) -> impl Future<Output = Result<BranchStatus>> + Send;
+}
+
+/// Build operations on projects and packages.
+pub trait BuildApi: Send + Sync {
+    fn project_result(&self, project: &str) -> impl Future<Output = Result<ResultList>> + Send;
+
+    fn package_result(
+        &self,
+        project: &str,
+        package: &str,
+    ) -> impl Future<Output = Result<ResultList>> + Send;
+
+    fn rebuild_project(
+        &self,
+        project: &str,
+        filters: &RebuildFilters,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn rebuild_package(
+        &self,
+        project: &str,
+        package: &str,
+    ) -> impl Future<Output = Result<()>> + Send;
+
+    fn jobhistory(
+        &self,
+        project: &str,
+        repository: &str,
+        arch: &str,
+        filters: &JobHistoryFilters,
+    ) -> impl Future<Output = Result<JobHistList>> + Send;
+
+    fn jobstatus(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> impl Future<Output = Result<JobStatus>> + Send;
+
+    fn build_history(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> impl Future<Output = Result<BuildHistory>> + Send;
+
+    fn build_status(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> impl Future<Output = Result<BuildStatus>> + Send;
+
+    fn binaries(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> impl Future<Output = Result<BinaryList>> + Send;
+
+    fn binary_file(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+        file: &str,
+    ) -> impl Future<Output = Result<BoxStream<'static, Result<Bytes>>>> + Send;
+
+    /// Returns size and mtime of the build log.
+    fn log_entry(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> impl Future<Output = Result<(usize, u64)>> + Send;
+
+    fn log_stream(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+        options: PackageLogStreamOptions,
+    ) -> Result<BoxStream<'_, Result<Bytes>>>;
+}
+
+/// All operations supported by [`Client`], so code can be written against
+/// other implementations, e.g. fakes or decorators adding caching or logging.
+pub trait ObsApi: ProjectApi + PackageApi + BuildApi {}
+
+impl<T: ProjectApi + PackageApi + BuildApi> ObsApi for T {}
+
+impl ProjectApi for Client {
+    async fn project_meta(&self, project: &str) -> Result<ProjectMeta> {
+        self.project(project.to_owned()).meta().await
+    }
+
+    async fn delete_project(&self, project: &str) -> Result<()> {
+        self.project(project.to_owned()).delete().await
+    }
+
+    async fn list_packages(&self, project: &str) -> Result<Directory> {
+        self.project(project.to_owned()).list_packages().await
+    }
+
+    async fn repositories(&self, project: &str) -> Result<Vec<String>> {
+        self.project(project.to_owned()).repositories().await
+    }
+
+    async fn arches(&self, project: &str, repository: &str) -> Result<Vec<String>> {
+        self.project(project.to_owned()).arches(repository).await
+    }
+}
+
+impl PackageApi for Client {
+    async fn create_package(&self, project: &str, package: &str) -> Result<()> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .create()
+            .await
+    }
+
+    async fn delete_package(&self, project: &str, package: &str) -> Result<()> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .delete()
+            .await
+    }
+
+    async fn package_meta(&self, project: &str, package: &str) -> Result<PackageMeta> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .meta()
+            .await
+    }
+
+    async fn revisions(&self, project: &str, package: &str) -> Result<RevisionList> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .revisions()
+            .await
+    }
+
+    async fn list(
+        &self,
+        project: &str,
+        package: &str,
+        rev: Option<&str>,
+    ) -> Result<SourceDirectory> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .list(rev)
+            .await
+    }
+
+    async fn list_meta(
+        &self,
+        project: &str,
+        package: &str,
+        rev: Option<&str>,
+    ) -> Result<SourceDirectory> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .list_meta(rev)
+            .await
+    }
+
+    async fn source_file(
+        &self,
+        project: &str,
+        package: &str,
+        file: &str,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let stream = self
+            .project(project.to_owned())
+            .package(package.to_owned())
+            .source_file(file)
+            .await?;
+        Ok(stream.boxed())
+    }
+
+    async fn upload_for_commit(
+        &self,
+        project: &str,
+        package: &str,
+        file: &str,
+        data: Bytes,
+    ) -> Result<()> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .upload_for_commit(file, data)
+            .await
+    }
+
+    async fn commit(
+        &self,
+        project: &str,
+        package: &str,
+        filelist: &CommitFileList,
+        options: &CommitOptions,
+    ) -> Result<CommitResult> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .commit(filelist, options)
+            .await
+    }
+
+    async fn branch(
+        &self,
+        project: &str,
+        package: &str,
+        options: &BranchOptions,
+    ) -> Result<BranchStatus> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .branch(options)
+            .await
+    }
+}
+
+impl BuildApi for Client {
+    async fn project_result(&self, project: &str) -> Result<ResultList> {
+        self.project(project.to_owned()).result().await
+    }
+
+    async fn package_result(&self, project: &str, package: &str) -> Result<ResultList> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .result()
+            .await
+    }
+
+    async fn rebuild_project(&self, project: &str, filters: &RebuildFilters) -> Result<()> {
+        self.project(project.to_owned()).rebuild(filters).await
+    }
+
+    async fn rebuild_package(&self, project: &str, package: &str) -> Result<()> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .rebuild()
+            .await
+    }
+
+    async fn jobhistory(
+        &self,
+        project: &str,
+        repository: &str,
+        arch: &str,
+        filters: &JobHistoryFilters,
+    ) -> Result<JobHistList> {
+        self.project(project.to_owned())
+            .jobhistory(repository, arch, filters)
+            .await
+    }
+
+    async fn jobstatus(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> Result<JobStatus> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .jobstatus(repository, arch)
+            .await
+    }
+
+    async fn build_history(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> Result<BuildHistory> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .history(repository, arch)
+            .await
+    }
+
+    async fn build_status(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> Result<BuildStatus> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .status(repository, arch)
+            .await
+    }
+
+    async fn binaries(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> Result<BinaryList> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .binaries(repository, arch)
+            .await
+    }
+
+    async fn binary_file(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+        file: &str,
+    ) -> Result<BoxStream<'static, Result<Bytes>>> {
+        let stream = self
+            .project(project.to_owned())
+            .package(package.to_owned())
+            .binary_file(repository, arch, file)
+            .await?;
+        Ok(stream.boxed())
+    }
+
+    async fn log_entry(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+    ) -> Result<(usize, u64)> {
+        self.project(project.to_owned())
+            .package(package.to_owned())
+            .log(repository, arch)
+            .entry()
+            .await
+    }
+
+    fn log_stream(
+        &self,
+        project: &str,
+        package: &str,
+        repository: &str,
+        arch: &str,
+        options: PackageLogStreamOptions,
+    ) -> Result<BoxStream<'_, Result<Bytes>>> {
+        let stream = self
+            .project(project.to_owned())
+            .package(package.to_owned())
+            .log(repository, arch)
+            .stream(options)?;
+        Ok(stream.boxed())
+    }
+}
@@ -0,0 +1,23 @@
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use reqwest::Response;
+
+use crate::Error;
+
+/// Sends the HTTP requests of a [`Client`](crate::Client).
+///
+/// By default requests are sent using a [`reqwest::Client`], but other
+/// transports can be used to e.g. serve requests in-process in tests.
+/// Authentication, session cookies, retries and error handling are done by the
+/// `Client`, so a transport only has to deliver the request as-is.
+pub trait Transport: Send + Sync {
+    fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, Result<Response, Error>>;
+}
+
+impl Transport for reqwest::Client {
+    fn execute(&self, request: reqwest::Request) -> BoxFuture<'_, Result<Response, Error>> {
+        reqwest::Client::execute(self, request)
+            .map_err(Into::into)
+            .boxed()
+    }
+}
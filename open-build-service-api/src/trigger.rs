@@ -0,0 +1,166 @@
+//! Client for the `/trigger` endpoints, which are used by webhooks and CI.
+
+use reqwest::Method;
+use reqwest::header::CONTENT_TYPE;
+use url::Url;
+
+use crate::{Client, Error, Result};
+
+/// What to trigger. Tokens scoped to a package always trigger that package,
+/// so for those the project and package can be left out.
+#[derive(Clone, Debug, Default)]
+pub struct TriggerOptions {
+    pub project: Option<String>,
+    pub package: Option<String>,
+    /// Only rebuild, or release the packages built, in this repository.
+    pub repository: Option<String>,
+    /// Only rebuild for this architecture.
+    pub arch: Option<String>,
+    /// Only release to this project and repository, rather than to all
+    /// release targets.
+    pub target_project: Option<String>,
+    pub target_repository: Option<String>,
+}
+
+/// The SCM sending a webhook to trigger workflows, see
+/// [`TriggerClient::workflow`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Scm {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl Scm {
+    fn event_header(&self) -> &'static str {
+        match self {
+            Scm::GitHub => "X-GitHub-Event",
+            Scm::GitLab => "X-Gitlab-Event",
+            Scm::Gitea => "X-Gitea-Event",
+        }
+    }
+}
+
+/// A client authenticated using an API token, as created by
+/// [`Client::create_token`], rather than user credentials.
+#[derive(Clone, Debug)]
+pub struct TriggerClient {
+    client: Client,
+}
+
+impl TriggerClient {
+    /// Creates a client using the given token and default settings.
+    pub fn new(url: Url, token: String) -> Result<Self> {
+        Ok(Self::from_client(
+            Client::builder(url).token_auth(token).build()?,
+        ))
+    }
+
+    /// Wraps a client created using [`Client::builder`], which should use
+    /// [`ClientBuilder::token_auth`](crate::ClientBuilder::token_auth).
+    pub fn from_client(client: Client) -> Self {
+        Self { client }
+    }
+
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Runs the source services of the package, for
+    /// [`TokenKind::Runservice`](crate::TokenKind::Runservice) tokens.
+    pub async fn runservice(&self, options: &TriggerOptions) -> Result<()> {
+        self.trigger("runservice", options, &[]).await
+    }
+
+    /// Rebuilds the package, for
+    /// [`TokenKind::Rebuild`](crate::TokenKind::Rebuild) tokens.
+    pub async fn rebuild(&self, options: &TriggerOptions) -> Result<()> {
+        self.trigger(
+            "rebuild",
+            options,
+            &[
+                ("repository", options.repository.as_deref()),
+                ("arch", options.arch.as_deref()),
+            ],
+        )
+        .await
+    }
+
+    /// Releases the built package, for
+    /// [`TokenKind::Release`](crate::TokenKind::Release) tokens.
+    pub async fn release(&self, options: &TriggerOptions) -> Result<()> {
+        self.trigger(
+            "release",
+            options,
+            &[
+                ("filter_source_repository", options.repository.as_deref()),
+                ("targetproject", options.target_project.as_deref()),
+                ("targetrepository", options.target_repository.as_deref()),
+            ],
+        )
+        .await
+    }
+
+    /// Runs the workflows in `.obs/workflows.yml` as if `scm` sent a webhook
+    /// for `event`, e.g. `pull_request` or `Merge Request Hook`, with
+    /// `payload` as its JSON body. Requires a
+    /// [`TokenKind::Workflow`](crate::TokenKind::Workflow) token, whose id
+    /// has to be given as well.
+    pub async fn workflow(
+        &self,
+        token_id: u64,
+        scm: Scm,
+        event: &str,
+        payload: String,
+    ) -> Result<()> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("trigger")
+            .push("workflow");
+        u.query_pairs_mut().append_pair("id", &token_id.to_string());
+
+        self.client
+            .send_with_error(
+                self.client
+                    .authenticated_request(Method::POST, u)
+                    .header(scm.event_header(), event)
+                    .header(CONTENT_TYPE, "application/json")
+                    .body(payload),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn trigger(
+        &self,
+        operation: &str,
+        options: &TriggerOptions,
+        params: &[(&str, Option<&str>)],
+    ) -> Result<()> {
+        let mut u = self.client.base.clone();
+        u.path_segments_mut()
+            .map_err(|_| Error::InvalidUrl)?
+            .push("trigger")
+            .push(operation);
+        {
+            let mut query = u.query_pairs_mut();
+            let target = [
+                ("project", options.project.as_deref()),
+                ("package", options.package.as_deref()),
+            ];
+            for (key, value) in target.iter().chain(params) {
+                if let Some(value) = value {
+                    query.append_pair(key, value);
+                }
+            }
+        }
+
+        self.client
+            .send_with_error(self.client.authenticated_request(Method::POST, u))
+            .await?;
+
+        Ok(())
+    }
+}
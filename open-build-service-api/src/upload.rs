@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use bytes::Bytes;
+use futures::prelude::*;
+use md5::{Digest, Md5};
+use reqwest::Body;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::CommitFileEntry;
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug)]
+pub struct UploadProgress {
+    pub uploaded: u64,
+    /// Total size, if known up front.
+    pub total: Option<u64>,
+}
+
+#[derive(Clone, Default)]
+pub struct UploadOptions {
+    /// Called after every chunk read from the source, and once more when the
+    /// upload finished.
+    pub progress: Option<Arc<dyn Fn(UploadProgress) + Send + Sync>>,
+}
+
+impl std::fmt::Debug for UploadOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("UploadOptions")
+            .field("progress", &self.progress.is_some())
+            .finish()
+    }
+}
+
+#[derive(Default)]
+struct Digested {
+    md5: Md5,
+    size: u64,
+}
+
+/// Request body streaming from a reader, hashing the contents on the way.
+pub(crate) struct HashingBody {
+    digested: Arc<Mutex<Digested>>,
+    total: Option<u64>,
+    progress: Option<Arc<dyn Fn(UploadProgress) + Send + Sync>>,
+}
+
+impl HashingBody {
+    pub fn new<R>(reader: R, total: Option<u64>, options: &UploadOptions) -> (Self, Body)
+    where
+        R: AsyncRead + Send + 'static,
+    {
+        let digested = Arc::new(Mutex::new(Digested::default()));
+        let hashing = Self {
+            digested: digested.clone(),
+            total,
+            progress: options.progress.clone(),
+        };
+
+        let progress = options.progress.clone();
+        let chunks = read_chunks(reader).inspect_ok(move |chunk| {
+            let uploaded = {
+                let mut digested = digested.lock().unwrap();
+                digested.md5.update(chunk);
+                digested.size += chunk.len() as u64;
+                digested.size
+            };
+            if let Some(progress) = &progress {
+                progress(UploadProgress { uploaded, total });
+            }
+        });
+
+        (hashing, Body::wrap_stream(chunks))
+    }
+
+    /// Returns the entry for the uploaded contents, once the body was sent.
+    pub fn finish(self, name: String) -> CommitFileEntry {
+        let digested = std::mem::take(&mut *self.digested.lock().unwrap());
+        if let Some(progress) = &self.progress {
+            progress(UploadProgress {
+                uploaded: digested.size,
+                total: self.total,
+            });
+        }
+
+        CommitFileEntry {
+            name,
+            md5: base16ct::lower::encode_string(&digested.md5.finalize()),
+        }
+    }
+}
+
+/// Streams the contents of `reader` in chunks of up to [`CHUNK_SIZE`].
+pub(crate) fn read_chunks<R>(
+    reader: R,
+) -> impl Stream<Item = std::io::Result<Bytes>> + Send + 'static
+where
+    R: AsyncRead + Send + 'static,
+{
+    stream::try_unfold(Box::pin(reader), |mut reader| async move {
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let len = reader.read(&mut chunk).await?;
+        if len == 0 {
+            return Ok(None);
+        }
+        chunk.truncate(len);
+        Ok(Some((Bytes::from(chunk), reader)))
+    })
+}
+
+/// Computes the md5 of a local file without reading it into memory at once.
+pub(crate) async fn md5_path(path: &Path) -> std::io::Result<String> {
+    let file = tokio::fs::File::open(path).await?;
+    let md5 = read_chunks(file)
+        .try_fold(Md5::new(), |mut md5, chunk| async move {
+            md5.update(&chunk);
+            Ok(md5)
+        })
+        .await?;
+    Ok(base16ct::lower::encode_string(&md5.finalize()))
+}
+
+/// Matches `name` against a shell-style pattern supporting `*` and `?`.
+pub(crate) fn matches_pattern(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let (mut p, mut n) = (0, 0);
+    // Position of the last `*` seen, and the name position it was tried at.
+    let mut backtrack = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match backtrack {
+                Some((star, tried)) => {
+                    p = star + 1;
+                    n = tried + 1;
+                    backtrack = Some((star, tried + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
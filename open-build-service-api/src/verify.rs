@@ -0,0 +1,793 @@
+//! OpenPGP signature verification of repository metadata and packages, using
+//! keys like the one returned by
+//! [`ProjectBuilder::pubkey`](crate::ProjectBuilder::pubkey).
+//!
+//! Only the subset of OpenPGP used by OBS is supported: RSA keys, with
+//! version 3 and 4 signatures using SHA-1 or SHA-2 hashes. Key expiry and
+//! revocations are not taken into account.
+
+use base64ct::{Base64, Encoding};
+use rsa::traits::PublicKeyParts;
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use sha1::Sha1;
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+use thiserror::Error;
+
+const TAG_SIGNATURE: u8 = 2;
+const TAG_PUBLIC_KEY: u8 = 6;
+const TAG_PUBLIC_SUBKEY: u8 = 14;
+
+const SIG_BINARY: u8 = 0x00;
+const SIG_TEXT: u8 = 0x01;
+
+const SUBPACKET_CREATED: u8 = 2;
+const SUBPACKET_ISSUER: u8 = 16;
+const SUBPACKET_ISSUER_FINGERPRINT: u8 = 33;
+
+const RPM_LEAD_SIZE: usize = 96;
+const RPM_HEADER_MAGIC: [u8; 8] = [0x8e, 0xad, 0xe8, 0x01, 0, 0, 0, 0];
+const RPM_BIN_TYPE: u32 = 7;
+const RPMSIGTAG_DSA: u32 = 267;
+const RPMSIGTAG_RSA: u32 = 268;
+const RPMSIGTAG_PGP: u32 = 1002;
+const RPMSIGTAG_GPG: u32 = 1005;
+const RPMTAG_PAYLOADDIGEST: u32 = 5092;
+const RPMTAG_PAYLOADDIGESTALGO: u32 = 5093;
+
+// Larger than the 4096 bits the rsa crate allows by default, as some
+// projects use bigger keys.
+const MAX_KEY_SIZE: usize = 8192;
+
+#[derive(Error, Debug)]
+pub enum VerifyError {
+    #[error("No signature found")]
+    Unsigned,
+    #[error("Signed by unknown key {0}")]
+    UnknownKey(String),
+    #[error("Unsupported {0}")]
+    Unsupported(String),
+    #[error("Malformed {0}")]
+    Malformed(&'static str),
+    #[error("Bad signature by key {0}")]
+    BadSignature(String),
+    #[error("Payload not covered by any signature")]
+    UnsignedPayload,
+    #[error("Payload doesn't match the signed digest")]
+    PayloadMismatch,
+}
+
+type Result<T> = std::result::Result<T, VerifyError>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedSignature {
+    /// Fingerprint of the key that made the signature, as lowercase hex.
+    pub fingerprint: String,
+    /// Creation time of the signature as a unix timestamp.
+    pub created: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedText {
+    /// The signed text, with dash-escaping undone.
+    pub text: String,
+    pub signature: VerifiedSignature,
+}
+
+#[derive(Clone, Debug)]
+struct Key {
+    fingerprint: [u8; 20],
+    /// `None` for keys using algorithms other than RSA.
+    rsa: Option<RsaPublicKey>,
+    algorithm: u8,
+}
+
+impl Key {
+    fn id(&self) -> [u8; 8] {
+        self.fingerprint[12..].try_into().unwrap()
+    }
+}
+
+/// A set of public keys to verify signatures against.
+#[derive(Clone, Debug, Default)]
+pub struct Keyring {
+    keys: Vec<Key>,
+}
+
+#[derive(Debug)]
+struct Signature<'a> {
+    sig_type: u8,
+    algorithm: u8,
+    hash: u8,
+    issuer: Option<[u8; 8]>,
+    created: u64,
+    /// Data hashed after the signed contents.
+    trailer: Vec<u8>,
+    left16: [u8; 2],
+    mpi: &'a [u8],
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if len > self.data.len() {
+            return Err(VerifyError::Malformed("packet"));
+        }
+        let (head, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn mpi(&mut self) -> Result<&'a [u8]> {
+        let bits = self.u16()? as usize;
+        self.take(bits.div_ceil(8))
+    }
+
+    fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+}
+
+struct RpmEntry {
+    tag: u32,
+    kind: u32,
+    offset: usize,
+    count: usize,
+}
+
+/// An rpm header structure, as used for both the signature header and the
+/// main header.
+struct RpmHeader<'a> {
+    index: &'a [u8],
+    store: &'a [u8],
+    /// Length of the whole header, including the magic and the counts.
+    len: usize,
+}
+
+impl<'a> RpmHeader<'a> {
+    fn parse(data: &'a [u8]) -> Result<Self> {
+        let malformed = || VerifyError::Malformed("rpm header");
+        let mut reader = Reader::new(data);
+        if reader.take(8).map_err(|_| malformed())? != RPM_HEADER_MAGIC {
+            return Err(malformed());
+        }
+        let entries = reader.u32().map_err(|_| malformed())? as usize;
+        let store_len = reader.u32().map_err(|_| malformed())? as usize;
+        let index_len = entries.checked_mul(16).ok_or_else(malformed)?;
+        let index = reader.take(index_len).map_err(|_| malformed())?;
+        let store = reader.take(store_len).map_err(|_| malformed())?;
+        Ok(Self {
+            index,
+            store,
+            len: 16 + index_len + store_len,
+        })
+    }
+
+    fn entries(&self) -> impl Iterator<Item = RpmEntry> + '_ {
+        self.index.chunks_exact(16).map(|entry| RpmEntry {
+            tag: u32::from_be_bytes(entry[0..4].try_into().unwrap()),
+            kind: u32::from_be_bytes(entry[4..8].try_into().unwrap()),
+            offset: u32::from_be_bytes(entry[8..12].try_into().unwrap()) as usize,
+            count: u32::from_be_bytes(entry[12..16].try_into().unwrap()) as usize,
+        })
+    }
+
+    /// The data of a binary entry, or the store from the start of any other
+    /// entry, as their size isn't given by their count.
+    fn data(&self, entry: &RpmEntry) -> Result<&'a [u8]> {
+        let data = self
+            .store
+            .get(entry.offset..)
+            .ok_or(VerifyError::Malformed("rpm header"))?;
+        if entry.kind != RPM_BIN_TYPE {
+            return Ok(data);
+        }
+        data.get(..entry.count)
+            .ok_or(VerifyError::Malformed("rpm header"))
+    }
+
+    /// Checks `payload` against the payload digest in this header.
+    fn verify_payload_digest(&self, payload: &[u8]) -> Result<()> {
+        let entry = self
+            .entries()
+            .find(|entry| entry.tag == RPMTAG_PAYLOADDIGEST)
+            .ok_or(VerifyError::UnsignedPayload)?;
+        // A string array, of which only the first one is used.
+        let expected = self.data(&entry)?;
+        let expected = expected.split(|&b| b == 0).next().unwrap_or_default();
+
+        let algorithm = match self
+            .entries()
+            .find(|entry| entry.tag == RPMTAG_PAYLOADDIGESTALGO)
+        {
+            Some(entry) => {
+                let data = self.data(&entry)?;
+                let data = data.get(..4).ok_or(VerifyError::Malformed("rpm header"))?;
+                u32::from_be_bytes(data.try_into().unwrap())
+            }
+            // SHA-256 if not given; rpm uses the OpenPGP algorithm numbers.
+            None => 8,
+        };
+        let actual = match algorithm {
+            2 => digest::<Sha1>(payload, &[]),
+            8 => digest::<Sha256>(payload, &[]),
+            9 => digest::<Sha384>(payload, &[]),
+            10 => digest::<Sha512>(payload, &[]),
+            11 => digest::<Sha224>(payload, &[]),
+            algorithm => {
+                return Err(VerifyError::Unsupported(format!(
+                    "payload digest algorithm {algorithm}"
+                )));
+            }
+        };
+        if hex(&actual).as_bytes() != expected {
+            return Err(VerifyError::PayloadMismatch);
+        }
+        Ok(())
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    base16ct::lower::encode_string(bytes)
+}
+
+/// Splits binary OpenPGP data into its packets, as tag and body.
+fn packets(data: &[u8]) -> Result<Vec<(u8, &[u8])>> {
+    let mut reader = Reader::new(data);
+    let mut packets = Vec::new();
+    while !reader.is_empty() {
+        let header = reader.u8()?;
+        if header & 0x80 == 0 {
+            return Err(VerifyError::Malformed("packet header"));
+        }
+
+        let (tag, len) = if header & 0x40 != 0 {
+            let len = match reader.u8()? {
+                len @ 0..192 => len as usize,
+                first @ 192..224 => ((first as usize - 192) << 8) + reader.u8()? as usize + 192,
+                255 => reader.u32()? as usize,
+                _ => {
+                    return Err(VerifyError::Unsupported(
+                        "partial packet lengths".to_owned(),
+                    ));
+                }
+            };
+            (header & 0x3f, len)
+        } else {
+            let len = match header & 0x03 {
+                0 => reader.u8()? as usize,
+                1 => reader.u16()? as usize,
+                2 => reader.u32()? as usize,
+                _ => reader.data.len(),
+            };
+            ((header >> 2) & 0x0f, len)
+        };
+        packets.push((tag, reader.take(len)?));
+    }
+    Ok(packets)
+}
+
+fn crc24(data: &[u8]) -> u32 {
+    let mut crc = 0xb704ce_u32;
+    for &byte in data {
+        crc ^= (byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x1000000 != 0 {
+                crc ^= 0x1864cfb;
+            }
+        }
+    }
+    crc & 0xffffff
+}
+
+/// Decodes all ASCII armored blocks of the given kind, e.g. `SIGNATURE`.
+fn dearmor(armored: &str, kind: &str) -> Result<Vec<Vec<u8>>> {
+    let begin = format!("-----BEGIN PGP {kind}-----");
+    let end = format!("-----END PGP {kind}-----");
+
+    let mut blocks = Vec::new();
+    let mut lines = armored.lines().map(str::trim_end);
+    while lines.any(|line| line == begin) {
+        // Skip the armor headers.
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+        }
+
+        let mut base64 = String::new();
+        let mut checksum = None;
+        for line in lines.by_ref() {
+            if line == end {
+                break;
+            } else if let Some(crc) = line.strip_prefix('=') {
+                checksum = Some(crc.to_owned());
+            } else {
+                base64.push_str(line);
+            }
+        }
+
+        let data =
+            Base64::decode_vec(&base64).map_err(|_| VerifyError::Malformed("ASCII armor"))?;
+        if let Some(checksum) = checksum {
+            let crc = Base64::decode_vec(&checksum)
+                .map_err(|_| VerifyError::Malformed("ASCII armor checksum"))?;
+            if crc[..] != crc24(&data).to_be_bytes()[1..] {
+                return Err(VerifyError::Malformed("ASCII armor checksum"));
+            }
+        }
+        blocks.push(data);
+    }
+    Ok(blocks)
+}
+
+/// Returns the binary form of possibly ASCII armored OpenPGP data.
+fn binary_or_dearmor(data: &[u8], kind: &str) -> Result<Vec<Vec<u8>>> {
+    if data.first().is_some_and(|b| b & 0x80 != 0) {
+        return Ok(vec![data.to_vec()]);
+    }
+    let armored = std::str::from_utf8(data).map_err(|_| VerifyError::Malformed("ASCII armor"))?;
+    dearmor(armored, kind)
+}
+
+fn parse_key(body: &[u8]) -> Result<Option<Key>> {
+    let mut reader = Reader::new(body);
+    // Only version 4 keys are used in practice; older and newer ones are
+    // skipped rather than rejecting the whole keyring.
+    if reader.u8()? != 4 {
+        return Ok(None);
+    }
+    reader.u32()?;
+    let algorithm = reader.u8()?;
+
+    let rsa = match algorithm {
+        // RSA (encrypt or sign) and RSA (sign only)
+        1 | 3 => {
+            let n = BigUint::from_bytes_be(reader.mpi()?);
+            let e = BigUint::from_bytes_be(reader.mpi()?);
+            Some(
+                RsaPublicKey::new_with_max_size(n, e, MAX_KEY_SIZE)
+                    .map_err(|_| VerifyError::Malformed("RSA key"))?,
+            )
+        }
+        _ => None,
+    };
+
+    let mut fingerprint = Sha1::new();
+    fingerprint.update([0x99]);
+    fingerprint.update((body.len() as u16).to_be_bytes());
+    fingerprint.update(body);
+
+    Ok(Some(Key {
+        fingerprint: fingerprint.finalize().into(),
+        rsa,
+        algorithm,
+    }))
+}
+
+/// Reads the subpackets of a v4 signature. Only the `hashed` area is covered
+/// by the signature, so the unhashed one is only used for the issuer, which
+/// is just a hint for finding the key.
+fn parse_subpackets(data: &[u8], hashed: bool, signature: &mut Signature) -> Result<()> {
+    let mut reader = Reader::new(data);
+    while !reader.is_empty() {
+        let len = match reader.u8()? {
+            len @ 0..192 => len as usize,
+            first @ 192..255 => ((first as usize - 192) << 8) + reader.u8()? as usize + 192,
+            255 => reader.u32()? as usize,
+        };
+        let mut subpacket = Reader::new(reader.take(len)?);
+        // The top bit only marks the subpacket as critical.
+        let kind = subpacket.u8()? & 0x7f;
+        if !hashed && (kind == SUBPACKET_CREATED || signature.issuer.is_some()) {
+            continue;
+        }
+        match kind {
+            SUBPACKET_CREATED => signature.created = subpacket.u32()? as u64,
+            SUBPACKET_ISSUER => signature.issuer = Some(subpacket.take(8)?.try_into().unwrap()),
+            SUBPACKET_ISSUER_FINGERPRINT if subpacket.u8()? == 4 => {
+                let fingerprint = subpacket.take(20)?;
+                signature.issuer = Some(fingerprint[12..].try_into().unwrap());
+            }
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+fn parse_signature(body: &[u8]) -> Result<Signature<'_>> {
+    let mut reader = Reader::new(body);
+    match reader.u8()? {
+        3 => {
+            if reader.u8()? != 5 {
+                return Err(VerifyError::Malformed("signature"));
+            }
+            let hashed = reader.take(5)?;
+            let issuer = reader.take(8)?.try_into().unwrap();
+            let algorithm = reader.u8()?;
+            let hash = reader.u8()?;
+            let left16 = reader.take(2)?.try_into().unwrap();
+            Ok(Signature {
+                sig_type: hashed[0],
+                algorithm,
+                hash,
+                issuer: Some(issuer),
+                created: u32::from_be_bytes(hashed[1..].try_into().unwrap()) as u64,
+                trailer: hashed.to_vec(),
+                left16,
+                mpi: reader.data,
+            })
+        }
+        4 => {
+            let sig_type = reader.u8()?;
+            let algorithm = reader.u8()?;
+            let hash = reader.u8()?;
+            let hashed_len = reader.u16()? as usize;
+            let hashed = reader.take(hashed_len)?;
+            let unhashed_len = reader.u16()? as usize;
+            let unhashed = reader.take(unhashed_len)?;
+            let left16 = reader.take(2)?.try_into().unwrap();
+
+            let mut trailer = body[..6 + hashed_len].to_vec();
+            trailer.extend_from_slice(&[0x04, 0xff]);
+            trailer.extend_from_slice(&(6 + hashed_len as u32).to_be_bytes());
+
+            let mut signature = Signature {
+                sig_type,
+                algorithm,
+                hash,
+                issuer: None,
+                created: 0,
+                trailer,
+                left16,
+                mpi: reader.data,
+            };
+            parse_subpackets(hashed, true, &mut signature)?;
+            parse_subpackets(unhashed, false, &mut signature)?;
+            Ok(signature)
+        }
+        version => Err(VerifyError::Unsupported(format!(
+            "signature version {version}"
+        ))),
+    }
+}
+
+/// Normalizes line endings to CRLF, as hashed for text signatures.
+fn canonical_text(data: &[u8]) -> Vec<u8> {
+    let mut text = Vec::with_capacity(data.len());
+    let mut previous = 0;
+    for &byte in data {
+        if byte == b'\n' && previous != b'\r' {
+            text.push(b'\r');
+        }
+        text.push(byte);
+        previous = byte;
+    }
+    text
+}
+
+fn digest<D: Digest>(data: &[u8], trailer: &[u8]) -> Vec<u8> {
+    let mut digest = D::new();
+    digest.update(data);
+    digest.update(trailer);
+    digest.finalize().to_vec()
+}
+
+impl Keyring {
+    /// Reads the keys from one or more ASCII armored public key blocks.
+    pub fn from_armored(armored: &str) -> Result<Self> {
+        let mut keyring = Self::default();
+        for block in dearmor(armored, "PUBLIC KEY BLOCK")? {
+            keyring.add_binary(&block)?;
+        }
+        if keyring.keys.is_empty() {
+            return Err(VerifyError::Malformed("public key"));
+        }
+        Ok(keyring)
+    }
+
+    /// Reads the keys from binary OpenPGP data.
+    pub fn from_binary(data: &[u8]) -> Result<Self> {
+        let mut keyring = Self::default();
+        keyring.add_binary(data)?;
+        if keyring.keys.is_empty() {
+            return Err(VerifyError::Malformed("public key"));
+        }
+        Ok(keyring)
+    }
+
+    fn add_binary(&mut self, data: &[u8]) -> Result<()> {
+        for (tag, body) in packets(data)? {
+            if (tag == TAG_PUBLIC_KEY || tag == TAG_PUBLIC_SUBKEY)
+                && let Some(key) = parse_key(body)?
+            {
+                self.keys.push(key);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the keys of another keyring, e.g. to also accept signatures
+    /// by the keys of projects this one builds against.
+    pub fn extend(&mut self, other: Keyring) {
+        self.keys.extend(other.keys);
+    }
+
+    /// Fingerprints of the primary keys and subkeys, as lowercase hex.
+    pub fn fingerprints(&self) -> Vec<String> {
+        self.keys.iter().map(|key| hex(&key.fingerprint)).collect()
+    }
+
+    fn verify_signature(&self, signature: &Signature, data: &[u8]) -> Result<VerifiedSignature> {
+        let keys: Vec<_> = self
+            .keys
+            .iter()
+            .filter(|key| signature.issuer.is_none_or(|issuer| issuer == key.id()))
+            .collect();
+        let Some(issuer) = keys.first() else {
+            let issuer = signature.issuer.map(|issuer| hex(&issuer));
+            return Err(VerifyError::UnknownKey(issuer.unwrap_or_default()));
+        };
+
+        let canonical;
+        let data = match signature.sig_type {
+            SIG_BINARY => data,
+            SIG_TEXT => {
+                canonical = canonical_text(data);
+                &canonical
+            }
+            sig_type => {
+                return Err(VerifyError::Unsupported(format!(
+                    "signature type {sig_type:#04x}"
+                )));
+            }
+        };
+
+        let trailer = &signature.trailer;
+        let (hashed, scheme) = match signature.hash {
+            2 => (digest::<Sha1>(data, trailer), Pkcs1v15Sign::new::<Sha1>()),
+            8 => (
+                digest::<Sha256>(data, trailer),
+                Pkcs1v15Sign::new::<Sha256>(),
+            ),
+            9 => (
+                digest::<Sha384>(data, trailer),
+                Pkcs1v15Sign::new::<Sha384>(),
+            ),
+            10 => (
+                digest::<Sha512>(data, trailer),
+                Pkcs1v15Sign::new::<Sha512>(),
+            ),
+            11 => (
+                digest::<Sha224>(data, trailer),
+                Pkcs1v15Sign::new::<Sha224>(),
+            ),
+            hash => {
+                return Err(VerifyError::Unsupported(format!("hash algorithm {hash}")));
+            }
+        };
+
+        if !matches!(signature.algorithm, 1 | 3) {
+            return Err(VerifyError::Unsupported(format!(
+                "public key algorithm {}",
+                signature.algorithm
+            )));
+        }
+        // The first bytes of the hash are included for a quick check.
+        if hashed[..2] != signature.left16 {
+            return Err(VerifyError::BadSignature(hex(&issuer.id())));
+        }
+        let mpi = Reader::new(signature.mpi).mpi()?;
+
+        for key in &keys {
+            let Some(rsa) = &key.rsa else {
+                continue;
+            };
+            // Signatures can't be larger than the modulus, but their leading
+            // zero bytes are dropped in the encoding.
+            if mpi.len() > rsa.size() {
+                continue;
+            }
+            let mut padded = vec![0; rsa.size() - mpi.len()];
+            padded.extend_from_slice(mpi);
+            if rsa.verify(scheme.clone(), &hashed, &padded).is_ok() {
+                return Ok(VerifiedSignature {
+                    fingerprint: hex(&key.fingerprint),
+                    created: signature.created,
+                });
+            }
+        }
+
+        if keys.iter().all(|key| key.rsa.is_none()) {
+            return Err(VerifyError::Unsupported(format!(
+                "public key algorithm {}",
+                issuer.algorithm
+            )));
+        }
+        Err(VerifyError::BadSignature(hex(&issuer.id())))
+    }
+
+    /// Verifies binary OpenPGP signature packets, succeeding if any of them
+    /// is valid.
+    fn verify_packets(&self, data: &[u8], signature: &[u8]) -> Result<VerifiedSignature> {
+        let mut result = Err(VerifyError::Unsigned);
+        for (tag, body) in packets(signature)? {
+            if tag != TAG_SIGNATURE {
+                continue;
+            }
+            result = parse_signature(body).and_then(|sig| self.verify_signature(&sig, data));
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Verifies a detached signature, either binary or ASCII armored, like
+    /// `Release.gpg` or `repomd.xml.asc`.
+    pub fn verify_detached(&self, data: &[u8], signature: &[u8]) -> Result<VerifiedSignature> {
+        let mut result = Err(VerifyError::Unsigned);
+        for block in binary_or_dearmor(signature, "SIGNATURE")? {
+            result = self.verify_packets(data, &block);
+            if result.is_ok() {
+                break;
+            }
+        }
+        result
+    }
+
+    /// Verifies a message with an inline cleartext signature, like
+    /// `InRelease`, returning the signed text.
+    pub fn verify_cleartext(&self, message: &str) -> Result<VerifiedText> {
+        let mut lines = message.lines();
+        if !lines.any(|line| line.trim_end() == "-----BEGIN PGP SIGNED MESSAGE-----") {
+            return Err(VerifyError::Unsigned);
+        }
+        // Skip the armor headers, like the hash algorithm used.
+        for line in lines.by_ref() {
+            if line.trim_end().is_empty() {
+                break;
+            }
+        }
+
+        let mut text = Vec::new();
+        let mut signed_text = false;
+        for line in lines.by_ref() {
+            if line.trim_end() == "-----BEGIN PGP SIGNATURE-----" {
+                signed_text = true;
+                break;
+            }
+            text.push(line.strip_prefix("- ").unwrap_or(line));
+        }
+        if !signed_text {
+            return Err(VerifyError::Malformed("cleartext signature"));
+        }
+
+        // Trailing whitespace isn't part of the signed text, and neither is
+        // the final line ending.
+        let signed = text
+            .iter()
+            .map(|line| line.trim_end_matches([' ', '\t', '\r']))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        let armored = format!(
+            "-----BEGIN PGP SIGNATURE-----\n{}",
+            lines.collect::<Vec<_>>().join("\n")
+        );
+        let signature = self.verify_detached(signed.as_bytes(), armored.as_bytes())?;
+
+        let mut text = text.join("\n");
+        text.push('\n');
+        Ok(VerifiedText { text, signature })
+    }
+
+    /// Verifies the signatures in the signature header of an rpm package.
+    /// All the signatures present have to be valid, and the payload has to be
+    /// covered by one of them, either directly or by the payload digest in a
+    /// signed header.
+    pub fn verify_rpm(&self, rpm: &[u8]) -> Result<VerifiedSignature> {
+        if rpm.len() < RPM_LEAD_SIZE || rpm[..4] != [0xed, 0xab, 0xee, 0xdb] {
+            return Err(VerifyError::Malformed("rpm lead"));
+        }
+        let sigheader = RpmHeader::parse(&rpm[RPM_LEAD_SIZE..])?;
+        let header_start = RPM_LEAD_SIZE + sigheader.len.next_multiple_of(8);
+        let header = RpmHeader::parse(
+            rpm.get(header_start..)
+                .ok_or(VerifyError::Malformed("rpm header"))?,
+        )?;
+        let header_and_payload = &rpm[header_start..];
+        let payload = &header_and_payload[header.len..];
+
+        let mut result = Err(VerifyError::Unsigned);
+        let mut payload_signed = false;
+        for entry in sigheader.entries() {
+            let (signed, covers_payload) = match entry.tag {
+                RPMSIGTAG_RSA | RPMSIGTAG_DSA => (&header_and_payload[..header.len], false),
+                RPMSIGTAG_PGP | RPMSIGTAG_GPG => (header_and_payload, true),
+                _ => continue,
+            };
+            let signature = sigheader.data(&entry)?;
+            result = Ok(self.verify_packets(signed, signature)?);
+            payload_signed |= covers_payload;
+        }
+        let verified = result?;
+
+        if !payload_signed {
+            header.verify_payload_digest(payload)?;
+        }
+
+        Ok(verified)
+    }
+
+    /// Verifies a deb package signed using debsigs, with the signature of
+    /// its other members in a `_gpgorigin` member.
+    pub fn verify_deb(&self, deb: &[u8]) -> Result<VerifiedSignature> {
+        let mut reader = Reader::new(
+            deb.strip_prefix(b"!<arch>\n")
+                .ok_or(VerifyError::Malformed("deb archive"))?,
+        );
+
+        let mut signed = Vec::new();
+        let mut signature = None;
+        while !reader.is_empty() {
+            let header = reader.take(60)?;
+            let name = std::str::from_utf8(&header[..16])
+                .map_err(|_| VerifyError::Malformed("deb archive"))?
+                .trim_end()
+                .trim_end_matches('/');
+            let size = std::str::from_utf8(&header[48..58])
+                .ok()
+                .and_then(|size| size.trim_end().parse().ok())
+                .ok_or(VerifyError::Malformed("deb archive"))?;
+            let contents = reader.take(size)?;
+            // Members are aligned to even offsets.
+            if size % 2 == 1 && !reader.is_empty() {
+                reader.take(1)?;
+            }
+
+            match name {
+                "_gpgorigin" => signature = Some(contents),
+                "_gpgbuilder" => {
+                    return Err(VerifyError::Unsupported("dpkg-sig signatures".to_owned()));
+                }
+                _ => signed.extend_from_slice(contents),
+            }
+        }
+
+        let signature = signature.ok_or(VerifyError::Unsigned)?;
+        self.verify_detached(&signed, signature)
+    }
+
+    /// Verifies a package downloaded from a repository, based on the
+    /// extension of its file name.
+    pub fn verify_package(&self, filename: &str, data: &[u8]) -> Result<VerifiedSignature> {
+        if filename.ends_with(".rpm") {
+            self.verify_rpm(data)
+        } else if filename.ends_with(".deb") || filename.ends_with(".udeb") {
+            self.verify_deb(data)
+        } else {
+            Err(VerifyError::Unsupported(format!(
+                "package type of {filename}"
+            )))
+        }
+    }
+}
@@ -0,0 +1,391 @@
+//! Parser and validator for the SCM/CI workflows configured in
+//! `.obs/workflows.yml`, which OBS runs when triggered by
+//! [`TriggerClient::workflow`](crate::TriggerClient::workflow).
+
+use std::fmt;
+
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use thiserror::Error;
+
+/// Placeholders OBS substitutes in the values of steps.
+pub const PLACEHOLDERS: &[&str] = &[
+    "SCM_ORGANIZATION_NAME",
+    "SCM_REPOSITORY_NAME",
+    "SCM_PR_NUMBER",
+    "SCM_COMMIT_SHA",
+];
+
+#[derive(Error, Debug)]
+#[error("{0}")]
+pub struct ParseError(#[from] serde_yaml_ng::Error);
+
+impl ParseError {
+    /// Line and column of the error, both starting at 1.
+    pub fn location(&self) -> Option<(usize, usize)> {
+        self.0
+            .location()
+            .map(|location| (location.line(), location.column()))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Event {
+    Push,
+    TagPush,
+    PullRequest,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BranchFilter {
+    #[serde(default)]
+    pub only: Vec<String>,
+    #[serde(default)]
+    pub ignore: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Filters {
+    pub event: Option<Event>,
+    pub branches: Option<BranchFilter>,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AddRepositories {
+    Enabled,
+    Disabled,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct BranchPackage {
+    pub source_project: String,
+    pub source_package: String,
+    pub target_project: String,
+    /// Whether to add the repositories of the source project to the branch.
+    pub add_repositories: Option<AddRepositories>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct LinkPackage {
+    pub source_project: String,
+    pub source_package: String,
+    pub target_project: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SubmitRequest {
+    pub source_project: String,
+    pub source_package: String,
+    pub target_project: String,
+    /// Defaults to the source package.
+    pub target_package: Option<String>,
+    pub description: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RepositoryPath {
+    pub target_project: String,
+    pub target_repository: String,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct WorkflowRepository {
+    pub name: String,
+    #[serde(default)]
+    pub paths: Vec<RepositoryPath>,
+    #[serde(default)]
+    pub architectures: Vec<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigureRepositories {
+    pub project: String,
+    #[serde(default)]
+    pub repositories: Vec<WorkflowRepository>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PackageTarget {
+    pub project: String,
+    pub package: String,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagType {
+    Build,
+    Publish,
+    Debuginfo,
+    Useforbuild,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum FlagStatus {
+    Enable,
+    Disable,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Flag {
+    #[serde(rename = "type")]
+    pub flag_type: FlagType,
+    pub status: FlagStatus,
+    pub project: String,
+    pub package: Option<String>,
+    pub repository: Option<String>,
+    pub architecture: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct SetFlags {
+    #[serde(default)]
+    pub flags: Vec<Flag>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Step {
+    BranchPackage(BranchPackage),
+    LinkPackage(LinkPackage),
+    ConfigureRepositories(ConfigureRepositories),
+    RebuildPackage(PackageTarget),
+    TriggerServices(PackageTarget),
+    SetFlags(SetFlags),
+    SubmitRequest(SubmitRequest),
+}
+
+impl Step {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Step::BranchPackage(_) => "branch_package",
+            Step::LinkPackage(_) => "link_package",
+            Step::ConfigureRepositories(_) => "configure_repositories",
+            Step::RebuildPackage(_) => "rebuild_package",
+            Step::TriggerServices(_) => "trigger_services",
+            Step::SetFlags(_) => "set_flags",
+            Step::SubmitRequest(_) => "submit_request",
+        }
+    }
+
+    /// All the values of the step OBS substitutes placeholders in.
+    fn values(&self) -> Vec<&str> {
+        match self {
+            Step::BranchPackage(step) => vec![
+                &step.source_project,
+                &step.source_package,
+                &step.target_project,
+            ],
+            Step::LinkPackage(step) => vec![
+                &step.source_project,
+                &step.source_package,
+                &step.target_project,
+            ],
+            Step::ConfigureRepositories(step) => std::iter::once(step.project.as_str())
+                .chain(step.repositories.iter().flat_map(|repository| {
+                    std::iter::once(repository.name.as_str()).chain(
+                        repository.paths.iter().flat_map(|path| {
+                            [
+                                path.target_project.as_str(),
+                                path.target_repository.as_str(),
+                            ]
+                        }),
+                    )
+                }))
+                .collect(),
+            Step::SubmitRequest(step) => [
+                &step.source_project,
+                &step.source_package,
+                &step.target_project,
+            ]
+            .into_iter()
+            .map(String::as_str)
+            .chain(step.target_package.as_deref())
+            .collect(),
+            Step::RebuildPackage(step) | Step::TriggerServices(step) => {
+                vec![&step.project, &step.package]
+            }
+            Step::SetFlags(step) => step
+                .flags
+                .iter()
+                .flat_map(|flag| {
+                    std::iter::once(flag.project.as_str())
+                        .chain(flag.package.as_deref())
+                        .chain(flag.repository.as_deref())
+                        .chain(flag.architecture.as_deref())
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Workflow {
+    #[serde(default)]
+    pub steps: Vec<Step>,
+    #[serde(default)]
+    pub filters: Filters,
+}
+
+/// A problem found by [`Workflows::validate`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Issue {
+    pub workflow: String,
+    /// Index of the step the issue is in, if it's in one.
+    pub step: Option<usize>,
+    pub message: String,
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.workflow)?;
+        if let Some(step) = self.step {
+            write!(f, ", step {}", step + 1)?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The workflows of a `.obs/workflows.yml`, in the order they are listed.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Workflows {
+    pub workflows: Vec<(String, Workflow)>,
+}
+
+impl<'de> Deserialize<'de> for Workflows {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct WorkflowsVisitor;
+
+        impl<'de> Visitor<'de> for WorkflowsVisitor {
+            type Value = Workflows;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a mapping of workflow names to workflows")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut workflows = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    workflows.push(entry);
+                }
+                Ok(Workflows { workflows })
+            }
+        }
+
+        deserializer.deserialize_map(WorkflowsVisitor)
+    }
+}
+
+/// Returns the placeholders in `value` that OBS doesn't know about.
+fn unknown_placeholders(value: &str) -> Vec<&str> {
+    value
+        .split("%{")
+        .skip(1)
+        .filter_map(|rest| rest.split_once('}').map(|(name, _)| name))
+        .filter(|name| !PLACEHOLDERS.contains(name))
+        .collect()
+}
+
+impl Workflows {
+    pub fn parse(yaml: &str) -> Result<Self, ParseError> {
+        // Steps are written as maps with a single key, rather than using the
+        // YAML tags serde_yaml_ng expects for enums by default.
+        let deserializer = serde_yaml_ng::Deserializer::from_str(yaml);
+        Ok(serde_yaml_ng::with::singleton_map_recursive::deserialize(
+            deserializer,
+        )?)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Workflow> {
+        self.workflows
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, workflow)| workflow)
+    }
+
+    /// Checks for problems OBS only reports once the workflow runs.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        for (n, (name, workflow)) in self.workflows.iter().enumerate() {
+            let duplicate = self.workflows[..n].iter().any(|(other, _)| other == name);
+            let mut issue = |step: Option<usize>, message: String| {
+                issues.push(Issue {
+                    workflow: name.clone(),
+                    step,
+                    message,
+                })
+            };
+
+            if duplicate {
+                issue(None, "defined more than once".to_owned());
+            }
+            if workflow.steps.is_empty() {
+                issue(None, "no steps".to_owned());
+            }
+            if let Some(branches) = &workflow.filters.branches
+                && !branches.only.is_empty()
+                && !branches.ignore.is_empty()
+            {
+                issue(
+                    None,
+                    "branch filters can't have both only and ignore".to_owned(),
+                );
+            }
+
+            for (i, step) in workflow.steps.iter().enumerate() {
+                for value in step.values() {
+                    if value.trim().is_empty() {
+                        issue(Some(i), format!("{} has an empty value", step.name()));
+                    }
+                    for placeholder in unknown_placeholders(value) {
+                        issue(Some(i), format!("unknown placeholder %{{{placeholder}}}"));
+                    }
+                }
+
+                match step {
+                    Step::ConfigureRepositories(step) => {
+                        if step.repositories.is_empty() {
+                            issue(Some(i), "no repositories to configure".to_owned());
+                        }
+                        for repository in &step.repositories {
+                            if repository.paths.is_empty() {
+                                issue(
+                                    Some(i),
+                                    format!("repository {} has no paths", repository.name),
+                                );
+                            }
+                            if repository.architectures.is_empty() {
+                                issue(
+                                    Some(i),
+                                    format!("repository {} has no architectures", repository.name),
+                                );
+                            }
+                        }
+                    }
+                    Step::SetFlags(step) if step.flags.is_empty() => {
+                        issue(Some(i), "no flags to set".to_owned());
+                    }
+                    _ => (),
+                }
+            }
+        }
+        issues
+    }
+}
@@ -1,5 +1,6 @@
 use std::{
     collections::HashMap,
+    sync::{Arc, Mutex},
     time::{Duration, SystemTime},
 };
 
@@ -60,6 +61,2189 @@ async fn test_project_list() {
     assert_eq!(dir.entries[1].name, TEST_PACKAGE_2);
 }
 
+#[tokio::test]
+async fn test_client_builder() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = Client::builder(mock.uri())
+        .basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+        .timeout(Duration::from_secs(30))
+        .connect_timeout(Duration::from_secs(5))
+        .no_proxy()
+        .header(
+            reqwest::header::HeaderName::from_static("x-test"),
+            reqwest::header::HeaderValue::from_static("value"),
+        )
+        .build()
+        .unwrap();
+    let meta = obs.project(TEST_PROJECT.to_owned()).meta().await.unwrap();
+    assert_eq!(meta.name, TEST_PROJECT);
+
+    let obs = Client::builder(mock.uri())
+        .basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+        .http_client(reqwest::Client::new())
+        .build()
+        .unwrap();
+    let meta = obs.project(TEST_PROJECT.to_owned()).meta().await.unwrap();
+    assert_eq!(meta.name, TEST_PROJECT);
+
+    let obs = Client::builder(mock.uri())
+        .basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+        .timeout(Duration::from_nanos(1))
+        .build()
+        .unwrap();
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::RequestError(e) if e.is_timeout()));
+}
+
+#[tokio::test]
+async fn test_auth_basic() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = Client::new(
+        mock.uri(),
+        mock.auth().username().to_owned(),
+        "wrong".to_owned(),
+    );
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ApiError(e) if e.code == "authentication_required"
+    ));
+
+    let obs = Client::builder(mock.uri()).build().unwrap();
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ApiError(e) if e.code == "authentication_required"
+    ));
+}
+
+#[tokio::test]
+async fn test_auth_token() {
+    let token = "some-token";
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_token(token.to_owned());
+
+    let obs = Client::builder(mock.uri())
+        .token_auth(token.to_owned())
+        .build()
+        .unwrap();
+    obs.project(TEST_PROJECT.to_owned()).meta().await.unwrap();
+
+    let obs = Client::builder(mock.uri())
+        .token_auth("other-token".to_owned())
+        .build()
+        .unwrap();
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ApiError(e) if e.code == "authentication_required"
+    ));
+}
+
+#[tokio::test]
+async fn test_auth_signature() {
+    let key = std::env::temp_dir().join(format!("obs-test-key-{}", random_md5()));
+    let status = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = Client::builder(mock.uri())
+        .signature_auth(DEFAULT_USERNAME.to_owned(), key.clone())
+        .build()
+        .unwrap();
+    let project = obs.project(TEST_PROJECT.to_owned());
+    project.meta().await.unwrap();
+    // The second request is signed right away, now that the realm is known.
+    project.meta().await.unwrap();
+    assert_eq!(mock.session_count(), 2);
+
+    let obs = Client::builder(mock.uri())
+        .signature_auth("other".to_owned(), key.clone())
+        .build()
+        .unwrap();
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        Error::ApiError(e) if e.code == "authentication_required"
+    ));
+
+    let obs = Client::builder(mock.uri())
+        .signature_auth(DEFAULT_USERNAME.to_owned(), key.with_extension("missing"))
+        .build()
+        .unwrap();
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AuthenticationError(_)));
+
+    std::fs::remove_file(&key).unwrap();
+    std::fs::remove_file(key.with_extension("pub")).unwrap();
+}
+
+#[tokio::test]
+async fn test_auth_session_cookies() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+    project.meta().await.unwrap();
+    project.meta().await.unwrap();
+    assert_eq!(mock.session_count(), 2);
+
+    mock.expire_sessions();
+
+    let obs = Client::builder(mock.uri())
+        .basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+        .session_cookies(true)
+        .build()
+        .unwrap();
+    let project = obs.project(TEST_PROJECT.to_owned());
+    project.meta().await.unwrap();
+    project.meta().await.unwrap();
+    project.list_packages().await.unwrap();
+    assert_eq!(mock.session_count(), 1);
+
+    // Expired sessions are transparently replaced.
+    mock.expire_sessions();
+    project.meta().await.unwrap();
+    project.meta().await.unwrap();
+    assert_eq!(mock.session_count(), 1);
+}
+
+#[tokio::test]
+async fn test_retry() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+
+    let fault = MockFault::Status {
+        code: 503,
+        retry_after: None,
+    };
+    let builder = || {
+        Client::builder(mock.uri()).basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+    };
+    let policy = RetryPolicy::new(3).initial_backoff(Duration::from_millis(10));
+
+    let obs = builder().retry_policy(policy.clone()).build().unwrap();
+    mock.inject_faults(fault.clone(), 2);
+    let meta = obs.project(TEST_PROJECT.to_owned()).meta().await.unwrap();
+    assert_eq!(meta.name, TEST_PROJECT);
+
+    mock.inject_faults(fault.clone(), 3);
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, Error::HttpError(e) if e.status == reqwest::StatusCode::SERVICE_UNAVAILABLE)
+    );
+
+    // Commands aren't retried unless explicitly enabled.
+    let package = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    mock.inject_faults(fault.clone(), 1);
+    package.rebuild().await.unwrap_err();
+    package.rebuild().await.unwrap();
+
+    let obs = builder()
+        .retry_policy(policy.clone().retry_non_idempotent(true))
+        .build()
+        .unwrap();
+    mock.inject_faults(fault.clone(), 1);
+    obs.project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned())
+        .rebuild()
+        .await
+        .unwrap();
+
+    // Timeouts are retried as well.
+    let obs = builder()
+        .timeout(Duration::from_millis(200))
+        .retry_policy(policy.clone())
+        .build()
+        .unwrap();
+    mock.inject_faults(MockFault::Delay(Duration::from_secs(1)), 1);
+    obs.project(TEST_PROJECT.to_owned()).meta().await.unwrap();
+
+    // Retry-After takes precedence over the backoff.
+    let obs = builder().retry_policy(policy).build().unwrap();
+    mock.inject_faults(
+        MockFault::Status {
+            code: 429,
+            retry_after: Some(1),
+        },
+        1,
+    );
+    let start = std::time::Instant::now();
+    obs.project(TEST_PROJECT.to_owned()).meta().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_secs(1));
+
+    // Without a policy nothing is retried.
+    let obs = create_authenticated_client(mock.clone());
+    mock.inject_faults(fault, 1);
+    obs.project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+}
+
+#[tokio::test]
+async fn test_errors() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+
+    let err = obs
+        .project("nonexistent".to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+    assert!(!err.is_permission_denied());
+    assert!(!err.is_retryable());
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.code, "unknown_project");
+    assert_eq!(api_error.method, reqwest::Method::GET);
+    assert_eq!(api_error.url.path(), "/source/nonexistent/_meta");
+
+    mock.inject_faults(
+        MockFault::ApiError {
+            code: 403,
+            error_code: "cmd_execution_no_permission".to_owned(),
+            summary: "no permission to execute command".to_owned(),
+            details: Some("user has no maintainer role".to_owned()),
+        },
+        1,
+    );
+    let err = project.meta().await.unwrap_err();
+    assert!(err.is_permission_denied());
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.code, "cmd_execution_no_permission");
+    assert_eq!(api_error.summary, "no permission to execute command");
+    assert_eq!(
+        api_error.details.as_deref(),
+        Some("user has no maintainer role")
+    );
+
+    // Server errors with a status body are parsed as well.
+    mock.inject_faults(
+        MockFault::ApiError {
+            code: 503,
+            error_code: "service_unavailable".to_owned(),
+            summary: "in maintenance".to_owned(),
+            details: None,
+        },
+        1,
+    );
+    let err = project.meta().await.unwrap_err();
+    assert!(err.is_retryable());
+    assert_eq!(err.status(), Some(reqwest::StatusCode::SERVICE_UNAVAILABLE));
+    assert_eq!(err.api_error().unwrap().code, "service_unavailable");
+
+    // As are errors without one, e.g. from a proxy.
+    mock.inject_faults(
+        MockFault::Status {
+            code: 404,
+            retry_after: None,
+        },
+        1,
+    );
+    let err = project.meta().await.unwrap_err();
+    assert!(err.is_not_found());
+    assert!(err.api_error().is_none());
+    assert!(matches!(
+        &err,
+        Error::HttpError(e) if e.method == reqwest::Method::GET && e.body.starts_with("404")
+    ));
+}
+
+#[tokio::test]
+async fn test_in_process_transport() {
+    let test_file = "test";
+    let test_contents = b"some file contents here";
+    let test_entry = CommitFileEntry::from_contents(test_file.to_owned(), test_contents);
+    let file_list = CommitFileList::new().entry(test_entry.clone());
+
+    let mock = ObsMock::in_process(DEFAULT_USERNAME, DEFAULT_PASSWORD);
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_or_update_repository(
+        TEST_PROJECT,
+        TEST_REPO.to_owned(),
+        TEST_ARCH_1.to_owned(),
+        MockRepositoryCode::Building,
+    );
+
+    let obs = Client::builder(mock.uri())
+        .basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+        .session_cookies(true)
+        .transport(mock.transport())
+        .build()
+        .unwrap();
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+
+    package_1.create().await.unwrap();
+    let commit_result = package_1
+        .commit(&file_list, &CommitOptions::default())
+        .await
+        .unwrap();
+    assert!(matches!(commit_result, CommitResult::MissingEntries(_)));
+
+    package_1
+        .upload_for_commit(test_file, test_contents.to_vec())
+        .await
+        .unwrap();
+    let commit_result = package_1
+        .commit(&file_list, &CommitOptions::default())
+        .await
+        .unwrap();
+    assert!(matches!(commit_result, CommitResult::Success(_)));
+
+    let mut contents = Vec::new();
+    let mut stream = package_1.source_file(test_file).await.unwrap();
+    while let Some(chunk) = stream.next().await {
+        contents.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(contents, test_contents);
+
+    mock.add_completed_build_log(
+        TEST_PROJECT,
+        TEST_REPO,
+        TEST_ARCH_1,
+        TEST_PACKAGE_1.to_owned(),
+        MockBuildLog {
+            contents: "some log text".to_owned(),
+            mtime: SystemTime::UNIX_EPOCH,
+            chunk_size: Some(5),
+        },
+        false,
+    );
+    let log: Vec<_> = package_1
+        .log(TEST_REPO, TEST_ARCH_1)
+        .stream(Default::default())
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(log.concat(), b"some log text");
+
+    let err = obs
+        .project("nonexistent".to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+    assert_eq!(err.api_error().unwrap().code, "unknown_project");
+
+    // The session cookie is handled by the client rather than the transport.
+    assert_eq!(mock.session_count(), 1);
+
+    let obs = Client::builder(mock.uri())
+        .transport(mock.transport())
+        .build()
+        .unwrap();
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    assert_eq!(err.status(), Some(reqwest::StatusCode::UNAUTHORIZED));
+}
+
+/// Puts an OBS-like signature challenge in front of the mock: unsigned
+/// requests are rejected, signed ones are passed on with the mock's basic
+/// auth credentials. Records the method and path of every request, and
+/// whether it was signed.
+struct SignatureTransport {
+    inner: MockTransport,
+    basic_auth: reqwest::header::HeaderValue,
+    requests: Arc<Mutex<Vec<(reqwest::Method, String, bool)>>>,
+}
+
+impl Transport for SignatureTransport {
+    fn execute(
+        &self,
+        mut request: reqwest::Request,
+    ) -> futures::future::BoxFuture<'_, Result<reqwest::Response, Error>> {
+        use futures::FutureExt;
+        use reqwest::header::{AUTHORIZATION, WWW_AUTHENTICATE};
+
+        let signed = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| {
+                value.starts_with(&format!(r#"Signature keyId="{DEFAULT_USERNAME}""#))
+                    && value.contains("signature=")
+            });
+        self.requests.lock().unwrap().push((
+            request.method().clone(),
+            request.url().path().to_owned(),
+            signed,
+        ));
+        if !signed {
+            let response = http::Response::builder()
+                .status(401)
+                .header(
+                    WWW_AUTHENTICATE,
+                    r#"Signature realm="obs-test",headers="(created)""#,
+                )
+                .body(Vec::new())
+                .unwrap();
+            return futures::future::ready(Ok(response.into())).boxed();
+        }
+
+        request
+            .headers_mut()
+            .insert(AUTHORIZATION, self.basic_auth.clone());
+        self.inner.execute(request)
+    }
+}
+
+#[tokio::test]
+async fn test_signature_auth() {
+    let dir = std::env::temp_dir().join(format!("obs-signature-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let key = dir.join("id_ed25519");
+    let keygen = std::process::Command::new("ssh-keygen")
+        .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+        .arg(&key)
+        .status();
+    match keygen {
+        Ok(status) => assert!(status.success()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("ssh-keygen not found, skipping");
+            return;
+        }
+        Err(e) => panic!("failed to run ssh-keygen: {e}"),
+    }
+
+    let mock = ObsMock::in_process(DEFAULT_USERNAME, DEFAULT_PASSWORD);
+    mock.add_project(TEST_PROJECT.to_owned());
+    let basic_auth = reqwest::Client::new()
+        .get(mock.uri())
+        .basic_auth(DEFAULT_USERNAME, Some(DEFAULT_PASSWORD))
+        .build()
+        .unwrap()
+        .headers()[reqwest::header::AUTHORIZATION]
+        .clone();
+    let client = |requests: &Arc<Mutex<_>>| {
+        Client::builder(mock.uri())
+            .signature_auth(DEFAULT_USERNAME.to_owned(), key.clone())
+            .transport(SignatureTransport {
+                inner: mock.transport(),
+                basic_auth: basic_auth.clone(),
+                requests: requests.clone(),
+            })
+            .build()
+            .unwrap()
+    };
+
+    // Requests that can be replayed are signed once challenged.
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let obs = client(&requests);
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    package_1.create().await.unwrap();
+    let meta_path = format!("/source/{TEST_PROJECT}/{TEST_PACKAGE_1}/_meta");
+    assert_eq!(
+        *requests.lock().unwrap(),
+        [
+            (reqwest::Method::PUT, meta_path.clone(), false),
+            (reqwest::Method::PUT, meta_path, true),
+        ]
+    );
+
+    // Streamed uploads can't be replayed, so the challenge is fetched before
+    // sending them signed right away.
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let obs = client(&requests);
+    let path = dir.join("upload");
+    std::fs::write(&path, "streamed").unwrap();
+    obs.project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned())
+        .upload_path_for_commit("upload", &path, &UploadOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        *requests.lock().unwrap(),
+        [
+            (
+                reqwest::Method::GET,
+                format!("/person/{DEFAULT_USERNAME}"),
+                false
+            ),
+            (
+                reqwest::Method::PUT,
+                format!("/source/{TEST_PROJECT}/{TEST_PACKAGE_1}/upload"),
+                true
+            ),
+        ]
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+// Written against the traits only, so it works with any implementation.
+async fn commit_single_file<A: ObsApi>(api: &A, file: &str, contents: &[u8]) -> SourceDirectory {
+    let entry = CommitFileEntry::from_contents(file.to_owned(), contents);
+    let file_list = CommitFileList::new().entry(entry);
+
+    api.create_package(TEST_PROJECT, TEST_PACKAGE_1)
+        .await
+        .unwrap();
+    let result = api
+        .commit(
+            TEST_PROJECT,
+            TEST_PACKAGE_1,
+            &file_list,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    if let CommitResult::MissingEntries(missing) = result {
+        for entry in missing.entries {
+            assert_eq!(entry.name, file);
+            api.upload_for_commit(
+                TEST_PROJECT,
+                TEST_PACKAGE_1,
+                file,
+                bytes::Bytes::copy_from_slice(contents),
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    match api
+        .commit(
+            TEST_PROJECT,
+            TEST_PACKAGE_1,
+            &file_list,
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap()
+    {
+        CommitResult::Success(directory) => directory,
+        result => panic!("Expected success, got {result:?}"),
+    }
+}
+
+#[tokio::test]
+async fn test_obs_api() {
+    let test_file = "test";
+    let test_contents = b"some file contents here";
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    let obs = create_authenticated_client(mock.clone());
+    let directory = commit_single_file(&obs, test_file, test_contents).await;
+    assert_eq!(directory.entries.len(), 1);
+
+    let mock = ObsMock::in_process(DEFAULT_USERNAME, DEFAULT_PASSWORD);
+    mock.add_project(TEST_PROJECT.to_owned());
+    let obs = mock.client();
+    let directory = commit_single_file(&obs, test_file, test_contents).await;
+    assert_eq!(directory.entries.len(), 1);
+    assert_eq!(directory.entries[0].name, test_file);
+
+    let listing = obs.list_packages(TEST_PROJECT).await.unwrap();
+    assert_eq!(listing.entries.len(), 1);
+    assert_eq!(listing.entries[0].name, TEST_PACKAGE_1);
+
+    let contents: Vec<_> = obs
+        .source_file(TEST_PROJECT, TEST_PACKAGE_1, test_file)
+        .await
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    assert_eq!(contents.concat(), test_contents);
+
+    let err = obs
+        .package_meta(TEST_PROJECT, TEST_PACKAGE_2)
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
+
+#[cfg(feature = "blocking")]
+#[test]
+fn test_blocking() {
+    let test_file = "test";
+    let test_contents = b"some file contents here";
+    let test_entry = CommitFileEntry::from_contents(test_file.to_owned(), test_contents);
+    let file_list = CommitFileList::new().entry(test_entry);
+
+    let mock = ObsMock::in_process(DEFAULT_USERNAME, DEFAULT_PASSWORD);
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_or_update_repository(
+        TEST_PROJECT,
+        TEST_REPO.to_owned(),
+        TEST_ARCH_1.to_owned(),
+        MockRepositoryCode::Building,
+    );
+
+    let obs = blocking::Client::from_async(mock.client());
+    let meta = obs.project(TEST_PROJECT.to_owned()).meta().unwrap();
+    assert_eq!(meta.name, TEST_PROJECT);
+    assert_eq!(
+        obs.project(TEST_PROJECT.to_owned()).repositories().unwrap(),
+        vec![TEST_REPO.to_owned()]
+    );
+
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    package_1.create().unwrap();
+    package_1
+        .upload_for_commit(test_file, test_contents.to_vec())
+        .unwrap();
+    let commit_result = package_1
+        .commit(&file_list, &CommitOptions::default())
+        .unwrap();
+    assert!(matches!(commit_result, CommitResult::Success(_)));
+
+    let contents: Vec<_> = package_1
+        .source_file(test_file)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(contents.concat(), test_contents);
+
+    mock.add_completed_build_log(
+        TEST_PROJECT,
+        TEST_REPO,
+        TEST_ARCH_1,
+        TEST_PACKAGE_1.to_owned(),
+        MockBuildLog {
+            contents: "some log text".to_owned(),
+            mtime: SystemTime::UNIX_EPOCH,
+            chunk_size: Some(5),
+        },
+        false,
+    );
+    let log = package_1.log(TEST_REPO, TEST_ARCH_1);
+    assert_eq!(log.entry().unwrap(), (13, 0));
+    let chunks: Vec<_> = log
+        .stream(Default::default())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks.concat(), b"some log text");
+
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_2.to_owned())
+        .meta()
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
+
+// Records closed spans and events as "name field=value ..." lines.
+#[cfg(feature = "tracing")]
+mod trace_recorder {
+    use std::fmt::Write;
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Subscriber};
+    use tracing_subscriber::layer::Context;
+    use tracing_subscriber::registry::LookupSpan;
+
+    struct Fields(String);
+
+    impl Visit for Fields {
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            write!(self.0, " {}={:?}", field.name(), value).unwrap();
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            write!(self.0, " {}={}", field.name(), value).unwrap();
+        }
+    }
+
+    #[derive(Clone, Default)]
+    pub struct Recorder(pub Arc<Mutex<Vec<String>>>);
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> tracing_subscriber::Layer<S> for Recorder {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+            let mut fields = Fields(attrs.metadata().name().to_owned());
+            attrs.record(&mut fields);
+            ctx.span(id).unwrap().extensions_mut().insert(fields);
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+            let span = ctx.span(id).unwrap();
+            let mut extensions = span.extensions_mut();
+            values.record(extensions.get_mut::<Fields>().unwrap());
+        }
+
+        fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+            let mut fields = Fields("event".to_owned());
+            event.record(&mut fields);
+            self.0.lock().unwrap().push(fields.0);
+        }
+
+        fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+            let span = ctx.span(&id).unwrap();
+            let fields = span.extensions_mut().remove::<Fields>().unwrap();
+            self.0.lock().unwrap().push(fields.0);
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+#[tokio::test]
+async fn test_tracing() {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    let test_file = "test";
+    let test_contents = b"some file contents here";
+    let test_entry = CommitFileEntry::from_contents(test_file.to_owned(), test_contents);
+    let file_list = CommitFileList::new().entry(test_entry);
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_or_update_repository(
+        TEST_PROJECT,
+        TEST_REPO.to_owned(),
+        TEST_ARCH_1.to_owned(),
+        MockRepositoryCode::Building,
+    );
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+    mock.add_completed_build_log(
+        TEST_PROJECT,
+        TEST_REPO,
+        TEST_ARCH_1,
+        TEST_PACKAGE_1.to_owned(),
+        MockBuildLog {
+            contents: "some log text".to_owned(),
+            mtime: SystemTime::UNIX_EPOCH,
+            chunk_size: Some(5),
+        },
+        false,
+    );
+
+    let recorder = trace_recorder::Recorder::default();
+    let _guard =
+        tracing::subscriber::set_default(tracing_subscriber::registry().with(recorder.clone()));
+
+    let obs = create_authenticated_client(mock.clone());
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+
+    package_1.meta().await.unwrap();
+    let lines = std::mem::take(&mut *recorder.0.lock().unwrap());
+    let requests: Vec<_> = lines
+        .iter()
+        .filter(|l| l.starts_with("obs_request"))
+        .collect();
+    assert_eq!(requests.len(), 1, "{lines:?}");
+    let line = requests[0];
+    assert!(line.starts_with("obs_request method=GET"), "{line}");
+    assert!(
+        line.contains(&format!(
+            " path=/source/{TEST_PROJECT}/{TEST_PACKAGE_1}/_meta"
+        )),
+        "{line}"
+    );
+    assert!(line.contains(" status=200"), "{line}");
+    assert!(line.contains(" duration_ms="), "{line}");
+    assert!(line.contains(" size="), "{line}");
+    assert!(!line.contains(DEFAULT_PASSWORD), "{line}");
+
+    obs.project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_2.to_owned())
+        .meta()
+        .await
+        .unwrap_err();
+    let lines = std::mem::take(&mut *recorder.0.lock().unwrap());
+    assert!(
+        lines
+            .iter()
+            .any(|l| l.starts_with("obs_request") && l.contains(" status=404")),
+        "{lines:?}"
+    );
+
+    package_1
+        .commit(&file_list, &CommitOptions::default())
+        .await
+        .unwrap();
+    let lines = std::mem::take(&mut *recorder.0.lock().unwrap());
+    assert!(
+        lines.iter().any(|l| l.starts_with("event")
+            && l.contains("commit is missing entries")
+            && l.contains(" missing=1")),
+        "{lines:?}"
+    );
+
+    let _: Vec<_> = package_1
+        .log(TEST_REPO, TEST_ARCH_1)
+        .stream(Default::default())
+        .unwrap()
+        .try_collect()
+        .await
+        .unwrap();
+    let lines = std::mem::take(&mut *recorder.0.lock().unwrap());
+    let reconnects = lines
+        .iter()
+        .filter(|l| l.contains("reconnecting build log stream"))
+        .count();
+    assert_eq!(reconnects, 3, "{lines:?}");
+}
+
+#[tokio::test]
+async fn test_bulk() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_or_update_repository(
+        TEST_PROJECT,
+        TEST_REPO.to_owned(),
+        TEST_ARCH_1.to_owned(),
+        MockRepositoryCode::Finished,
+    );
+    for package in [TEST_PACKAGE_1, TEST_PACKAGE_2] {
+        mock.add_new_package(
+            TEST_PROJECT,
+            package.to_owned(),
+            MockPackageOptions::default(),
+        );
+    }
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+
+    let metas = project
+        .package_metas(&BulkOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(metas.len(), 2);
+    for (name, meta) in &metas {
+        assert_eq!(&meta.as_ref().unwrap().name, name);
+    }
+
+    let sources = project
+        .package_sources(&BulkOptions {
+            packages: Some(vec![TEST_PACKAGE_1.to_owned(), "nonexistent".to_owned()]),
+            concurrency: Some(1),
+        })
+        .await
+        .unwrap();
+    assert_eq!(sources.len(), 2);
+    assert!(sources[TEST_PACKAGE_1].is_ok());
+    assert!(sources["nonexistent"].as_ref().unwrap_err().is_not_found());
+
+    let results = project
+        .package_results(&BulkOptions::default())
+        .await
+        .unwrap();
+    for (name, result) in &results {
+        let result = result.as_ref().unwrap();
+        assert_eq!(result.results.len(), 1);
+        assert!(
+            result.results[0]
+                .statuses
+                .iter()
+                .all(|status| &status.package == name)
+        );
+    }
+
+    let err = obs
+        .project("nonexistent".to_owned())
+        .package_metas(&BulkOptions::default())
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+
+    // The client-wide limit applies on top of the per-operation concurrency,
+    // so the listing and two metas are fetched one after another.
+    let obs = Client::builder(mock.uri())
+        .basic_auth(
+            mock.auth().username().to_owned(),
+            mock.auth().password().to_owned(),
+        )
+        .max_concurrent_requests(1)
+        .build()
+        .unwrap();
+    mock.inject_faults(MockFault::Delay(Duration::from_millis(200)), 3);
+    let start = std::time::Instant::now();
+    let metas = obs
+        .project(TEST_PROJECT.to_owned())
+        .package_metas(&BulkOptions::default())
+        .await
+        .unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(600));
+    assert!(metas.values().all(|meta| meta.is_ok()));
+
+    // Streamed downloads only count until their headers arrived, so one that
+    // wasn't read yet doesn't hold up other requests.
+    let key = mock
+        .add_package_files(
+            TEST_PROJECT,
+            TEST_PACKAGE_1,
+            MockSourceFile {
+                path: "file".to_owned(),
+                contents: b"contents".to_vec(),
+            },
+        )
+        .into_owned();
+    mock.add_package_revision(
+        TEST_PROJECT,
+        TEST_PACKAGE_1,
+        MockRevisionOptions::default(),
+        [(
+            "file".to_owned(),
+            MockEntry::from_key(&key, SystemTime::now()),
+        )]
+        .into(),
+    );
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    let download = package_1.source_file("file").await.unwrap();
+    tokio::time::timeout(Duration::from_secs(5), package_1.meta())
+        .await
+        .unwrap()
+        .unwrap();
+    drop(download);
+}
+
+#[tokio::test]
+async fn test_listing_options() {
+    let other_project = "other";
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_project(other_project.to_owned());
+    mock.add_project(format!("{other_project}:sub"));
+    mock.add_project_link(TEST_PROJECT, other_project.to_owned());
+
+    for (project, package) in [
+        (TEST_PROJECT, TEST_PACKAGE_1),
+        (other_project, TEST_PACKAGE_1),
+        (other_project, "inherited"),
+    ] {
+        mock.add_new_package(project, package.to_owned(), MockPackageOptions::default());
+    }
+
+    let spec_key = mock
+        .add_package_files(
+            TEST_PROJECT,
+            TEST_PACKAGE_1,
+            MockSourceFile {
+                path: "test.spec".to_owned(),
+                contents: b"Version: 1.2".to_vec(),
+            },
+        )
+        .into_owned();
+    mock.add_package_revision(
+        TEST_PROJECT,
+        TEST_PACKAGE_1,
+        MockRevisionOptions {
+            version: Some("1.2".to_owned()),
+            ..Default::default()
+        },
+        [(
+            "test.spec".to_owned(),
+            MockEntry::from_key(&spec_key, SystemTime::now()),
+        )]
+        .into(),
+    );
+    mock.branch(
+        TEST_PROJECT.to_owned(),
+        TEST_PACKAGE_1.to_owned(),
+        TEST_PROJECT,
+        TEST_PACKAGE_2.to_owned(),
+        MockBranchOptions::default(),
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+
+    let projects = obs
+        .list_projects(&ProjectListOptions::default())
+        .await
+        .unwrap();
+    let names: Vec<_> = projects.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, [other_project, "other:sub", TEST_PROJECT]);
+
+    let projects = obs
+        .list_projects(&ProjectListOptions {
+            prefix: Some(format!("{other_project}:")),
+        })
+        .await
+        .unwrap();
+    let names: Vec<_> = projects.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["other:sub"]);
+
+    let project = obs.project(TEST_PROJECT.to_owned());
+    let mut expanded = project
+        .list_packages_with(&PackageListOptions {
+            expand: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    expanded.entries.sort_by(|a, b| a.name.cmp(&b.name));
+    let entries: Vec<_> = expanded
+        .entries
+        .iter()
+        .map(|e| (e.name.as_str(), e.originproject.as_deref()))
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("inherited", Some(other_project)),
+            (TEST_PACKAGE_1, None),
+            (TEST_PACKAGE_2, None),
+        ]
+    );
+
+    let mut infos = project
+        .source_info(&SourceInfoOptions {
+            parse: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    infos.sourceinfos.sort_by(|a, b| a.package.cmp(&b.package));
+    assert_eq!(infos.sourceinfos.len(), 2);
+
+    let info_1 = &infos.sourceinfos[0];
+    assert_eq!(info_1.package, TEST_PACKAGE_1);
+    assert_eq!(info_1.rev.as_deref(), Some("1"));
+    assert_eq!(info_1.filename.as_deref(), Some("test.spec"));
+    assert_eq!(info_1.version.as_deref(), Some("1.2"));
+    assert_eq!(info_1.verifymd5, info_1.srcmd5);
+    assert!(info_1.linked.is_empty());
+
+    let info_2 = &infos.sourceinfos[1];
+    assert_eq!(info_2.package, TEST_PACKAGE_2);
+    assert_ne!(info_2.verifymd5, info_2.srcmd5);
+    assert_eq!(info_2.linked.len(), 1);
+    assert_eq!(info_2.linked[0].project, TEST_PROJECT);
+    assert_eq!(info_2.linked[0].package, TEST_PACKAGE_1);
+
+    let deleted_options = PackageListOptions {
+        deleted: true,
+        ..Default::default()
+    };
+    let deleted = project.list_packages_with(&deleted_options).await.unwrap();
+    assert!(deleted.entries.is_empty());
+
+    let package_2 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_2.to_owned());
+    package_2.delete().await.unwrap();
+    let deleted = project.list_packages_with(&deleted_options).await.unwrap();
+    let names: Vec<_> = deleted.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, [TEST_PACKAGE_2]);
+
+    package_2.create().await.unwrap();
+    let deleted = project.list_packages_with(&deleted_options).await.unwrap();
+    assert!(deleted.entries.is_empty());
+}
+
+#[tokio::test]
+async fn test_source_info() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+
+    let mut entries = HashMap::new();
+    for name in ["a-helper.spec".to_owned(), format!("{TEST_PACKAGE_1}.spec")] {
+        let key = mock
+            .add_package_files(
+                TEST_PROJECT,
+                TEST_PACKAGE_1,
+                MockSourceFile {
+                    path: name.clone(),
+                    contents: name.as_bytes().to_vec(),
+                },
+            )
+            .into_owned();
+        entries.insert(name, MockEntry::from_key(&key, SystemTime::now()));
+    }
+    let srcmd5 = random_md5();
+    mock.add_package_revision(
+        TEST_PROJECT,
+        TEST_PACKAGE_1,
+        MockRevisionOptions {
+            srcmd5: srcmd5.clone(),
+            version: Some("2.0".to_owned()),
+            ..Default::default()
+        },
+        entries,
+    );
+    mock.branch(
+        TEST_PROJECT.to_owned(),
+        TEST_PACKAGE_1.to_owned(),
+        TEST_PROJECT,
+        TEST_PACKAGE_2.to_owned(),
+        MockBranchOptions {
+            link_resolution: MockLinkResolution::Error {
+                error: "conflict in file test".to_owned(),
+            },
+            ..Default::default()
+        },
+    );
+
+    let obs = create_authenticated_client(mock);
+
+    let info = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned())
+        .info(&SourceInfoOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(info.package, TEST_PACKAGE_1);
+    assert_eq!(info.rev.as_deref(), Some("1"));
+    assert_eq!(info.srcmd5.as_deref(), Some(srcmd5.as_str()));
+    assert_eq!(info.verifymd5.as_deref(), Some(srcmd5.as_str()));
+    assert_eq!(info.filename, Some(format!("{TEST_PACKAGE_1}.spec")));
+    assert!(info.version.is_none());
+    assert!(info.error.is_none());
+
+    let info = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned())
+        .info(&SourceInfoOptions {
+            parse: true,
+            repository: Some(TEST_REPO.to_owned()),
+            arch: Some(TEST_ARCH_1.to_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(info.version.as_deref(), Some("2.0"));
+    assert!(info.release.is_some());
+
+    let info = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_2.to_owned())
+        .info(&SourceInfoOptions {
+            parse: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(info.error.as_deref(), Some("conflict in file test"));
+    assert!(info.version.is_none());
+    assert_eq!(info.linked.len(), 1);
+    assert_eq!(info.linked[0].package, TEST_PACKAGE_1);
+
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .package("nonexistent".to_owned())
+        .info(&SourceInfoOptions::default())
+        .await
+        .unwrap_err();
+    assert!(err.is_not_found());
+}
+
+#[tokio::test]
+async fn test_project_config() {
+    use open_build_service_api::prjconf::{LineKind, ProjectConfig};
+
+    const CONFIG: &str = "# Debian config\n\
+Type: dsc\n\
+Repotype: debian\n\
+\n\
+Preinstall: bash coreutils\n\
+Preinstall:   dpkg tar\n\
+Support: build-essential\n\
+%if \"%_repository\" == \"testing\"\n\
+Prefer: libfoo-dev\n\
+%else\n\
+Prefer: libfoo1-dev\n\
+%endif\n\
+Substitute: gcc gcc-12 cpp\n\
+\n\
+Macros:\n\
+%vendor Debian\n\
+# not a comment here\n\
+:Macros\n";
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.set_project_config(TEST_PROJECT, CONFIG.to_owned());
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+
+    let raw = project.config().await.unwrap();
+    assert_eq!(raw, CONFIG);
+
+    let mut config = ProjectConfig::parse(&raw);
+    assert_eq!(config.to_string(), CONFIG);
+    assert_eq!(config.type_(), Some("dsc"));
+    assert_eq!(config.repotype(), vec!["debian"]);
+    assert_eq!(
+        config.preinstall(),
+        vec!["bash", "coreutils", "dpkg", "tar"]
+    );
+    assert_eq!(config.support(), vec!["build-essential"]);
+    assert_eq!(config.prefer(), vec!["libfoo-dev", "libfoo1-dev"]);
+    assert_eq!(config.substitutions(), vec![("gcc", vec!["gcc-12", "cpp"])]);
+    assert_eq!(
+        config.macros(),
+        vec!["%vendor Debian", "# not a comment here"]
+    );
+
+    let prefers: Vec<_> = config.directives("prefer").collect();
+    assert_eq!(prefers.len(), 2);
+    assert_eq!(
+        prefers[0].conditions(),
+        ["%if \"%_repository\" == \"testing\""]
+    );
+    assert_eq!(prefers[1].conditions(), ["%else"]);
+    assert_eq!(config.lines()[0].kind(), &LineKind::Comment);
+    assert_eq!(config.lines()[7].kind(), &LineKind::Conditional);
+
+    config.set("Type", "spec");
+    config.set("Prefer", "libbar-dev");
+    assert_eq!(config.remove("Support"), 1);
+    let expected = CONFIG
+        .replace("Type: dsc", "Type: spec")
+        .replace("Support: build-essential\n", "")
+        .replace("cpp\n", "cpp\nPrefer: libbar-dev\n");
+    assert_eq!(config.to_string(), expected);
+    assert_eq!(
+        config.prefer(),
+        vec!["libfoo-dev", "libfoo1-dev", "libbar-dev"]
+    );
+
+    project
+        .set_config(&config.to_string(), Some("Switch to spec"))
+        .await
+        .unwrap();
+    assert_eq!(mock.project_config(TEST_PROJECT), expected);
+    assert_eq!(project.config().await.unwrap(), expected);
+
+    assert!(matches!(
+        obs.project("nonexistent".to_owned()).config().await,
+        Err(Error::ApiError(_))
+    ));
+
+    // A macros block without `:Macros` runs to the end, so directives are
+    // added before it, and outside of the conditional it is in.
+    let mut config = ProjectConfig::parse("Type: spec\n%ifarch x86_64\nMacros:\n%_lto 1\n%endif\n");
+    config.add("Prefer", "libbar-dev");
+    assert_eq!(
+        config.to_string(),
+        "Type: spec\nPrefer: libbar-dev\n%ifarch x86_64\nMacros:\n%_lto 1\n%endif\n"
+    );
+    assert_eq!(config.prefer(), vec!["libbar-dev"]);
+    assert!(config.macros().contains(&"%_lto 1"));
+}
+
+#[tokio::test]
+async fn test_signkey() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+
+    assert!(matches!(
+        project.pubkey().await,
+        Err(Error::ApiError(e)) if e.code == "404"
+    ));
+    let keyinfo = project.keyinfo().await.unwrap();
+    assert_eq!(keyinfo.project, TEST_PROJECT);
+    assert!(keyinfo.pubkey.is_none());
+    assert!(keyinfo.sslcert.is_none());
+    assert!(project.extendkey(None).await.is_err());
+
+    project.create_signkey().await.unwrap();
+    assert_eq!(project.pubkey().await.unwrap(), TEST_SIGNKEY);
+
+    let keyinfo = project.keyinfo().await.unwrap();
+    let pubkey = keyinfo.pubkey.unwrap();
+    assert_eq!(pubkey.fingerprint, TEST_SIGNKEY_FINGERPRINT);
+    assert_eq!(pubkey.keyid, "e4de9c29");
+    assert_eq!(pubkey.algo, "rsa");
+    assert_eq!(pubkey.keysize, Some(2048));
+    assert_eq!(pubkey.expires, Some(TEST_SIGNKEY_EXPIRES));
+    assert_eq!(pubkey.key, TEST_SIGNKEY);
+    let sslcert = keyinfo.sslcert.unwrap();
+    assert_eq!(sslcert.cert, TEST_SSLCERT);
+    assert_eq!(sslcert.keyid.as_deref(), Some("e4de9c29"));
+    assert!(sslcert.subject.contains("home:test OBS Project"));
+
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    project.extendkey(Some(10)).await.unwrap();
+    let expires = project.keyinfo().await.unwrap().pubkey.unwrap().expires;
+    assert!(
+        expires.is_some_and(|e| (now + 10 * 24 * 60 * 60..now + 11 * 24 * 60 * 60).contains(&e))
+    );
+
+    let other = obs.project("other".to_owned());
+    mock.add_project("other".to_owned());
+    mock.set_project_signkey("other");
+    assert_eq!(
+        other.keyinfo().await.unwrap().pubkey.unwrap().expires,
+        Some(TEST_SIGNKEY_EXPIRES)
+    );
+}
+
+#[cfg(feature = "verify")]
+#[tokio::test]
+async fn test_verify_signatures() {
+    use open_build_service_api::verify::VerifyError;
+
+    const RELEASE: &[u8] = include_bytes!("data/verify/Release");
+    const SIGNED_RPM: &[u8] = include_bytes!("data/verify/hello-1.0-1.noarch.rpm");
+    const SIGNED_DEB: &[u8] = include_bytes!("data/verify/hello_1.0-1_all.deb");
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.set_project_signkey(TEST_PROJECT);
+    mock.add_or_update_repository(
+        TEST_PROJECT,
+        TEST_REPO.to_owned(),
+        TEST_ARCH_1.to_owned(),
+        MockRepositoryCode::Finished,
+    );
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+    let binaries = [
+        ("hello-1.0-1.noarch.rpm", SIGNED_RPM),
+        (
+            "hello-unsigned.rpm",
+            &include_bytes!("data/verify/hello-unsigned.rpm")[..],
+        ),
+        ("hello_1.0-1_all.deb", SIGNED_DEB),
+        (
+            "hello-unsigned.deb",
+            &include_bytes!("data/verify/hello-unsigned.deb")[..],
+        ),
+    ];
+    mock.set_package_binaries(
+        TEST_PROJECT,
+        TEST_REPO,
+        TEST_ARCH_1,
+        TEST_PACKAGE_1.to_owned(),
+        binaries
+            .iter()
+            .map(|(name, contents)| {
+                (
+                    name.to_string(),
+                    MockBinary {
+                        contents: contents.to_vec(),
+                        mtime: SystemTime::now(),
+                    },
+                )
+            })
+            .collect(),
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+    let keyring = obs
+        .project(TEST_PROJECT.to_owned())
+        .keyring()
+        .await
+        .unwrap();
+    let fingerprint = TEST_SIGNKEY_FINGERPRINT.replace(' ', "");
+    assert_eq!(keyring.fingerprints(), std::slice::from_ref(&fingerprint));
+
+    mock.inject_faults(MockFault::Body(b"not a key".to_vec()), 1);
+    let err = obs
+        .project(TEST_PROJECT.to_owned())
+        .keyring()
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::Verify(e) if e.downcast_ref::<VerifyError>().is_some()),
+        "{err:?}"
+    );
+
+    let signature = keyring
+        .verify_detached(
+            include_bytes!("data/verify/repomd.xml"),
+            include_bytes!("data/verify/repomd.xml.asc"),
+        )
+        .unwrap();
+    assert_eq!(signature.fingerprint, fingerprint);
+    assert!(signature.created > 0);
+
+    let signature = keyring
+        .verify_detached(RELEASE, include_bytes!("data/verify/Release.gpg"))
+        .unwrap();
+    // The same signature with a creation time of 1 added to its unhashed
+    // subpackets, which anyone can do without breaking it.
+    let rewritten = keyring
+        .verify_detached(
+            RELEASE,
+            include_bytes!("data/verify/Release-unhashed-created.sig"),
+        )
+        .unwrap();
+    assert_eq!(rewritten, signature);
+    assert_ne!(rewritten.created, 1);
+    let tampered = String::from_utf8_lossy(RELEASE).replace("Debian_13", "Debian_14");
+    assert!(matches!(
+        keyring.verify_detached(
+            tampered.as_bytes(),
+            include_bytes!("data/verify/Release.gpg")
+        ),
+        Err(VerifyError::BadSignature(_))
+    ));
+    assert!(matches!(
+        keyring.verify_detached(RELEASE, b""),
+        Err(VerifyError::Unsigned)
+    ));
+
+    let inrelease = include_str!("data/verify/InRelease");
+    let verified = keyring.verify_cleartext(inrelease).unwrap();
+    assert_eq!(verified.text.as_bytes(), RELEASE);
+    assert_eq!(verified.signature.fingerprint, fingerprint);
+    assert!(matches!(
+        keyring.verify_cleartext(&inrelease.replace("Debian_13", "Debian_14")),
+        Err(VerifyError::BadSignature(_))
+    ));
+    assert!(matches!(
+        keyring.verify_cleartext(&String::from_utf8_lossy(RELEASE)),
+        Err(VerifyError::Unsigned)
+    ));
+
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    for (name, _) in &binaries {
+        let contents: Vec<u8> = package_1
+            .binary_file(TEST_REPO, TEST_ARCH_1, name)
+            .await
+            .unwrap()
+            .map_ok(|chunk| chunk.to_vec())
+            .try_concat()
+            .await
+            .unwrap();
+        let result = keyring.verify_package(name, &contents);
+        if name.contains("unsigned") {
+            assert!(matches!(result, Err(VerifyError::Unsigned)), "{name}");
+        } else {
+            assert_eq!(result.unwrap().fingerprint, fingerprint);
+        }
+    }
+
+    // Here the payload is covered by the header+payload signature.
+    let mut tampered = SIGNED_RPM.to_vec();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        keyring.verify_rpm(&tampered),
+        Err(VerifyError::BadSignature(_))
+    ));
+    // Hiding that signature leaves only the header signature, without a
+    // payload digest in the header.
+    let mut header_signed = SIGNED_RPM.to_vec();
+    header_signed[128..132].copy_from_slice(&999u32.to_be_bytes());
+    assert!(matches!(
+        keyring.verify_rpm(&header_signed),
+        Err(VerifyError::UnsignedPayload)
+    ));
+
+    // Here only the header is signed, covering the payload by its digest.
+    const HEADER_SIGNED_RPM: &[u8] = include_bytes!("data/verify/hello-1.0-2.noarch.rpm");
+    assert_eq!(
+        keyring.verify_rpm(HEADER_SIGNED_RPM).unwrap().fingerprint,
+        fingerprint
+    );
+    let mut tampered = HEADER_SIGNED_RPM.to_vec();
+    *tampered.last_mut().unwrap() ^= 1;
+    assert!(matches!(
+        keyring.verify_rpm(&tampered),
+        Err(VerifyError::PayloadMismatch)
+    ));
+
+    for len in [100, 200, 800] {
+        assert!(
+            matches!(
+                keyring.verify_rpm(&SIGNED_RPM[..len]),
+                Err(VerifyError::Malformed(_))
+            ),
+            "{len}"
+        );
+    }
+    let mut huge = SIGNED_RPM.to_vec();
+    huge[104..108].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert!(matches!(
+        keyring.verify_rpm(&huge),
+        Err(VerifyError::Malformed(_))
+    ));
+    let mut tampered = SIGNED_DEB.to_vec();
+    let data = tampered
+        .windows(11)
+        .position(|w| w == b"data.tar.gz")
+        .unwrap();
+    tampered[data + 100] ^= 1;
+    assert!(matches!(
+        keyring.verify_deb(&tampered),
+        Err(VerifyError::BadSignature(_))
+    ));
+    assert!(matches!(
+        keyring.verify_package("hello.tar.gz", SIGNED_DEB),
+        Err(VerifyError::Unsupported(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_comments() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    let request = obs.bs_request(42);
+
+    assert!(project.comments().await.unwrap().comments.is_empty());
+
+    package_1
+        .create_comment("Build failed on x86_64", None)
+        .await
+        .unwrap();
+    let comments = package_1.comments().await.unwrap().comments;
+    assert_eq!(comments.len(), 1);
+    let failure = &comments[0];
+    assert_eq!(failure.who, DEFAULT_USERNAME);
+    assert_eq!(failure.body, "Build failed on x86_64");
+    assert_eq!(failure.parent, None);
+    assert!(failure.when.ends_with(" UTC"));
+
+    package_1
+        .create_comment("Fixed in the next revision", Some(failure.id))
+        .await
+        .unwrap();
+    let comments = package_1.comments().await.unwrap().comments;
+    assert_eq!(comments.len(), 2);
+    assert_eq!(comments[1].parent, Some(failure.id));
+    assert!(project.comments().await.unwrap().comments.is_empty());
+
+    let id = mock.add_comment(
+        MockCommentTarget::Request(42),
+        "reviewer".to_owned(),
+        "Please add a changelog entry".to_owned(),
+        None,
+    );
+    request
+        .create_comment("Done <3 & thanks", Some(id))
+        .await
+        .unwrap();
+    let comments = request.comments().await.unwrap().comments;
+    assert_eq!(comments.len(), 2);
+    assert_eq!(comments[0].who, "reviewer");
+    assert_eq!(comments[1].body, "Done <3 & thanks");
+    assert_eq!(comments[1].parent, Some(id));
+    assert!(
+        obs.bs_request(43)
+            .comments()
+            .await
+            .unwrap()
+            .comments
+            .is_empty()
+    );
+
+    // Replies have to be to comments on the same target.
+    assert!(matches!(
+        project.create_comment("Reply", Some(id)).await,
+        Err(Error::ApiError(e)) if e.code == "not_found"
+    ));
+
+    obs.delete_comment(id).await.unwrap();
+    let comments = request.comments().await.unwrap().comments;
+    assert_eq!(comments.len(), 1);
+    assert!(matches!(
+        obs.delete_comment(id).await,
+        Err(Error::ApiError(e)) if e.code == "not_found"
+    ));
+
+    assert!(matches!(
+        obs.project("nonexistent".to_owned()).comments().await,
+        Err(Error::ApiError(e)) if e.code == "unknown_project"
+    ));
+}
+
+#[tokio::test]
+async fn test_roles() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+    mock.add_person(
+        "alice".to_owned(),
+        MockPerson {
+            email: "alice@example.com".to_owned(),
+            realname: "Alice".to_owned(),
+            globalroles: vec!["Admin".to_owned()],
+        },
+    );
+    mock.add_group(
+        "devs".to_owned(),
+        MockGroup {
+            email: None,
+            maintainers: vec!["alice".to_owned()],
+            members: vec!["alice".to_owned(), "bob".to_owned()],
+        },
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    let alice = RoleHolder::User("alice".to_owned());
+    let devs = RoleHolder::Group("devs".to_owned());
+
+    let person = obs.person("alice").await.unwrap();
+    assert_eq!(person.login, "alice");
+    assert_eq!(person.email.as_deref(), Some("alice@example.com"));
+    assert_eq!(person.realname.as_deref(), Some("Alice"));
+    assert_eq!(person.globalroles, ["Admin"]);
+    assert!(matches!(
+        obs.person("nobody").await,
+        Err(Error::ApiError(e)) if e.code == "not_found"
+    ));
+
+    let group = obs.group("devs").await.unwrap();
+    assert_eq!(group.title, "devs");
+    assert_eq!(group.email, None);
+    assert_eq!(group.maintainers.len(), 1);
+    assert_eq!(group.maintainers[0].userid, "alice");
+    let members: Vec<_> = group.members.persons.iter().map(|p| &p.userid).collect();
+    assert_eq!(members, ["alice", "bob"]);
+
+    assert!(project.add_role(&alice, Role::Maintainer).await.unwrap());
+    assert!(!project.add_role(&alice, Role::Maintainer).await.unwrap());
+    assert!(project.add_role(&devs, Role::Bugowner).await.unwrap());
+    assert!(project.add_role(&alice, Role::Bugowner).await.unwrap());
+    assert_eq!(
+        mock.project_roles(TEST_PROJECT),
+        [
+            (
+                MockRoleHolder::User("alice".to_owned()),
+                "maintainer".to_owned()
+            ),
+            (
+                MockRoleHolder::User("alice".to_owned()),
+                "bugowner".to_owned()
+            ),
+            (
+                MockRoleHolder::Group("devs".to_owned()),
+                "bugowner".to_owned()
+            ),
+        ]
+    );
+    let meta = project.meta().await.unwrap();
+    assert_eq!(meta.persons.len(), 2);
+    assert_eq!(meta.groups[0].groupid, "devs");
+    assert_eq!(meta.groups[0].role, Role::Bugowner);
+
+    let package_meta = async || {
+        let data: Vec<_> = package_1
+            .source_file("_meta")
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        String::from_utf8(data.concat()).unwrap()
+    };
+    let original = package_meta().await;
+    assert!(package_1.add_role(&devs, Role::Reviewer).await.unwrap());
+    assert!(package_1.add_role(&alice, Role::Maintainer).await.unwrap());
+    assert_eq!(
+        package_meta().await,
+        format!(
+            r#"<package project="{TEST_PROJECT}" name="{TEST_PACKAGE_1}">
+        <title/>
+        <description/>
+        <person userid="alice" role="maintainer"/>
+        <group groupid="devs" role="reviewer"/>
+</package>"#
+        )
+    );
+    let meta = package_1.meta().await.unwrap();
+    assert_eq!(meta.persons[0].userid, "alice");
+    assert_eq!(meta.persons[0].role, Role::Maintainer);
+
+    let involved = obs.involved(&alice, None).await.unwrap();
+    let projects: Vec<_> = involved.projects.iter().map(|p| &p.name).collect();
+    assert_eq!(projects, [TEST_PROJECT]);
+    assert_eq!(involved.packages.len(), 1);
+    assert_eq!(involved.packages[0].project, TEST_PROJECT);
+    assert_eq!(involved.packages[0].name, TEST_PACKAGE_1);
+    let involved = obs.involved(&devs, Some(Role::Maintainer)).await.unwrap();
+    assert!(involved.projects.is_empty());
+    assert!(involved.packages.is_empty());
+    let involved = obs.involved(&devs, Some(Role::Reviewer)).await.unwrap();
+    assert!(involved.projects.is_empty());
+    assert_eq!(involved.packages.len(), 1);
+
+    // Quotes in names are kept from ending the XPath string early.
+    let quoted = RoleHolder::User("o'brien".to_owned());
+    let involved = obs.involved(&quoted, None).await.unwrap();
+    assert!(involved.projects.is_empty());
+    assert!(project.add_role(&quoted, Role::Reader).await.unwrap());
+    let involved = obs.involved(&quoted, Some(Role::Reader)).await.unwrap();
+    let projects: Vec<_> = involved.projects.iter().map(|p| &p.name).collect();
+    assert_eq!(projects, [TEST_PROJECT]);
+    assert!(project.remove_role(&quoted, Role::Reader).await.unwrap());
+
+    assert!(package_1.remove_role(&devs, Role::Reviewer).await.unwrap());
+    assert!(
+        package_1
+            .remove_role(&alice, Role::Maintainer)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !package_1
+            .remove_role(&alice, Role::Maintainer)
+            .await
+            .unwrap()
+    );
+    assert_eq!(package_meta().await, original);
+
+    // Simulate the meta changing between reading and writing it back, which
+    // makes the edit be redone on the current meta.
+    let changed = original.replace("<title/>", "<title>Changed</title>");
+    mock.inject_faults(MockFault::Body(changed.into_bytes()), 1);
+    assert!(package_1.add_role(&alice, Role::Reader).await.unwrap());
+    let meta = package_meta().await;
+    assert!(meta.contains("<title/>"));
+    assert!(meta.contains(r#"<person userid="alice" role="reader"/>"#));
+    assert!(package_1.remove_role(&alice, Role::Reader).await.unwrap());
+
+    for i in 0..4 {
+        let meta = original.replace("<title/>", &format!("<title>{i}</title>"));
+        mock.inject_faults(MockFault::Body(meta.into_bytes()), 1);
+    }
+    assert!(matches!(
+        package_1.add_role(&devs, Role::Maintainer).await,
+        Err(Error::Conflict)
+    ));
+    assert!(package_1.meta().await.unwrap().groups.is_empty());
+
+    // Roles unknown to the client don't keep the meta from being read.
+    let custom = Role::Other("cleaner".to_owned());
+    assert!(package_1.add_role(&alice, custom.clone()).await.unwrap());
+    assert!(package_meta().await.contains(r#"role="cleaner""#));
+    let meta = package_1.meta().await.unwrap();
+    assert_eq!(meta.persons[0].role, custom);
+    assert!(package_1.remove_role(&alice, custom).await.unwrap());
+    assert_eq!(package_meta().await, original);
+}
+
+#[tokio::test]
+async fn test_tokens() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_2.to_owned(),
+        MockPackageOptions::default(),
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+    assert!(
+        obs.tokens(DEFAULT_USERNAME)
+            .await
+            .unwrap()
+            .tokens
+            .is_empty()
+    );
+
+    let runservice = obs
+        .create_token(
+            DEFAULT_USERNAME,
+            TokenKind::Runservice,
+            &TokenOptions {
+                project: Some(TEST_PROJECT.to_owned()),
+                package: Some(TEST_PACKAGE_1.to_owned()),
+                description: Some("deploy".to_owned()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let rebuild = obs
+        .create_token(
+            DEFAULT_USERNAME,
+            TokenKind::Rebuild,
+            &TokenOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_ne!(runservice.string, rebuild.string);
+
+    let tokens = obs.tokens(DEFAULT_USERNAME).await.unwrap().tokens;
+    assert_eq!(tokens.len(), 2);
+    assert_eq!(tokens[0].id, runservice.id);
+    assert_eq!(tokens[0].string, runservice.string);
+    assert_eq!(tokens[0].kind, TokenKind::Runservice);
+    assert_eq!(tokens[0].description, "deploy");
+    assert_eq!(tokens[0].project.as_deref(), Some(TEST_PROJECT));
+    assert_eq!(tokens[0].package.as_deref(), Some(TEST_PACKAGE_1));
+    assert_eq!(tokens[0].triggered_at, None);
+    assert_eq!(tokens[1].kind, TokenKind::Rebuild);
+    assert_eq!(tokens[1].package, None);
+
+    // Kinds unknown to the client don't keep the others from being listed.
+    mock.inject_faults(
+        MockFault::Body(
+            br#"<directory count="2">
+  <entry id="1" string="abc" kind="rebuild"/>
+  <entry id="2" string="def" kind="scmsync"/>
+</directory>"#
+                .to_vec(),
+        ),
+        1,
+    );
+    let kinds: Vec<_> = obs
+        .tokens(DEFAULT_USERNAME)
+        .await
+        .unwrap()
+        .tokens
+        .into_iter()
+        .map(|token| token.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        [TokenKind::Rebuild, TokenKind::Other("scmsync".to_owned())]
+    );
+    assert_eq!(kinds[1].to_string(), "scmsync");
+
+    assert!(matches!(
+        obs.create_token(DEFAULT_USERNAME, TokenKind::Workflow, &TokenOptions::default())
+            .await,
+        Err(Error::ApiError(e)) if e.code == "missing_parameter"
+    ));
+    assert!(matches!(
+        obs.tokens("someone-else").await,
+        Err(Error::ApiError(e)) if e.code == "no_permission"
+    ));
+
+    // Scoped tokens trigger their own package.
+    let trigger = TriggerClient::new(mock.uri(), runservice.string.clone()).unwrap();
+    trigger
+        .runservice(&TriggerOptions::default())
+        .await
+        .unwrap();
+    assert!(matches!(
+        trigger.rebuild(&TriggerOptions::default()).await,
+        Err(Error::ApiError(e)) if e.code == "invalid_token"
+    ));
+
+    let trigger = TriggerClient::new(mock.uri(), rebuild.string.clone()).unwrap();
+    assert!(matches!(
+        trigger.rebuild(&TriggerOptions::default()).await,
+        Err(Error::ApiError(e)) if e.code == "missing_parameter"
+    ));
+    trigger
+        .rebuild(&TriggerOptions {
+            project: Some(TEST_PROJECT.to_owned()),
+            package: Some(TEST_PACKAGE_2.to_owned()),
+            repository: Some(TEST_REPO.to_owned()),
+            arch: Some(TEST_ARCH_1.to_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(
+        mock.triggers(),
+        [
+            MockTrigger {
+                kind: MockTokenKind::Runservice,
+                token_id: runservice.id,
+                project: TEST_PROJECT.to_owned(),
+                package: TEST_PACKAGE_1.to_owned(),
+                params: vec![],
+            },
+            MockTrigger {
+                kind: MockTokenKind::Rebuild,
+                token_id: rebuild.id,
+                project: TEST_PROJECT.to_owned(),
+                package: TEST_PACKAGE_2.to_owned(),
+                params: vec![
+                    ("repository".to_owned(), TEST_REPO.to_owned()),
+                    ("arch".to_owned(), TEST_ARCH_1.to_owned()),
+                ],
+            },
+        ]
+    );
+    let tokens = obs.tokens(DEFAULT_USERNAME).await.unwrap().tokens;
+    assert!(tokens[0].triggered_at.is_some());
+
+    obs.delete_token(DEFAULT_USERNAME, rebuild.id)
+        .await
+        .unwrap();
+    assert_eq!(obs.tokens(DEFAULT_USERNAME).await.unwrap().tokens.len(), 1);
+    assert!(matches!(
+        obs.delete_token(DEFAULT_USERNAME, rebuild.id).await,
+        Err(Error::ApiError(e)) if e.code == "not_found"
+    ));
+    assert!(matches!(
+        trigger.rebuild(&TriggerOptions::default()).await,
+        Err(Error::ApiError(e)) if e.code == "authentication_required"
+    ));
+}
+
+#[cfg(feature = "workflow")]
+#[test]
+fn test_workflow_config() {
+    use open_build_service_api::workflow::*;
+
+    let workflows = Workflows::parse(include_str!("data/workflow/workflows.yml")).unwrap();
+    let names: Vec<_> = workflows.workflows.iter().map(|(name, _)| name).collect();
+    assert_eq!(names, ["pr_workflow", "push_workflow"]);
+
+    let pr = workflows.get("pr_workflow").unwrap();
+    assert_eq!(pr.filters.event, Some(Event::PullRequest));
+    assert_eq!(pr.filters.branches.as_ref().unwrap().only, ["main"]);
+    assert_eq!(pr.steps.len(), 4);
+    let Step::BranchPackage(branch) = &pr.steps[0] else {
+        panic!("unexpected step {:?}", pr.steps[0]);
+    };
+    assert_eq!(branch.source_package, "hello");
+    assert_eq!(branch.target_project, "home:user:ci:PR-%{SCM_PR_NUMBER}");
+    assert_eq!(branch.add_repositories, None);
+    let Step::ConfigureRepositories(configure) = &pr.steps[1] else {
+        panic!("unexpected step {:?}", pr.steps[1]);
+    };
+    assert_eq!(configure.repositories[0].name, "openSUSE_Tumbleweed");
+    assert_eq!(
+        configure.repositories[0].paths[0],
+        RepositoryPath {
+            target_project: "openSUSE:Factory".to_owned(),
+            target_repository: "snapshot".to_owned(),
+        }
+    );
+    assert_eq!(
+        configure.repositories[0].architectures,
+        ["x86_64", "aarch64"]
+    );
+    let Step::SetFlags(set_flags) = &pr.steps[2] else {
+        panic!("unexpected step {:?}", pr.steps[2]);
+    };
+    assert_eq!(set_flags.flags[0].flag_type, FlagType::Publish);
+    assert_eq!(set_flags.flags[0].status, FlagStatus::Enable);
+    assert_eq!(set_flags.flags[0].repository, None);
+    let Step::SubmitRequest(submit) = &pr.steps[3] else {
+        panic!("unexpected step {:?}", pr.steps[3]);
+    };
+    assert_eq!(submit.target_project, "home:user:ci");
+    assert_eq!(submit.target_package, None);
+    assert_eq!(
+        submit.description.as_deref(),
+        Some("Changes of %{SCM_REPOSITORY_NAME}#%{SCM_PR_NUMBER}")
+    );
+
+    let issues: Vec<_> = workflows
+        .validate()
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        issues,
+        [
+            "push_workflow: branch filters can't have both only and ignore",
+            "push_workflow, step 1: unknown placeholder %{SCM_BRANCH_NAME}",
+            "push_workflow, step 3: no flags to set",
+        ]
+    );
+
+    let err = Workflows::parse(
+        "workflow:\n  steps:\n    - rebuild_package:\n        project: home:user\n        pakage: hello\n",
+    )
+    .unwrap_err();
+    assert_eq!(err.location().map(|(line, _)| line), Some(5));
+    assert!(err.to_string().contains("pakage"));
+    assert!(Workflows::parse("workflow:\n  steps:\n    - unknown_step: {}\n").is_err());
+}
+
+#[tokio::test]
+async fn test_workflow_trigger() {
+    let mock = start_mock().await;
+    let obs = create_authenticated_client(mock.clone());
+
+    let token = obs
+        .create_token(
+            DEFAULT_USERNAME,
+            TokenKind::Workflow,
+            &TokenOptions {
+                scm_token: Some("scm-secret".to_owned()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let trigger = TriggerClient::new(mock.uri(), token.string.clone()).unwrap();
+
+    let payload = r#"{"action": "opened", "number": 1}"#;
+    trigger
+        .workflow(token.id, Scm::GitHub, "pull_request", payload.to_owned())
+        .await
+        .unwrap();
+    trigger
+        .workflow(token.id, Scm::GitLab, "Push Hook", "{}".to_owned())
+        .await
+        .unwrap();
+    assert!(matches!(
+        trigger
+            .workflow(token.id, Scm::GitHub, "issues", "{}".to_owned())
+            .await,
+        Err(Error::ApiError(e)) if e.code == "bad_request"
+    ));
+    assert!(matches!(
+        trigger
+            .workflow(token.id + 1, Scm::GitHub, "push", "{}".to_owned())
+            .await,
+        Err(Error::ApiError(e)) if e.code == "invalid_token"
+    ));
+
+    let runs = mock.workflow_runs();
+    assert_eq!(runs.len(), 2);
+    mock.set_workflow_run_status(
+        runs[0].id,
+        MockWorkflowRunStatus::Fail,
+        Some("Failed to branch".to_owned()),
+    );
+
+    let runs = obs
+        .workflow_runs(token.id, &WorkflowRunListOptions::default())
+        .await
+        .unwrap()
+        .runs;
+    assert_eq!(runs.len(), 2);
+    assert_eq!(runs[0].status, WorkflowRunStatus::Fail);
+    assert_eq!(runs[0].hook_event.as_deref(), Some("pull_request"));
+    assert_eq!(runs[0].generic_event_type.as_deref(), Some("pull_request"));
+    assert_eq!(runs[0].scm_vendor.as_deref(), Some("github"));
+    assert_eq!(runs[0].request_payload.as_deref(), Some(payload));
+    assert_eq!(runs[0].response_body.as_deref(), Some("Failed to branch"));
+    assert_eq!(runs[1].status, WorkflowRunStatus::Running);
+    assert_eq!(runs[1].scm_vendor.as_deref(), Some("gitlab"));
+
+    let runs = obs
+        .workflow_runs(
+            token.id,
+            &WorkflowRunListOptions {
+                status: Some(WorkflowRunStatus::Running),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .runs;
+    assert_eq!(runs.len(), 1);
+    assert_eq!(runs[0].generic_event_type.as_deref(), Some("push"));
+
+    let run = obs.workflow_run(runs[0].id).await.unwrap();
+    assert_eq!(run.hook_event.as_deref(), Some("Push Hook"));
+    assert!(matches!(
+        obs.workflow_run(runs[0].id + 10).await,
+        Err(Error::ApiError(e)) if e.code == "not_found"
+    ));
+}
+
 #[tokio::test]
 async fn test_project_meta() {
     let mock = start_mock().await;
@@ -114,7 +2298,7 @@ async fn test_project_package_delete() {
     let err = package_1.list(None).await.unwrap_err();
     assert!(matches!(
         err,
-        Error::ApiError(ApiError { code, .. }) if code == "unknown_package"
+        Error::ApiError(e) if e.code == "unknown_package"
     ));
 
     project.meta().await.unwrap();
@@ -124,7 +2308,7 @@ async fn test_project_package_delete() {
     let err = project.meta().await.unwrap_err();
     assert!(matches!(
         err,
-        Error::ApiError(ApiError { code, .. }) if code == "unknown_project"
+        Error::ApiError(e) if e.code == "unknown_project"
     ));
 }
 
@@ -422,97 +2606,606 @@ async fn test_source_get() {
         },
     );
 
-    mock.add_package_revision(
-        TEST_PROJECT,
-        TEST_PACKAGE_1,
-        MockRevisionOptions::default(),
-        [(
-            test_file.to_owned(),
-            MockEntry::from_key(&test_key, SystemTime::now()),
-        )]
-        .into(),
+    mock.add_package_revision(
+        TEST_PROJECT,
+        TEST_PACKAGE_1,
+        MockRevisionOptions::default(),
+        [(
+            test_file.to_owned(),
+            MockEntry::from_key(&test_key, SystemTime::now()),
+        )]
+        .into(),
+    );
+
+    let obs = create_authenticated_client(mock);
+
+    let mut data = Vec::new();
+    obs.project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned())
+        .source_file(test_file)
+        .await
+        .unwrap()
+        .try_for_each(|chunk| {
+            data.extend_from_slice(&chunk);
+            futures::future::ready(Ok(()))
+        })
+        .await
+        .unwrap();
+    assert_eq!(&data[..], test_contents);
+}
+
+#[tokio::test]
+async fn test_cache() {
+    let base = std::env::temp_dir().join(format!("obs-cache-test-{}", std::process::id()));
+    let sources = base.join("sources");
+    let cache_dir = base.join("cache");
+    std::fs::create_dir_all(&sources).unwrap();
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_or_update_repository(
+        TEST_PROJECT,
+        TEST_REPO.to_owned(),
+        TEST_ARCH_1.to_owned(),
+        MockRepositoryCode::Finished,
+    );
+
+    let obs = Client::builder(mock.uri())
+        .basic_auth(DEFAULT_USERNAME.to_owned(), DEFAULT_PASSWORD.to_owned())
+        .cache(Cache::new(&cache_dir, 50))
+        .build()
+        .unwrap();
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    package_1.create().await.unwrap();
+
+    let contents = |c: char, len| c.to_string().repeat(len);
+    for (name, len) in [("a", 20), ("b", 20), ("c", 20), ("big", 100)] {
+        std::fs::write(
+            sources.join(name),
+            contents(name.chars().next().unwrap(), len),
+        )
+        .unwrap();
+    }
+    let directory = package_1
+        .commit_directory(&sources, &CommitDirectoryOptions::new(RemoteFiles::Delete))
+        .await
+        .unwrap();
+    let md5 = |name: &str| {
+        directory
+            .entries
+            .iter()
+            .find(|e| e.name == name)
+            .unwrap()
+            .md5
+            .clone()
+    };
+
+    async fn read_source(package: &PackageBuilder<'_>, file: &str) -> String {
+        let data = package
+            .source_file(file)
+            .await
+            .unwrap()
+            .map_ok(|chunk| chunk.to_vec())
+            .try_concat()
+            .await
+            .unwrap();
+        String::from_utf8(data).unwrap()
+    }
+
+    assert_eq!(read_source(&package_1, "a").await, contents('a', 20));
+    let cached_a = cache_dir.join("sources").join(md5("a"));
+    assert!(cached_a.exists());
+
+    // Served from the cache from now on.
+    std::fs::write(&cached_a, contents('x', 20)).unwrap();
+    assert_eq!(read_source(&package_1, "a").await, contents('x', 20));
+
+    assert_eq!(read_source(&package_1, "b").await, contents('b', 20));
+    // Larger than the whole cache, so never stored.
+    assert_eq!(read_source(&package_1, "big").await, contents('b', 100));
+    assert!(!cache_dir.join("sources").join(md5("big")).exists());
+
+    // Going over the limit evicts the least recently used file.
+    assert_eq!(read_source(&package_1, "c").await, contents('c', 20));
+    assert!(!cached_a.exists());
+    assert!(cache_dir.join("sources").join(md5("b")).exists());
+    assert!(cache_dir.join("sources").join(md5("c")).exists());
+
+    // A download not matching the listed md5 is rejected rather than cached.
+    mock.inject_faults(MockFault::Delay(Duration::ZERO), 1);
+    mock.inject_faults(MockFault::Body(contents('x', 20).into_bytes()), 1);
+    let Err(err) = package_1.source_file("a").await else {
+        panic!("corrupted download was accepted");
+    };
+    assert!(
+        matches!(&err, Error::ChecksumMismatch(expected, _) if *expected == md5("a")),
+        "{err:?}"
+    );
+    assert!(!cached_a.exists());
+
+    // Listed md5s that aren't one aren't used as cache keys.
+    let cached_sources = || {
+        std::fs::read_dir(cache_dir.join("sources"))
+            .unwrap()
+            .count()
+    };
+    let before = cached_sources();
+    for bad in ["../../escaped", &md5("a").to_uppercase()] {
+        let listing = format!(
+            r#"<directory name="{TEST_PACKAGE_1}" srcmd5="{}"><entry name="a" md5="{bad}" size="20" mtime="1"/></directory>"#,
+            directory.srcmd5,
+        );
+        mock.inject_faults(MockFault::Body(listing.into_bytes()), 1);
+        assert_eq!(read_source(&package_1, "a").await, contents('a', 20));
+        assert_eq!(cached_sources(), before);
+    }
+    assert!(!base.join("escaped").exists());
+
+    let set_binary = |contents: &[u8], mtime| {
+        mock.set_package_binaries(
+            TEST_PROJECT,
+            TEST_REPO,
+            TEST_ARCH_1,
+            TEST_PACKAGE_1.to_owned(),
+            [(
+                "test.rpm".to_owned(),
+                MockBinary {
+                    contents: contents.to_vec(),
+                    mtime: SystemTime::UNIX_EPOCH + Duration::from_secs(mtime),
+                },
+            )]
+            .into(),
+        );
+    };
+    let read_binary = || async {
+        package_1
+            .binary_file(TEST_REPO, TEST_ARCH_1, "test.rpm")
+            .await
+            .unwrap()
+            .map_ok(|chunk| chunk.to_vec())
+            .try_concat()
+            .await
+            .unwrap()
+    };
+
+    set_binary(b"binary 1", 10);
+    assert_eq!(read_binary().await, b"binary 1");
+    let cached: Vec<_> = std::fs::read_dir(cache_dir.join("binaries"))
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .collect();
+    assert_eq!(cached.len(), 1);
+    std::fs::write(&cached[0], "from cache").unwrap();
+    assert_eq!(read_binary().await, b"from cache");
+
+    // A rebuilt binary has a different key.
+    set_binary(b"binary 2", 20);
+    assert_eq!(read_binary().await, b"binary 2");
+
+    std::fs::remove_dir_all(&base).unwrap();
+}
+
+#[tokio::test]
+async fn test_commits() {
+    let test_file = "test";
+    let test_contents = b"some file contents here";
+    let test_entry = CommitFileEntry::from_contents(test_file.to_owned(), test_contents);
+
+    let file_list = CommitFileList::new().entry(test_entry.clone());
+
+    let mock = start_mock().await;
+
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = create_authenticated_client(mock);
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+
+    package_1.create().await.unwrap();
+
+    let commit_result = package_1
+        .commit(&file_list, &CommitOptions::default())
+        .await
+        .unwrap();
+    if let CommitResult::MissingEntries(missing) = commit_result {
+        assert_eq!(missing.entries.len(), 1);
+        assert_eq!(missing.entries[0].name, test_entry.name);
+        assert_eq!(missing.entries[0].md5, test_entry.md5);
+    } else {
+        panic!("Expected missing entries, got {commit_result:?}");
+    }
+
+    package_1
+        .upload_for_commit(test_file, test_contents.to_vec())
+        .await
+        .unwrap();
+
+    let commit_result = package_1
+        .commit(
+            &file_list,
+            &CommitOptions {
+                comment: Some("test comment".to_owned()),
+            },
+        )
+        .await
+        .unwrap();
+    if let CommitResult::Success(directory) = commit_result {
+        assert_eq!(directory.entries.len(), 1);
+        assert_eq!(directory.entries[0].name, test_entry.name);
+        assert_eq!(directory.entries[0].md5, test_entry.md5);
+    } else {
+        panic!("Expected missing entries, got {commit_result:?}");
+    }
+
+    let directory = package_1.list(None).await.unwrap();
+    assert_eq!(directory.entries.len(), 1);
+    assert_eq!(directory.entries[0].name, test_entry.name);
+    assert_eq!(directory.entries[0].md5, test_entry.md5);
+
+    let revisions = package_1.revisions().await.unwrap();
+    assert_eq!(
+        revisions.revisions.last().unwrap().comment.as_deref(),
+        Some("test comment")
+    );
+}
+
+#[tokio::test]
+async fn test_upload_streaming() {
+    // Large enough to be streamed in several chunks.
+    let contents: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    let expected = CommitFileEntry::from_contents("large".to_owned(), &contents);
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = create_authenticated_client(mock);
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    package_1.create().await.unwrap();
+
+    let updates = Arc::new(Mutex::new(Vec::new()));
+    let options = UploadOptions {
+        progress: Some(Arc::new({
+            let updates = updates.clone();
+            move |progress: UploadProgress| updates.lock().unwrap().push(progress)
+        })),
+    };
+
+    let entry = package_1
+        .upload_reader_for_commit("large", std::io::Cursor::new(contents.clone()), &options)
+        .await
+        .unwrap();
+    assert_eq!(entry.name, expected.name);
+    assert_eq!(entry.md5, expected.md5);
+
+    {
+        let updates = updates.lock().unwrap();
+        assert!(updates.len() > 2);
+        assert!(updates.windows(2).all(|w| w[0].uploaded <= w[1].uploaded));
+        let last = updates.last().unwrap();
+        assert_eq!(last.uploaded, contents.len() as u64);
+        assert_eq!(last.total, None);
+    }
+
+    let path = std::env::temp_dir().join(format!("obs-upload-test-{}", std::process::id()));
+    std::fs::write(&path, b"small file").unwrap();
+    updates.lock().unwrap().clear();
+
+    let small = package_1
+        .upload_path_for_commit("small", &path, &options)
+        .await
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(
+        small.md5,
+        CommitFileEntry::from_contents("small".to_owned(), b"small file").md5
+    );
+    let last = *updates.lock().unwrap().last().unwrap();
+    assert_eq!(last.uploaded, 10);
+    assert_eq!(last.total, Some(10));
+
+    let missing = package_1
+        .upload_path_for_commit("missing", "/nonexistent/obs-upload", &options)
+        .await
+        .unwrap_err();
+    assert!(matches!(missing, Error::IoError(_)));
+
+    let file_list = CommitFileList::new().entry(entry).entry(small);
+    let commit_result = package_1
+        .commit(&file_list, &CommitOptions::default())
+        .await
+        .unwrap();
+    let CommitResult::Success(directory) = commit_result else {
+        panic!("Expected success, got {commit_result:?}");
+    };
+    assert_eq!(directory.entries.len(), 2);
+
+    let mut downloaded = Vec::new();
+    let mut stream = package_1.source_file("large").await.unwrap();
+    while let Some(chunk) = stream.next().await {
+        downloaded.extend_from_slice(&chunk.unwrap());
+    }
+    assert_eq!(downloaded, contents);
+}
+
+#[tokio::test]
+async fn test_commit_directory() {
+    let dir = std::env::temp_dir().join(format!("obs-commit-dir-test-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("subdir")).unwrap();
+    std::fs::write(dir.join("test.spec"), b"spec").unwrap();
+    std::fs::write(dir.join("test.tar.gz"), b"tarball contents").unwrap();
+    std::fs::write(dir.join("build.log"), b"log").unwrap();
+    std::fs::write(dir.join("subdir").join("nested"), b"nested").unwrap();
+
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+
+    let obs = create_authenticated_client(mock);
+    let package_1 = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+    package_1.create().await.unwrap();
+    package_1
+        .upload_for_commit("remote", b"remote".to_vec())
+        .await
+        .unwrap();
+    package_1
+        .commit(
+            &CommitFileList::new().file_from_contents("remote".to_owned(), b"remote"),
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+
+    // Sizes of the uploaded files, which are all distinct here.
+    let uploaded = Arc::new(Mutex::new(std::collections::BTreeSet::new()));
+    let mut options = CommitDirectoryOptions::new(RemoteFiles::Keep);
+    options.comment = Some("from directory".to_owned());
+    options.exclude = vec!["*.log".to_owned()];
+    options.upload.progress = Some(Arc::new({
+        let uploaded = uploaded.clone();
+        move |progress: UploadProgress| {
+            uploaded.lock().unwrap().insert(progress.total.unwrap());
+        }
+    }));
+
+    let directory = package_1.commit_directory(&dir, &options).await.unwrap();
+    let mut names: Vec<_> = directory.entries.iter().map(|e| e.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, ["remote", "test.spec", "test.tar.gz"]);
+    assert_eq!(
+        *uploaded.lock().unwrap(),
+        [4, 16]
+            .into_iter()
+            .collect::<std::collections::BTreeSet<u64>>()
+    );
+    let revisions = package_1.revisions().await.unwrap();
+    assert_eq!(
+        revisions.revisions.last().unwrap().comment.as_deref(),
+        Some("from directory")
+    );
+
+    // Only the changed file is uploaded again, and the remote one is dropped.
+    std::fs::write(dir.join("test.spec"), b"new spec").unwrap();
+    uploaded.lock().unwrap().clear();
+    options.remote_files = RemoteFiles::Delete;
+
+    let directory = package_1.commit_directory(&dir, &options).await.unwrap();
+    let mut entries: Vec<_> = directory.entries.iter().collect();
+    entries.sort_by_key(|e| &e.name);
+    let names: Vec<_> = entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["test.spec", "test.tar.gz"]);
+    assert_eq!(
+        entries[0].md5,
+        CommitFileEntry::from_contents("test.spec".to_owned(), b"new spec").md5
+    );
+    assert_eq!(
+        *uploaded.lock().unwrap(),
+        [8].into_iter().collect::<std::collections::BTreeSet<u64>>()
     );
 
-    let obs = create_authenticated_client(mock);
+    options.include = vec!["*.tar.?z".to_owned()];
+    let directory = package_1.commit_directory(&dir, &options).await.unwrap();
+    let names: Vec<_> = directory.entries.iter().map(|e| e.name.as_str()).collect();
+    assert_eq!(names, ["test.tar.gz"]);
 
-    let mut data = Vec::new();
-    obs.project(TEST_PROJECT.to_owned())
-        .package(TEST_PACKAGE_1.to_owned())
-        .source_file(test_file)
-        .await
-        .unwrap()
-        .try_for_each(|chunk| {
-            data.extend_from_slice(&chunk);
-            futures::future::ready(Ok(()))
-        })
+    // A single attempt doesn't get to commit the uploaded file.
+    std::fs::write(dir.join("new.tar.xz"), b"new").unwrap();
+    options.max_attempts = 1;
+    let err = package_1
+        .commit_directory(&dir, &options)
         .await
-        .unwrap();
-    assert_eq!(&data[..], test_contents);
+        .unwrap_err();
+    assert!(
+        matches!(&err, Error::CommitIncomplete(missing) if missing == &["new.tar.xz"]),
+        "{err:?}"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
 }
 
 #[tokio::test]
-async fn test_commits() {
-    let test_file = "test";
-    let test_contents = b"some file contents here";
-    let test_entry = CommitFileEntry::from_contents(test_file.to_owned(), test_contents);
+async fn test_osc_working_copy() {
+    use open_build_service_api::osc::{FileStatus, WorkingCopy};
 
-    let file_list = CommitFileList::new().entry(test_entry.clone());
+    let base = std::env::temp_dir().join(format!("obs-osc-test-{}", std::process::id()));
+    let sources = base.join("sources");
+    let checkout = base.join("checkout");
+    std::fs::create_dir_all(&sources).unwrap();
 
     let mock = start_mock().await;
-
     mock.add_project(TEST_PROJECT.to_owned());
 
-    let obs = create_authenticated_client(mock);
+    let obs = create_authenticated_client(mock.clone());
     let package_1 = obs
         .project(TEST_PROJECT.to_owned())
         .package(TEST_PACKAGE_1.to_owned());
-
     package_1.create().await.unwrap();
 
-    let commit_result = package_1
-        .commit(&file_list, &CommitOptions::default())
+    let options = CommitDirectoryOptions::new(RemoteFiles::Delete);
+    for (name, contents) in [("a", "a1"), ("b", "b1"), ("c", "c1"), ("e", "e1")] {
+        std::fs::write(sources.join(name), contents).unwrap();
+    }
+    package_1
+        .commit_directory(&sources, &options)
         .await
         .unwrap();
-    if let CommitResult::MissingEntries(missing) = commit_result {
-        assert_eq!(missing.entries.len(), 1);
-        assert_eq!(missing.entries[0].name, test_entry.name);
-        assert_eq!(missing.entries[0].md5, test_entry.md5);
-    } else {
-        panic!("Expected missing entries, got {commit_result:?}");
+    for (name, contents) in [("a", "a2"), ("c", "c2"), ("d", "d2")] {
+        std::fs::write(sources.join(name), contents).unwrap();
     }
-
+    std::fs::remove_file(sources.join("e")).unwrap();
     package_1
-        .upload_for_commit(test_file, test_contents.to_vec())
+        .commit_directory(&sources, &options)
         .await
         .unwrap();
 
-    let commit_result = package_1
-        .commit(
-            &file_list,
-            &CommitOptions {
-                comment: Some("test comment".to_owned()),
-            },
-        )
+    let mut wc = WorkingCopy::checkout(&package_1, &checkout, Some("1"))
         .await
         .unwrap();
-    if let CommitResult::Success(directory) = commit_result {
-        assert_eq!(directory.entries.len(), 1);
-        assert_eq!(directory.entries[0].name, test_entry.name);
-        assert_eq!(directory.entries[0].md5, test_entry.md5);
-    } else {
-        panic!("Expected missing entries, got {commit_result:?}");
+    assert_eq!(wc.files().rev.as_deref(), Some("1"));
+    assert_eq!(std::fs::read_to_string(checkout.join("a")).unwrap(), "a1");
+    assert_eq!(
+        std::fs::read_to_string(checkout.join(".osc/_project")).unwrap(),
+        format!("{TEST_PROJECT}\n")
+    );
+    assert_eq!(
+        std::fs::read_to_string(checkout.join(".osc/_package")).unwrap(),
+        format!("{TEST_PACKAGE_1}\n")
+    );
+    assert_eq!(
+        std::fs::read_to_string(checkout.join(".osc/c")).unwrap(),
+        "c1"
+    );
+    assert!(
+        std::fs::read_to_string(checkout.join(".osc/_meta"))
+            .unwrap()
+            .contains(TEST_PACKAGE_1)
+    );
+
+    // File names from the server that would escape the working copy are
+    // refused before anything is written.
+    let escaping = base.join("escaping");
+    for name in ["../evil", "/tmp/evil", "sub/file", ".", ".."] {
+        let listing = format!(
+            r#"<directory name="{TEST_PACKAGE_1}" rev="1" srcmd5="{}"><entry name="{name}" md5="{}" size="1" mtime="1"/></directory>"#,
+            "0".repeat(32),
+            "1".repeat(32),
+        );
+        mock.inject_faults(MockFault::Body(listing.into_bytes()), 1);
+        let err = WorkingCopy::checkout(&package_1, &escaping, None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(err, Error::InvalidFileName(ref n) if n == name),
+            "{err:?}"
+        );
+        assert!(!escaping.exists());
     }
 
-    let directory = package_1.list(None).await.unwrap();
-    assert_eq!(directory.entries.len(), 1);
-    assert_eq!(directory.entries[0].name, test_entry.name);
-    assert_eq!(directory.entries[0].md5, test_entry.md5);
+    // Keeping the existing checkout intact.
+    assert!(
+        WorkingCopy::checkout(&package_1, &checkout, None)
+            .await
+            .is_err()
+    );
 
-    let revisions = package_1.revisions().await.unwrap();
+    std::fs::write(checkout.join("b"), "b-local").unwrap();
+    std::fs::write(checkout.join("c"), "c-local").unwrap();
+    std::fs::write(checkout.join("new"), "new").unwrap();
+    std::fs::write(checkout.join("junk"), "junk").unwrap();
+    wc.add("new").await.unwrap();
+    wc.delete("e").await.unwrap();
+    assert!(wc.add("nonexistent").await.is_err());
+
+    let status = wc.status().await.unwrap();
+    let expected = [
+        ("a", FileStatus::Unchanged),
+        ("b", FileStatus::Modified),
+        ("c", FileStatus::Modified),
+        ("e", FileStatus::Deleted),
+        ("junk", FileStatus::Unversioned),
+        ("new", FileStatus::Added),
+    ];
     assert_eq!(
-        revisions.revisions.last().unwrap().comment.as_deref(),
-        Some("test comment")
+        status
+            .iter()
+            .map(|(n, s)| (n.as_str(), *s))
+            .collect::<Vec<_>>(),
+        expected
     );
+
+    let reopened = WorkingCopy::open(&checkout).await.unwrap();
+    assert_eq!(reopened.apiurl(), wc.apiurl());
+    assert_eq!(reopened.project(), TEST_PROJECT);
+    assert_eq!(reopened.package(), TEST_PACKAGE_1);
+    assert_eq!(reopened.to_be_added(), ["new"]);
+    assert_eq!(reopened.to_be_deleted(), ["e"]);
+    assert_eq!(reopened.status().await.unwrap(), status);
+
+    let other = Client::new(
+        "https://api.example.com".parse().unwrap(),
+        "user".to_owned(),
+        "pass".to_owned(),
+    );
+    let err = wc.update(&other, None).await.unwrap_err();
+    assert!(matches!(err, Error::ApiUrlMismatch(..)), "{err:?}");
+
+    // The store's meta is refreshed along with the files.
+    package_1
+        .add_role(&RoleHolder::User("alice".to_owned()), Role::Reader)
+        .await
+        .unwrap();
+    let result = wc.update(&obs, None).await.unwrap();
+    assert!(
+        std::fs::read_to_string(checkout.join(".osc/_meta"))
+            .unwrap()
+            .contains(r#"userid="alice""#)
+    );
+    assert_eq!(result.updated, ["a"]);
+    assert_eq!(result.added, ["d"]);
+    assert!(result.deleted.is_empty());
+    assert_eq!(result.conflicted, ["c"]);
+    assert_eq!(wc.files().rev.as_deref(), Some("2"));
+    assert_eq!(std::fs::read_to_string(checkout.join("a")).unwrap(), "a2");
+    assert_eq!(
+        std::fs::read_to_string(checkout.join("c")).unwrap(),
+        "c-local"
+    );
+    assert_eq!(
+        std::fs::read_to_string(checkout.join("c.r2")).unwrap(),
+        "c2"
+    );
+    assert!(wc.to_be_deleted().is_empty());
+
+    let status = wc.status().await.unwrap();
+    let expected = [
+        ("a", FileStatus::Unchanged),
+        ("b", FileStatus::Modified),
+        ("c", FileStatus::Conflicted),
+        ("c.r2", FileStatus::Unversioned),
+        ("d", FileStatus::Unchanged),
+        ("junk", FileStatus::Unversioned),
+        ("new", FileStatus::Added),
+    ];
+    assert_eq!(
+        status
+            .iter()
+            .map(|(n, s)| (n.as_str(), *s))
+            .collect::<Vec<_>>(),
+        expected
+    );
+
+    wc.resolved("c").await.unwrap();
+    let reopened = WorkingCopy::open(&checkout).await.unwrap();
+    let status = reopened.status().await.unwrap();
+    assert_eq!(status["c"], FileStatus::Modified);
+    assert_eq!(status["d"], FileStatus::Unchanged);
+
+    std::fs::remove_dir_all(&base).unwrap();
 }
 
 #[tokio::test]
@@ -605,9 +3298,15 @@ async fn test_branch() {
         .await
         .unwrap_err();
     assert!(matches!(
-        err,
-        Error::ApiError(ApiError { code,.. }) if code == "double_branch_package"
+        &err,
+        Error::ApiError(e) if e.code == "double_branch_package"
     ));
+    let api_error = err.api_error().unwrap();
+    assert_eq!(api_error.status, reqwest::StatusCode::BAD_REQUEST);
+    assert_eq!(
+        api_error.data("targetproject"),
+        Some(test_project_branched_1.as_str())
+    );
 
     let status = package_1
         .branch(&BranchOptions {
@@ -666,6 +3365,463 @@ async fn test_branch() {
     assert!(dir.linkinfo[0].missingok);
 }
 
+#[tokio::test]
+async fn test_maintenance() {
+    let maintenance_project = "Maintenance";
+    let update_project_1 = "Distro:1:Update";
+    let update_project_2 = "Distro:2:Update";
+
+    let mock = start_mock().await;
+    mock.add_project(maintenance_project.to_owned());
+    mock.set_project_maintenance(
+        maintenance_project,
+        vec![update_project_1.to_owned(), update_project_2.to_owned()],
+    );
+    for project in [update_project_1, update_project_2] {
+        mock.add_project(project.to_owned());
+        mock.add_new_package(
+            project,
+            TEST_PACKAGE_1.to_owned(),
+            MockPackageOptions::default(),
+        );
+    }
+    mock.add_new_package(
+        update_project_1,
+        TEST_PACKAGE_2.to_owned(),
+        MockPackageOptions::default(),
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+
+    let meta = obs
+        .project(maintenance_project.to_owned())
+        .meta()
+        .await
+        .unwrap();
+    assert_eq!(meta.kind, ProjectKind::Maintenance);
+    let maintains: Vec<_> = meta
+        .maintenance
+        .unwrap()
+        .maintains
+        .into_iter()
+        .map(|m| m.project)
+        .collect();
+    assert_eq!(maintains, [update_project_1, update_project_2]);
+
+    let branched_project =
+        format!("home:{DEFAULT_USERNAME}:branches:OBS_Maintained:{TEST_PACKAGE_1}");
+    let planned = obs
+        .mbranch_plan(TEST_PACKAGE_1, &BranchOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(planned.len(), 2);
+    assert_eq!(
+        obs.mbranch(TEST_PACKAGE_1, &BranchOptions::default())
+            .await
+            .unwrap(),
+        branched_project
+    );
+    for (branch, project) in planned.iter().zip([update_project_1, update_project_2]) {
+        assert_eq!(branch.source_project, project);
+        assert_eq!(branch.source_package, TEST_PACKAGE_1);
+        assert_eq!(branch.target_project, branched_project);
+        assert_eq!(
+            branch.target_package,
+            format!("{TEST_PACKAGE_1}.{}", project.replace(':', "_"))
+        );
+
+        let dir = obs
+            .project(branch.target_project.clone())
+            .package(branch.target_package.clone())
+            .list(None)
+            .await
+            .unwrap();
+        assert_eq!(dir.linkinfo[0].project, project);
+        assert_eq!(dir.linkinfo[0].package, TEST_PACKAGE_1);
+    }
+
+    // Only maintained projects that have the package are branched from.
+    let options = BranchOptions {
+        target_project: Some(branched_project.clone()),
+        ..Default::default()
+    };
+    let planned = obs.mbranch_plan(TEST_PACKAGE_2, &options).await.unwrap();
+    assert_eq!(planned.len(), 1);
+    assert_eq!(planned[0].source_project, update_project_1);
+    assert_eq!(
+        obs.mbranch(TEST_PACKAGE_2, &options).await.unwrap(),
+        branched_project
+    );
+    obs.project(branched_project.clone())
+        .package(planned[0].target_package.clone())
+        .list(None)
+        .await
+        .unwrap();
+
+    let error = obs
+        .mbranch(TEST_PACKAGE_1, &BranchOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "double_branch_package"
+    ));
+    obs.mbranch(
+        TEST_PACKAGE_1,
+        &BranchOptions {
+            force: true,
+            ..Default::default()
+        },
+    )
+    .await
+    .unwrap();
+
+    let error = obs
+        .mbranch("unmaintained", &BranchOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(error, Error::ApiError(e) if e.code == "not_found"));
+
+    let maintenance = obs.project(maintenance_project.to_owned());
+    assert!(
+        maintenance
+            .maintenance_incidents()
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let incident_1 = maintenance.create_maintenance_incident().await.unwrap();
+    assert_eq!(incident_1, format!("{maintenance_project}:1"));
+    let incident_2 = maintenance.create_maintenance_incident().await.unwrap();
+    assert_eq!(incident_2, format!("{maintenance_project}:2"));
+    assert_eq!(
+        mock.project_kind(&incident_1),
+        MockProjectKind::MaintenanceIncident
+    );
+
+    let incidents = maintenance.maintenance_incidents().await.unwrap();
+    assert_eq!(incidents.len(), 2);
+    assert_eq!(incidents[0].name, incident_1);
+    assert_eq!(incidents[1].name, incident_2);
+    for incident in &incidents {
+        assert_eq!(incident.kind, ProjectKind::MaintenanceIncident);
+        assert!(incident.maintenance.is_none());
+    }
+
+    let error = obs
+        .project(update_project_1.to_owned())
+        .create_maintenance_incident()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "incident_has_no_maintenance_project"
+    ));
+
+    let request = obs
+        .project(incident_1.clone())
+        .create_maintenance_release_request("Security update")
+        .await
+        .unwrap();
+    assert_eq!(request.id, 1);
+    assert_eq!(request.creator, DEFAULT_USERNAME);
+    assert_eq!(request.description, "Security update");
+    assert_eq!(request.state.name, BsRequestStateName::New);
+    assert_eq!(request.actions.len(), 1);
+    assert_eq!(
+        request.actions[0].action_type,
+        BsRequestActionType::MaintenanceRelease
+    );
+    assert_eq!(
+        request.actions[0].source.as_ref().unwrap().project,
+        incident_1
+    );
+
+    // Action types unknown to the client don't keep requests from being read.
+    let request: BsRequest = quick_xml::de::from_str(
+        r#"<request id="2" creator="alice">
+  <action type="release_backlog"/>
+  <state name="new" who="alice" when="2026-01-01T00:00:00"/>
+</request>"#,
+    )
+    .unwrap();
+    assert_eq!(
+        request.actions[0].action_type,
+        BsRequestActionType::Other("release_backlog".to_owned())
+    );
+
+    let requests = mock.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].actions,
+        [MockRequestAction {
+            action_type: "maintenance_release".to_owned(),
+            source: Some((incident_1.clone(), None)),
+            target: None,
+        }]
+    );
+
+    let error = obs
+        .project(update_project_1.to_owned())
+        .create_maintenance_release_request("")
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "no_maintenance_release_target"
+    ));
+}
+
+#[test]
+fn test_patchinfo_parse() {
+    let patchinfo = Patchinfo::from_xml(include_str!("data/patchinfo/_patchinfo")).unwrap();
+    assert_eq!(patchinfo.incident.as_deref(), Some("1234"));
+    assert_eq!(patchinfo.packager.as_deref(), Some("maintainer"));
+    assert_eq!(patchinfo.category, PatchCategory::Security);
+    assert_eq!(patchinfo.rating, PatchRating::Important);
+    assert_eq!(patchinfo.summary, "Security update for curl");
+    assert!(patchinfo.description.contains("CVE-2024-0001: Fixed"));
+    assert_eq!(
+        patchinfo.issues,
+        [
+            PatchIssue {
+                tracker: "bnc".to_owned(),
+                id: "1210001".to_owned(),
+                description: Some("VUL-0: CVE-2024-0001: curl: heap overflow".to_owned()),
+            },
+            PatchIssue {
+                tracker: "cve".to_owned(),
+                id: "CVE-2024-0001".to_owned(),
+                description: None,
+            },
+        ]
+    );
+    assert_eq!(patchinfo.packages, ["curl"]);
+    assert!(patchinfo.binaries.is_empty());
+    assert_eq!(
+        patchinfo.release_targets,
+        [ReleaseTarget {
+            project: "Distro:1:Update".to_owned(),
+            repository: Some("update".to_owned()),
+        }]
+    );
+    assert!(patchinfo.reboot_needed);
+    assert!(!patchinfo.relogin_needed);
+    assert!(patchinfo.zypp_restart_needed);
+    assert_eq!(
+        patchinfo.other_elements,
+        [
+            "<embargo_date>2026-11-01</embargo_date>",
+            r#"<stopped by="maintainer">Waiting for the upstream fix</stopped>"#,
+        ]
+    );
+
+    let xml = patchinfo.to_xml().unwrap();
+    assert!(xml.contains("<reboot_needed/>"));
+    assert!(xml.contains("  <embargo_date>2026-11-01</embargo_date>\n"));
+    assert!(!xml.contains("relogin_needed"));
+    assert_eq!(Patchinfo::from_xml(&xml).unwrap(), patchinfo);
+
+    let ptf = Patchinfo::from_xml(
+        "<patchinfo><category>ptf</category><rating>unspecified</rating></patchinfo>",
+    )
+    .unwrap();
+    assert_eq!(ptf.category, PatchCategory::Ptf);
+    assert_eq!(ptf.rating, PatchRating::Other("unspecified".to_owned()));
+    let xml = ptf.to_xml().unwrap();
+    assert!(xml.contains("<category>ptf</category>"));
+    assert!(xml.contains("<rating>unspecified</rating>"));
+}
+
+#[tokio::test]
+async fn test_patchinfo() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    for package in [TEST_PACKAGE_1, TEST_PACKAGE_2] {
+        mock.add_new_package(
+            TEST_PROJECT,
+            package.to_owned(),
+            MockPackageOptions::default(),
+        );
+    }
+
+    let obs = create_authenticated_client(mock);
+    let project = obs.project(TEST_PROJECT.to_owned());
+
+    let name = project
+        .create_patchinfo(&PatchinfoOptions {
+            comment: Some("Fix things".to_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(name, "patchinfo");
+
+    let package = obs.project(TEST_PROJECT.to_owned()).package(name.clone());
+    let patchinfo = package.patchinfo().await.unwrap();
+    assert_eq!(patchinfo.packager.as_deref(), Some(DEFAULT_USERNAME));
+    assert_eq!(patchinfo.category, PatchCategory::Recommended);
+    assert_eq!(patchinfo.rating, PatchRating::Low);
+    assert_eq!(patchinfo.summary, "Fix things");
+    assert!(patchinfo.packages.is_empty());
+    assert!(!patchinfo.reboot_needed);
+
+    let error = project
+        .create_patchinfo(&PatchinfoOptions::default())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "patchinfo_file_exists"
+    ));
+    project
+        .create_patchinfo(&PatchinfoOptions {
+            force: true,
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(package.patchinfo().await.unwrap().summary, "");
+
+    let other = project
+        .create_patchinfo(&PatchinfoOptions {
+            name: Some("patchinfo.other".to_owned()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(other, "patchinfo.other");
+
+    package.update_patchinfo().await.unwrap();
+    let mut patchinfo = package.patchinfo().await.unwrap();
+    assert_eq!(patchinfo.packages, [TEST_PACKAGE_1, TEST_PACKAGE_2]);
+
+    // Updating again doesn't add the packages twice.
+    package.update_patchinfo().await.unwrap();
+    assert_eq!(package.patchinfo().await.unwrap(), patchinfo);
+
+    patchinfo.category = PatchCategory::Security;
+    patchinfo.rating = PatchRating::Critical;
+    patchinfo.issues.push(PatchIssue {
+        tracker: "cve".to_owned(),
+        id: "CVE-2024-0001".to_owned(),
+        description: None,
+    });
+    patchinfo.reboot_needed = true;
+
+    let xml = patchinfo.to_xml().unwrap();
+    package
+        .upload_for_commit(PATCHINFO_FILE, xml.clone())
+        .await
+        .unwrap();
+    package
+        .commit(
+            &CommitFileList::new().file_from_contents(PATCHINFO_FILE.to_owned(), xml.as_bytes()),
+            &CommitOptions::default(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(package.patchinfo().await.unwrap(), patchinfo);
+
+    let error = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned())
+        .update_patchinfo()
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "invalid_package"
+    ));
+}
+
+#[tokio::test]
+async fn test_lock() {
+    let mock = start_mock().await;
+    mock.add_project(TEST_PROJECT.to_owned());
+    mock.add_new_package(
+        TEST_PROJECT,
+        TEST_PACKAGE_1.to_owned(),
+        MockPackageOptions::default(),
+    );
+
+    let obs = create_authenticated_client(mock.clone());
+    let project = obs.project(TEST_PROJECT.to_owned());
+    let package = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_1.to_owned());
+
+    assert!(!project.meta().await.unwrap().locked);
+    assert!(!package.meta().await.unwrap().locked);
+
+    project.lock(Some("Release 1.0")).await.unwrap();
+    assert!(project.meta().await.unwrap().locked);
+    // Locking again is fine.
+    project.lock(None).await.unwrap();
+    assert!(project.meta().await.unwrap().locked);
+    // The package meta only tells about the package's own lock.
+    assert!(!package.meta().await.unwrap().locked);
+
+    package.lock(Some("Release 1.0")).await.unwrap();
+    assert!(package.meta().await.unwrap().locked);
+    let revisions = package.list_meta(None).await.unwrap();
+    package.lock(None).await.unwrap();
+    assert_eq!(package.list_meta(None).await.unwrap().rev, revisions.rev);
+
+    // Changes are refused while locked, also through the project.
+    let user = RoleHolder::User(DEFAULT_USERNAME.to_owned());
+    let error = package.add_role(&user, Role::Bugowner).await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "modify_project_no_permission"
+    ));
+    assert!(project.add_role(&user, Role::Bugowner).await.is_err());
+
+    assert!(project.unlock("Released").await.unwrap());
+    assert!(!project.meta().await.unwrap().locked);
+    let error = package.add_role(&user, Role::Bugowner).await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "modify_package_no_permission"
+    ));
+    assert!(project.add_role(&user, Role::Bugowner).await.unwrap());
+
+    assert!(package.unlock("Released").await.unwrap());
+    assert!(!package.meta().await.unwrap().locked);
+    assert!(package.add_role(&user, Role::Bugowner).await.unwrap());
+
+    // Unlocking what isn't locked, e.g. when cleaning up after a partially
+    // failed release, just reports that.
+    assert!(!package.unlock("Released").await.unwrap());
+    assert!(!project.unlock("Released").await.unwrap());
+
+    // If checking the lock fails too, the unlock error is what's reported.
+    mock.inject_faults(MockFault::Delay(Duration::ZERO), 1);
+    mock.inject_faults(
+        MockFault::ApiError {
+            code: 403,
+            error_code: "source_access_no_permission".to_owned(),
+            summary: "no read access".to_owned(),
+            details: None,
+        },
+        1,
+    );
+    let error = package.unlock("Released").await.unwrap_err();
+    assert!(matches!(
+        error,
+        Error::ApiError(e) if e.code == "not_locked"
+    ));
+
+    let error = obs
+        .project(TEST_PROJECT.to_owned())
+        .package(TEST_PACKAGE_2.to_owned())
+        .unlock("Released")
+        .await
+        .unwrap_err();
+    assert!(error.is_not_found());
+}
+
 fn get_results_by_arch(mut results: ResultList) -> (ResultListResult, ResultListResult) {
     assert_eq!(results.results.len(), 2);
 
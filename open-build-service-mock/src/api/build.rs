@@ -2,8 +2,7 @@ use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
-use wiremock::ResponseTemplate;
-use wiremock::{Request, Respond};
+use wiremock::Request;
 
 use crate::{MockBuildStatus, MockPackageCode, ObsMock};
 
@@ -43,10 +42,8 @@ impl ProjectBuildCommandResponder {
     }
 }
 
-impl Respond for ProjectBuildCommandResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for ProjectBuildCommandResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let project_name = components.next_back().unwrap();
 
@@ -131,7 +128,7 @@ impl Respond for ProjectBuildCommandResponder {
                     }
                 }
 
-                ResponseTemplate::new(StatusCode::OK)
+                MockResponse::new(StatusCode::OK)
                     .set_body_xml(build_status_xml("ok", None, |_| Ok(())).unwrap())
             }
             _ => ApiError::new(
@@ -154,10 +151,8 @@ impl RepoListingResponder {
     }
 }
 
-impl Respond for RepoListingResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for RepoListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let project_name = components.next_back().unwrap();
 
@@ -181,7 +176,7 @@ impl Respond for RepoListingResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(200).set_body_xml(xml)
+        MockResponse::new(200).set_body_xml(xml)
     }
 }
 
@@ -195,10 +190,8 @@ impl ArchListingResponder {
     }
 }
 
-impl Respond for ArchListingResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for ArchListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let repo_name = components.nth_back(0).unwrap();
         let project_name = components.nth_back(0).unwrap();
@@ -229,7 +222,7 @@ impl Respond for ArchListingResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
 
@@ -267,10 +260,8 @@ fn package_status_xml(
     Ok(())
 }
 
-impl Respond for BuildResultsResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildResultsResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let project_name = components.nth_back(1).unwrap();
 
@@ -346,7 +337,7 @@ impl Respond for BuildResultsResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(200).set_body_xml(xml)
+        MockResponse::new(200).set_body_xml(xml)
     }
 }
 
@@ -360,10 +351,8 @@ impl BuildJobHistoryResponder {
     }
 }
 
-impl Respond for BuildJobHistoryResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildJobHistoryResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut package_names = vec![];
         let mut code_names = vec![];
         let mut limit = None;
@@ -468,7 +457,7 @@ impl Respond for BuildJobHistoryResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
 
@@ -482,10 +471,8 @@ impl BuildBinaryListResponder {
     }
 }
 
-impl Respond for BuildBinaryListResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildBinaryListResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(0).unwrap();
         let arch = components.nth_back(0).unwrap();
@@ -535,7 +522,7 @@ impl Respond for BuildBinaryListResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
 
@@ -549,10 +536,8 @@ impl BuildBinaryFileResponder {
     }
 }
 
-impl Respond for BuildBinaryFileResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildBinaryFileResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let file_name = components.nth_back(0).unwrap();
         let package_name = components.nth_back(0).unwrap();
@@ -594,7 +579,7 @@ impl Respond for BuildBinaryFileResponder {
                     format!("{file_name}: No such file or directory")
                 ))
         );
-        ResponseTemplate::new(StatusCode::OK)
+        MockResponse::new(StatusCode::OK)
             .set_body_raw(file.contents.clone(), "application/octet-stream")
     }
 }
@@ -609,10 +594,8 @@ impl BuildPackageStatusResponder {
     }
 }
 
-impl Respond for BuildPackageStatusResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildPackageStatusResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(1).unwrap();
         let arch = components.nth_back(0).unwrap();
@@ -644,7 +627,7 @@ impl Respond for BuildPackageStatusResponder {
         )));
 
         let package = arch.packages.get(package_name);
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(package.map_or_else(
+        MockResponse::new(StatusCode::OK).set_body_xml(package.map_or_else(
             || {
                 let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
                 package_status_xml(
@@ -704,10 +687,8 @@ fn parse_bool_param(value: Cow<str>) -> Result<bool, ApiError> {
     }
 }
 
-impl Respond for BuildLogResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildLogResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut start = 0usize;
         let mut end = None;
         // Note that these APIs have no concept of an incomplete build log at
@@ -808,7 +789,7 @@ impl Respond for BuildLogResponder {
                 })
                 .unwrap();
 
-            ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+            MockResponse::new(StatusCode::OK).set_body_xml(xml)
         } else {
             let contents = log.as_ref().map_or("", |log| &log.contents);
             ensure!(
@@ -829,7 +810,7 @@ impl Respond for BuildLogResponder {
                     .unwrap_or(end),
             );
 
-            ResponseTemplate::new(StatusCode::OK).set_body_string(&contents[start..end])
+            MockResponse::new(StatusCode::OK).set_body_string(&contents[start..end])
         }
     }
 }
@@ -844,10 +825,8 @@ impl BuildHistoryResponder {
     }
 }
 
-impl Respond for BuildHistoryResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for BuildHistoryResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(1).unwrap();
         let arch = components.nth_back(0).unwrap();
@@ -904,6 +883,6 @@ impl Respond for BuildHistoryResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
@@ -0,0 +1,195 @@
+use std::time::SystemTime;
+
+use quick_xml::events::BytesText;
+use wiremock::Request;
+
+use crate::{MockCommentTarget, ObsMock};
+
+use super::*;
+
+fn unknown_comment(id: u64) -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        "not_found".to_owned(),
+        format!("Couldn't find Comment with 'id'={id}"),
+    )
+}
+
+/// Formats a time the way OBS does for comments, e.g.
+/// `2024-04-03 10:11:12 UTC`.
+pub(crate) fn format_time(time: SystemTime) -> String {
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let (days, secs) = (secs / 86400, secs % 86400);
+
+    // Converts days since the epoch to a civil date, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days as i64 + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02} UTC",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+fn comment_target(mock: &ObsMock, request: &Request) -> Result<MockCommentTarget, ApiError> {
+    let components: Vec<_> = request.url.path_segments().unwrap().skip(1).collect();
+    let projects = mock.projects().read().unwrap();
+
+    match components[..] {
+        ["project", project_name] => {
+            if !projects.contains_key(project_name) {
+                return Err(unknown_project(project_name.to_owned()));
+            }
+            Ok(MockCommentTarget::Project(project_name.to_owned()))
+        }
+        ["package", project_name, package_name] => {
+            let project = projects
+                .get(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))?;
+            if !project.packages.contains_key(package_name) {
+                return Err(unknown_package(package_name.to_owned()));
+            }
+            Ok(MockCommentTarget::Package(
+                project_name.to_owned(),
+                package_name.to_owned(),
+            ))
+        }
+        ["request", id] => Ok(MockCommentTarget::Request(id.parse().unwrap())),
+        _ => unreachable!(),
+    }
+}
+
+pub(crate) struct CommentListingResponder {
+    mock: ObsMock,
+}
+
+impl CommentListingResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for CommentListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let target = try_api!(comment_target(&self.mock, request));
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        let mut comments_xml = xml.create_element("comments");
+        comments_xml = match &target {
+            MockCommentTarget::Project(project) => {
+                comments_xml.with_attribute(("project", project.as_str()))
+            }
+            MockCommentTarget::Package(project, package) => {
+                comments_xml.with_attributes([("project", project.as_str()), ("package", package)])
+            }
+            MockCommentTarget::Request(id) => {
+                comments_xml.with_attribute(("request", id.to_string().as_str()))
+            }
+        };
+        comments_xml
+            .write_inner_content(|writer| {
+                for comment in self.mock.comments(&target) {
+                    let mut comment_xml = writer.create_element("comment").with_attributes([
+                        ("who", comment.who.as_str()),
+                        ("when", &format_time(comment.when)),
+                        ("id", &comment.id.to_string()),
+                    ]);
+                    if let Some(parent) = comment.parent {
+                        comment_xml =
+                            comment_xml.with_attribute(("parent", parent.to_string().as_str()));
+                    }
+                    comment_xml.write_text_content(BytesText::new(&comment.body))?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) struct CommentCreationResponder {
+    mock: ObsMock,
+}
+
+impl CommentCreationResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for CommentCreationResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let target = try_api!(comment_target(&self.mock, request));
+
+        let parent = match find_query_param(request, "parent_id") {
+            Some(parent) => {
+                let parent = try_api!(parent.parse::<u64>().map_err(|_| ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "400".to_owned(),
+                    format!("invalid parent_id: {parent}")
+                )));
+                ensure!(
+                    self.mock
+                        .comments(&target)
+                        .iter()
+                        .any(|comment| comment.id == parent),
+                    unknown_comment(parent)
+                );
+                Some(parent)
+            }
+            None => None,
+        };
+
+        let body = String::from_utf8_lossy(&request.body).into_owned();
+        ensure!(
+            !body.trim().is_empty(),
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_record".to_owned(),
+                "Body can't be blank".to_owned()
+            )
+        );
+
+        self.mock
+            .add_comment(target, self.mock.auth().username().to_owned(), body, parent);
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+    }
+}
+
+pub(crate) struct CommentDeleteResponder {
+    mock: ObsMock,
+}
+
+impl CommentDeleteResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for CommentDeleteResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let id: u64 = components.nth_back(0).unwrap().parse().unwrap();
+
+        ensure!(self.mock.delete_comment(id), unknown_comment(id));
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+    }
+}
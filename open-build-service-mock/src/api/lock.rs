@@ -0,0 +1,163 @@
+use std::time::SystemTime;
+
+use serde::Deserialize;
+use wiremock::Request;
+
+use crate::{MockProject, MockRevisionOptions, MockSourceFile, ObsMock, random_md5};
+
+use super::*;
+
+#[derive(Deserialize)]
+struct LockFlag {
+    enable: Option<()>,
+}
+
+#[derive(Deserialize)]
+struct MetaLock {
+    lock: Option<LockFlag>,
+}
+
+fn meta_locked(meta: &[u8]) -> bool {
+    quick_xml::de::from_reader::<_, MetaLock>(meta)
+        .ok()
+        .and_then(|meta| meta.lock)
+        .is_some_and(|lock| lock.enable.is_some())
+}
+
+/// Replaces the `<lock>` element of package meta, which the mock keeps as
+/// given rather than parsing it.
+fn set_meta_lock(meta: &[u8], locked: bool) -> Vec<u8> {
+    let mut meta = String::from_utf8_lossy(meta).into_owned();
+    if let Some(start) = meta.find("<lock>")
+        && let Some(end) = meta[start..].find("</lock>")
+    {
+        let mut end = start + end + "</lock>".len();
+        if meta[end..].starts_with('\n') {
+            end += 1;
+        }
+        let start = meta[..start].trim_end_matches(' ').len();
+        meta.replace_range(start..end, "");
+    }
+    if locked && let Some(end) = meta.rfind("</package>") {
+        meta.insert_str(
+            end,
+            "        <lock>\n                <enable/>\n        </lock>\n",
+        );
+    }
+    meta.into_bytes()
+}
+
+fn not_locked(name: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::BAD_REQUEST,
+        "not_locked".to_owned(),
+        format!("{name} is not locked"),
+    )
+}
+
+fn locked(code: &str, name: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::FORBIDDEN,
+        code.to_owned(),
+        format!("{name} is locked"),
+    )
+}
+
+/// Refuses changes to a locked project, or to a package locked itself or
+/// through its project, as OBS does. Unknown packages are left to the caller
+/// to report.
+pub(super) fn check_unlocked(
+    project_name: &str,
+    project: &MockProject,
+    package_name: Option<&str>,
+) -> Result<(), ApiError> {
+    if project.locked {
+        return Err(locked(
+            "modify_project_no_permission",
+            &format!("project '{project_name}'"),
+        ));
+    }
+    if let Some(package_name) = package_name
+        && let Some(package) = project.packages.get(package_name)
+        && meta_locked(package.meta())
+    {
+        return Err(locked(
+            "modify_package_no_permission",
+            &format!("package '{project_name}/{package_name}'"),
+        ));
+    }
+    Ok(())
+}
+
+/// Handles `cmd=lock` and `cmd=unlock` on projects. Locking is idempotent,
+/// but unlocking requires a comment and a locked project.
+pub(crate) fn do_project_lock(
+    request: &Request,
+    project_name: &str,
+    project: &mut MockProject,
+    locked: bool,
+) -> MockResponse {
+    if !locked {
+        try_api!(find_query_param(request, "comment").ok_or_else(|| missing_parameter("comment")));
+        ensure!(
+            project.locked,
+            not_locked(&format!("project '{project_name}'"))
+        );
+    }
+    project.locked = locked;
+
+    MockResponse::new(StatusCode::OK)
+        .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+}
+
+/// Handles `cmd=lock` and `cmd=unlock` on packages, which are recorded as
+/// meta revisions.
+pub(crate) fn do_package_lock(
+    request: &Request,
+    project_name: &str,
+    package_name: &str,
+    locked: bool,
+    mock: &ObsMock,
+    projects: &mut HashMap<String, MockProject>,
+) -> MockResponse {
+    let project = try_api!(
+        projects
+            .get_mut(project_name)
+            .ok_or_else(|| unknown_project(project_name.to_owned()))
+    );
+    let package = try_api!(
+        project
+            .packages
+            .get_mut(package_name)
+            .ok_or_else(|| unknown_package(package_name.to_owned()))
+    );
+
+    let comment = find_query_param(request, "comment").map(|comment| comment.into_owned());
+    if !locked {
+        try_api!(comment.as_ref().ok_or_else(|| missing_parameter("comment")));
+        ensure!(
+            meta_locked(package.meta()),
+            not_locked(&format!("package '{project_name}/{package_name}'"))
+        );
+    }
+
+    if meta_locked(package.meta()) != locked {
+        let contents = set_meta_lock(package.meta(), locked);
+        package.push_meta(
+            MockSourceFile {
+                path: MockSourceFile::META_PATH.to_owned(),
+                contents,
+            },
+            MockRevisionOptions {
+                srcmd5: random_md5(),
+                version: None,
+                time: SystemTime::now(),
+                user: mock.auth().username().to_owned(),
+                comment,
+            },
+        );
+    }
+
+    MockResponse::new(StatusCode::OK)
+        .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+}
@@ -0,0 +1,426 @@
+use std::time::SystemTime;
+
+use quick_xml::events::BytesText;
+use serde::Deserialize;
+use wiremock::Request;
+
+use crate::{
+    MockBranchOptions, MockLinkResolution, MockPackage, MockProject, MockProjectKind, MockRequest,
+    MockRequestAction, ObsMock, random_md5,
+};
+
+use super::*;
+
+/// Responds to `/source?cmd=branch`, which branches a package from all the
+/// projects maintained by any maintenance project.
+pub(crate) struct MaintenanceBranchResponder {
+    mock: ObsMock,
+}
+
+impl MaintenanceBranchResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for MaintenanceBranchResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let cmd = try_api!(
+            find_query_param(request, "cmd").ok_or_else(|| ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "missing_parameter".to_string(),
+                "POST request without given cmd parameter".to_string()
+            ))
+        );
+        ensure!(
+            cmd == "branch",
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                "illegal_request".to_string(),
+                "invalid_command".to_string(),
+            )
+        );
+
+        let package_name = try_api!(
+            find_query_param(request, "package").ok_or_else(|| missing_parameter("package"))
+        );
+        let target_project_name = find_query_param(request, "target_project")
+            .map(|name| name.into_owned())
+            .unwrap_or_else(|| {
+                format!(
+                    "home:{}:branches:OBS_Maintained:{package_name}",
+                    self.mock.auth().username()
+                )
+            });
+        let target_package_base =
+            find_query_param(request, "target_package").unwrap_or(package_name.clone());
+        let comment = find_query_param(request, "comment");
+        let force = find_query_param(request, "force").is_some();
+        let dryrun = find_query_param(request, "dryrun").is_some();
+        let rebuild = try_api!(parse_project_meta_enum_param(
+            request,
+            "add_repositories_rebuild"
+        ))
+        .unwrap_or_default();
+        let block = try_api!(parse_project_meta_enum_param(
+            request,
+            "add_repositories_block"
+        ))
+        .unwrap_or_default();
+
+        let mut projects = self.mock.projects().write().unwrap();
+
+        let mut maintained: Vec<_> = projects
+            .values()
+            .filter(|project| project.kind == MockProjectKind::Maintenance)
+            .flat_map(|project| project.maintains.iter().cloned())
+            .collect();
+        maintained.sort();
+        maintained.dedup();
+
+        let branches: Vec<_> = maintained
+            .into_iter()
+            .filter(|project_name| {
+                projects
+                    .get(project_name)
+                    .is_some_and(|project| project.packages.contains_key(package_name.as_ref()))
+            })
+            .map(|project_name| {
+                let target_package_name =
+                    format!("{target_package_base}.{}", project_name.replace(':', "_"));
+                (project_name, target_package_name)
+            })
+            .collect();
+        ensure!(
+            !branches.is_empty(),
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                "not_found".to_owned(),
+                "no packages found by search criteria".to_owned(),
+            )
+        );
+
+        if dryrun {
+            let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+            xml.create_element("collection")
+                .write_inner_content(|writer| {
+                    for (project_name, target_package_name) in &branches {
+                        writer
+                            .create_element("package")
+                            .with_attributes([
+                                ("project", project_name.as_str()),
+                                ("package", package_name.as_ref()),
+                            ])
+                            .write_inner_content(|writer| {
+                                writer
+                                    .create_element("target")
+                                    .with_attributes([
+                                        ("project", target_project_name.as_str()),
+                                        ("package", target_package_name.as_str()),
+                                    ])
+                                    .write_empty()?;
+                                Ok(())
+                            })?;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            return MockResponse::new(StatusCode::OK).set_body_xml(xml);
+        }
+
+        if let Some(target_project) = projects.get(&target_project_name) {
+            for (_, target_package_name) in &branches {
+                ensure!(
+                    force || !target_project.packages.contains_key(target_package_name),
+                    ApiError::new(
+                        StatusCode::BAD_REQUEST,
+                        "double_branch_package".to_owned(),
+                        format!(
+                            "branch target package already exists: {target_project_name}/{target_package_name}"
+                        )
+                    )
+                );
+            }
+        }
+
+        let packages: Vec<_> = branches
+            .into_iter()
+            .map(|(project_name, target_package_name)| {
+                let origin = projects[&project_name].packages.get(package_name.as_ref());
+                let package = MockPackage::new_branched(
+                    project_name.clone(),
+                    package_name.clone().into_owned(),
+                    origin,
+                    &target_project_name,
+                    &target_package_name,
+                    MockBranchOptions {
+                        srcmd5: random_md5(),
+                        link_resolution: MockLinkResolution::Available {
+                            xsrcmd5: random_md5(),
+                        },
+                        user: self.mock.auth().username().to_owned(),
+                        time: SystemTime::now(),
+                        comment: comment.clone().map(|comment| comment.into_owned()),
+                        missingok: false,
+                    },
+                );
+                (target_package_name, package)
+            })
+            .collect();
+
+        projects
+            .entry(target_project_name.clone())
+            .or_insert_with(|| MockProject {
+                rebuild,
+                block,
+                ..Default::default()
+            })
+            .packages
+            .extend(packages);
+
+        let xml = build_status_xml("ok", Some("Ok".to_owned()), |writer| {
+            branch_data_xml(writer, "targetproject", &target_project_name).unwrap();
+            Ok(())
+        })
+        .unwrap();
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+/// Responds to `/search/project`, for searches by kind and name prefix like
+/// `@kind='maintenance_incident' and starts-with(@name,'Maintenance:')`.
+pub(crate) struct SearchProjectResponder {
+    mock: ObsMock,
+}
+
+impl SearchProjectResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for SearchProjectResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let xpath = try_api!(
+            find_query_param(request, "match").ok_or_else(|| ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "missing_parameter".to_owned(),
+                "Missing parameter match".to_owned(),
+            ))
+        );
+
+        let mut kind = None;
+        let mut prefix = None;
+        for condition in xpath.split(" and ") {
+            if let Some(value) = condition.strip_prefix("@kind=").and_then(xpath_literal) {
+                kind = Some(try_api!(
+                    value
+                        .parse::<MockProjectKind>()
+                        .map_err(|_| illegal_xpath(&xpath))
+                ));
+            } else if let Some(value) = condition
+                .strip_prefix("starts-with(@name,")
+                .and_then(|value| value.strip_suffix(')'))
+                .and_then(xpath_literal)
+            {
+                prefix = Some(value);
+            } else {
+                return illegal_xpath(&xpath).into_response();
+            }
+        }
+
+        let projects = self.mock.projects().read().unwrap();
+        let mut project_names: Vec<_> = projects
+            .iter()
+            .filter(|(name, project)| {
+                kind.is_none_or(|kind| project.kind == kind)
+                    && prefix.is_none_or(|prefix| name.starts_with(prefix))
+            })
+            .map(|(name, _)| name)
+            .collect();
+        project_names.sort();
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("collection")
+            .with_attribute(("matches", project_names.len().to_string().as_str()))
+            .write_inner_content(|writer| {
+                for project_name in project_names {
+                    write_project_meta(writer, project_name, &projects[project_name])?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+#[derive(Deserialize)]
+struct RequestEndpoint {
+    #[serde(rename = "@project")]
+    project: String,
+    #[serde(rename = "@package")]
+    package: Option<String>,
+}
+
+impl RequestEndpoint {
+    fn into_pair(self) -> (String, Option<String>) {
+        (self.project, self.package)
+    }
+}
+
+#[derive(Deserialize)]
+struct RequestAction {
+    #[serde(rename = "@type")]
+    action_type: String,
+    source: Option<RequestEndpoint>,
+    target: Option<RequestEndpoint>,
+}
+
+#[derive(Deserialize)]
+struct NewRequest {
+    #[serde(default, rename = "action")]
+    actions: Vec<RequestAction>,
+    #[serde(default)]
+    description: String,
+}
+
+/// Formats a time the way OBS does for requests, e.g. `2024-04-03T10:11:12`.
+fn format_request_time(time: SystemTime) -> String {
+    format_time(time)
+        .trim_end_matches(" UTC")
+        .replacen(' ', "T", 1)
+}
+
+fn write_request(writer: &mut XMLWriter, request: &MockRequest) -> std::io::Result<()> {
+    writer
+        .create_element("request")
+        .with_attributes([
+            ("id", request.id.to_string().as_str()),
+            ("creator", request.creator.as_str()),
+        ])
+        .write_inner_content(|writer| {
+            for action in &request.actions {
+                writer
+                    .create_element("action")
+                    .with_attribute(("type", action.action_type.as_str()))
+                    .write_inner_content(|writer| {
+                        for (element, endpoint) in
+                            [("source", &action.source), ("target", &action.target)]
+                        {
+                            let Some((project, package)) = endpoint else {
+                                continue;
+                            };
+                            let mut endpoint_xml = writer
+                                .create_element(element)
+                                .with_attribute(("project", project.as_str()));
+                            if let Some(package) = package {
+                                endpoint_xml =
+                                    endpoint_xml.with_attribute(("package", package.as_str()));
+                            }
+                            endpoint_xml.write_empty()?;
+                        }
+                        Ok(())
+                    })?;
+            }
+            let when = format_request_time(request.created);
+            writer
+                .create_element("state")
+                .with_attributes([
+                    ("name", "new"),
+                    ("who", request.creator.as_str()),
+                    ("when", when.as_str()),
+                    ("created", when.as_str()),
+                ])
+                .write_empty()?;
+            writer
+                .create_element("description")
+                .write_text_content(BytesText::new(&request.description))?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+/// Responds to `/request?cmd=create`, supporting only `maintenance_release`
+/// requests.
+pub(crate) struct RequestCreationResponder {
+    mock: ObsMock,
+}
+
+impl RequestCreationResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for RequestCreationResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let cmd =
+            try_api!(find_query_param(request, "cmd").ok_or_else(|| missing_parameter("cmd")));
+        ensure!(
+            cmd == "create",
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "illegal_request".to_owned(),
+                format!("Unsupported cmd '{cmd}'"),
+            )
+        );
+
+        let new_request: NewRequest = try_api!(parse_xml_request(request));
+        ensure!(
+            !new_request.actions.is_empty(),
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_record".to_owned(),
+                "Request must have at least one action".to_owned(),
+            )
+        );
+
+        let projects = self.mock.projects().read().unwrap();
+        let mut actions = Vec::new();
+        for action in new_request.actions {
+            ensure!(
+                action.action_type == "maintenance_release",
+                ApiError::new(
+                    StatusCode::MISDIRECTED_REQUEST,
+                    "unsupported".to_owned(),
+                    "Operation not supported by the OBS mock server".to_owned(),
+                )
+            );
+            let source = try_api!(action.source.ok_or_else(|| ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_record".to_owned(),
+                "maintenance_release requests need a source".to_owned(),
+            )));
+            let project = try_api!(
+                projects
+                    .get(&source.project)
+                    .ok_or_else(|| unknown_project(source.project.clone()))
+            );
+            ensure!(
+                project.kind == MockProjectKind::MaintenanceIncident,
+                ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "no_maintenance_release_target".to_owned(),
+                    format!("{} is not a maintenance incident project", source.project),
+                )
+            );
+            actions.push(MockRequestAction {
+                action_type: action.action_type,
+                source: Some(source.into_pair()),
+                target: action.target.map(RequestEndpoint::into_pair),
+            });
+        }
+        drop(projects);
+
+        let created = self.mock.add_request(
+            self.mock.auth().username().to_owned(),
+            new_request.description,
+            actions,
+        );
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        write_request(&mut xml, &created).unwrap();
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
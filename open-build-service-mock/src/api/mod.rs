@@ -1,14 +1,47 @@
-use std::{borrow::Cow, fmt::Display, time::SystemTime};
-
-use http::{StatusCode, header::AUTHORIZATION};
-use wiremock::{Request, ResponseTemplate};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use http::{
+    HeaderName, HeaderValue, StatusCode,
+    header::{AUTHORIZATION, CONTENT_TYPE, COOKIE, RETRY_AFTER, SET_COOKIE, WWW_AUTHENTICATE},
+};
+use wiremock::{
+    Match, Request, Respond, ResponseTemplate,
+    http::Method,
+    matchers::{PathRegexMatcher, path_regex},
+};
+
+use crate::{MockFault, ObsMock, random_md5};
 
 mod build;
 pub(crate) use build::*;
 
+mod comment;
+pub(crate) use comment::*;
+
+mod lock;
+pub(crate) use lock::*;
+
+mod maintenance;
+pub(crate) use maintenance::*;
+
+mod patchinfo;
+pub(crate) use patchinfo::*;
+
+mod person;
+pub(crate) use person::*;
+
 mod source;
 pub(crate) use source::*;
 
+mod token;
+pub(crate) use token::*;
+
 pub type XMLWriter = quick_xml::Writer<std::io::Cursor<Vec<u8>>>;
 
 // BasicAuth Adapted from http-rs/http-types crate
@@ -78,14 +111,110 @@ fn build_status_xml(
     Ok(status_xml)
 }
 
-trait ResponseTemplateUtils {
-    fn set_body_xml(self, xml: XMLWriter) -> Self;
+/// A response of one of the responders, which can be served both by the
+/// wiremock server and in-process by a [`MockTransport`](crate::MockTransport).
+#[derive(Clone, Debug)]
+pub(crate) struct MockResponse {
+    pub status: StatusCode,
+    pub headers: Vec<(HeaderName, HeaderValue)>,
+    pub body: Vec<u8>,
+    pub delay: Option<Duration>,
 }
 
-impl ResponseTemplateUtils for ResponseTemplate {
-    fn set_body_xml(self, xml: XMLWriter) -> Self {
+impl MockResponse {
+    pub fn new<S>(status: S) -> Self
+    where
+        S: TryInto<StatusCode>,
+        S::Error: Debug,
+    {
+        Self {
+            status: status.try_into().expect("invalid status code"),
+            headers: Vec::new(),
+            body: Vec::new(),
+            delay: None,
+        }
+    }
+
+    pub fn append_header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: Debug,
+    {
+        self.headers.push((
+            key.try_into().expect("invalid header name"),
+            value.try_into().expect("invalid header value"),
+        ));
+        self
+    }
+
+    pub fn set_body_raw(mut self, body: impl Into<Vec<u8>>, mime: &str) -> Self {
+        self.headers.retain(|(name, _)| name != CONTENT_TYPE);
+        self.body = body.into();
+        self.append_header(CONTENT_TYPE, mime)
+    }
+
+    pub fn set_body_string(self, body: impl Into<String>) -> Self {
+        self.set_body_raw(body.into(), "text/plain")
+    }
+
+    pub fn set_body_xml(self, xml: XMLWriter) -> Self {
         self.set_body_raw(xml.into_inner().into_inner(), "application/xml")
     }
+
+    pub fn set_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    fn into_template(self) -> ResponseTemplate {
+        let mut template = ResponseTemplate::new(self.status).set_body_bytes(self.body);
+        for (name, value) in self.headers {
+            template = template.append_header(name, value);
+        }
+        match self.delay {
+            Some(delay) => template.set_delay(delay),
+            None => template,
+        }
+    }
+}
+
+pub(crate) trait MockRespond: Send + Sync {
+    fn respond(&self, request: &Request) -> MockResponse;
+}
+
+pub(crate) struct Route {
+    pub method: Method,
+    pub path: &'static str,
+    matcher: PathRegexMatcher,
+    pub responder: Arc<dyn MockRespond>,
+}
+
+impl Route {
+    pub fn new(method: Method, path: &'static str, responder: impl MockRespond + 'static) -> Self {
+        Self {
+            method,
+            path,
+            matcher: path_regex(path),
+            responder: Arc::new(responder),
+        }
+    }
+
+    /// Whether the route matches the request, the same way the wiremock
+    /// server would match it.
+    pub fn matches(&self, request: &Request) -> bool {
+        request.method == self.method && self.matcher.matches(request)
+    }
+}
+
+/// Serves a responder using the wiremock server.
+pub(crate) struct WiremockResponder(pub Arc<dyn MockRespond>);
+
+impl Respond for WiremockResponder {
+    fn respond(&self, request: &Request) -> ResponseTemplate {
+        self.0.respond(request).into_template()
+    }
 }
 
 #[derive(Debug)]
@@ -93,6 +222,8 @@ struct ApiError {
     http_status: StatusCode,
     code: String,
     summary: String,
+    details: Option<String>,
+    data: Vec<(String, String)>,
 }
 
 impl ApiError {
@@ -101,15 +232,38 @@ impl ApiError {
             http_status,
             code,
             summary,
+            details: None,
+            data: Vec::new(),
         }
     }
 
+    fn with_data(mut self, name: &str, value: String) -> ApiError {
+        self.data.push((name.to_owned(), value));
+        self
+    }
+
     fn into_xml(self) -> XMLWriter {
-        build_status_xml(&self.code, Some(self.summary), |_| Ok(())).unwrap()
+        use quick_xml::events::BytesText;
+
+        build_status_xml(&self.code, Some(self.summary), |writer| {
+            if let Some(details) = &self.details {
+                writer
+                    .create_element("details")
+                    .write_text_content(BytesText::new(details))?;
+            }
+            for (name, value) in &self.data {
+                writer
+                    .create_element("data")
+                    .with_attribute(("name", name.as_str()))
+                    .write_text_content(BytesText::new(value))?;
+            }
+            Ok(())
+        })
+        .unwrap()
     }
 
-    fn into_response(self) -> ResponseTemplate {
-        ResponseTemplate::new(self.http_status).set_body_xml(self.into_xml())
+    fn into_response(self) -> MockResponse {
+        MockResponse::new(self.http_status).set_body_xml(self.into_xml())
     }
 }
 
@@ -120,43 +274,218 @@ impl Display for ApiError {
 }
 
 fn unknown_project(project: String) -> ApiError {
-    ApiError {
-        http_status: StatusCode::NOT_FOUND,
-        code: "unknown_project".to_owned(),
-        summary: project,
-    }
+    ApiError::new(StatusCode::NOT_FOUND, "unknown_project".to_owned(), project)
 }
 
 fn unknown_package(package: String) -> ApiError {
     ApiError::new(StatusCode::NOT_FOUND, "unknown_package".to_owned(), package)
 }
 
-fn check_auth(auth: &BasicAuth, request: &Request) -> Result<(), ApiError> {
+pub(crate) const SESSION_COOKIE: &str = "_obs_api_session";
+
+const SIGNATURE_REALM: &str = "Use your developer account";
+
+fn authentication_required(summary: String) -> ApiError {
+    ApiError::new(
+        StatusCode::UNAUTHORIZED,
+        "authentication_required".to_owned(),
+        summary,
+    )
+}
+
+fn find_session_cookie(request: &Request) -> Option<&str> {
+    request
+        .headers
+        .get_all(COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find_map(|(name, value)| (name == SESSION_COOKIE).then_some(value))
+}
+
+// Checks the parameters of osc's ssh signature scheme. Note that the signature
+// itself is *not* verified, only its presence and that it claims to be from
+// the expected user.
+fn check_signature(auth: &BasicAuth, params: &str) -> Result<(), ApiError> {
+    let params: HashMap<&str, &str> = params
+        .split(',')
+        .filter_map(|param| param.trim().split_once('='))
+        .map(|(key, value)| (key, value.trim_matches('"')))
+        .collect();
+
+    let key_id = params
+        .get("keyId")
+        .ok_or_else(|| authentication_required("keyId missing in signature".to_owned()))?;
+    ensure_auth(
+        *key_id == auth.username(),
+        format!("Unknown user '{key_id}'"),
+    )?;
+    ensure_auth(
+        params.get("algorithm") == Some(&"ssh"),
+        "unsupported signature algorithm".to_owned(),
+    )?;
+    ensure_auth(
+        params.get("headers") == Some(&"(created)"),
+        "unsupported signature headers".to_owned(),
+    )?;
+    ensure_auth(
+        params
+            .get("created")
+            .is_some_and(|created| created.parse::<u64>().is_ok()),
+        "created missing in signature".to_owned(),
+    )?;
+
+    use base64ct::{Base64, Encoding};
+    ensure_auth(
+        params
+            .get("signature")
+            .is_some_and(|signature| Base64::decode_vec(signature).is_ok_and(|s| !s.is_empty())),
+        "invalid signature".to_owned(),
+    )
+}
+
+fn ensure_auth(test: bool, summary: String) -> Result<(), ApiError> {
+    if test {
+        Ok(())
+    } else {
+        Err(authentication_required(summary))
+    }
+}
+
+/// Returns the new session to hand out, if the request was authenticated
+/// using credentials instead of an existing session.
+fn check_auth(mock: &ObsMock, request: &Request) -> Result<Option<String>, ApiError> {
+    if let Some(session) = find_session_cookie(request)
+        && mock.inner.sessions.read().unwrap().contains(session)
+    {
+        return Ok(None);
+    }
+
     let given_auth = request
         .headers
         .get(AUTHORIZATION)
         .and_then(|auth| auth.to_str().ok())
-        .and_then(|s| s.strip_prefix("Basic "))
-        .and_then(|creds| BasicAuth::from_credentials(creds.trim().as_bytes()).ok())
-        .ok_or_else(|| {
-            ApiError::new(
-                StatusCode::UNAUTHORIZED,
-                "authentication_required".to_owned(),
-                "Authentication required".to_owned(),
-            )
-        })?;
-
-    if auth.username() == given_auth.username() || auth.password() == given_auth.password() {
-        Ok(())
+        .ok_or_else(|| authentication_required("Authentication required".to_owned()))?;
+
+    let auth = mock.auth();
+    if let Some(token) = given_auth.strip_prefix("Token ") {
+        // OBS only accepts tokens for a few specific endpoints, which isn't
+        // modelled here. Tokens don't create a session either.
+        ensure_auth(
+            mock.inner
+                .tokens
+                .read()
+                .unwrap()
+                .iter()
+                .any(|t| t.string == token.trim()),
+            "No valid token found".to_owned(),
+        )?;
+        return Ok(None);
+    } else if let Some(params) = given_auth.strip_prefix("Signature ") {
+        check_signature(auth, params)?;
     } else {
-        Err(ApiError::new(
-            StatusCode::UNAUTHORIZED,
-            "authentication_required".to_owned(),
+        let given_auth = given_auth
+            .strip_prefix("Basic ")
+            .and_then(|creds| BasicAuth::from_credentials(creds.trim().as_bytes()).ok())
+            .ok_or_else(|| authentication_required("Authentication required".to_owned()))?;
+
+        ensure_auth(
+            auth.username() == given_auth.username() && auth.password() == given_auth.password(),
             format!(
                 "Unknown user '{}' or invalid password",
                 given_auth.username()
             ),
-        ))
+        )?;
+    }
+
+    let session = random_md5();
+    mock.inner.sessions.write().unwrap().insert(session.clone());
+    Ok(Some(session))
+}
+
+pub(crate) struct AuthenticatedResponder<R> {
+    mock: ObsMock,
+    responder: R,
+}
+
+impl<R> AuthenticatedResponder<R> {
+    pub fn new(mock: ObsMock, responder: R) -> Self {
+        Self { mock, responder }
+    }
+}
+
+impl<R: MockRespond> MockRespond for AuthenticatedResponder<R> {
+    fn respond(&self, request: &Request) -> MockResponse {
+        match check_auth(&self.mock, request) {
+            Ok(session) => {
+                let response = self.responder.respond(request);
+                match session {
+                    Some(session) => response.append_header(
+                        SET_COOKIE,
+                        format!("{SESSION_COOKIE}={session}; path=/; HttpOnly"),
+                    ),
+                    None => response,
+                }
+            }
+            Err(err) => err
+                .into_response()
+                .append_header(
+                    WWW_AUTHENTICATE,
+                    r#"Basic realm="Use your developer account""#,
+                )
+                .append_header(
+                    WWW_AUTHENTICATE,
+                    format!(r#"Signature realm="{SIGNATURE_REALM}",headers="(created)""#),
+                ),
+        }
+    }
+}
+
+pub(crate) struct FaultInjectingResponder<R> {
+    mock: ObsMock,
+    responder: R,
+}
+
+impl<R> FaultInjectingResponder<R> {
+    pub fn new(mock: ObsMock, responder: R) -> Self {
+        Self { mock, responder }
+    }
+}
+
+impl<R: MockRespond> MockRespond for FaultInjectingResponder<R> {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let fault = self.mock.inner.faults.lock().unwrap().pop_front();
+        match fault {
+            Some(MockFault::Status { code, retry_after }) => {
+                let status = StatusCode::from_u16(code).expect("invalid fault status code");
+                let response = MockResponse::new(status).set_body_string(format!(
+                    "{} {}",
+                    status.as_str(),
+                    status.canonical_reason().unwrap_or_default()
+                ));
+                match retry_after {
+                    Some(seconds) => response.append_header(RETRY_AFTER, seconds.to_string()),
+                    None => response,
+                }
+            }
+            Some(MockFault::ApiError {
+                code,
+                error_code,
+                summary,
+                details,
+            }) => {
+                let status = StatusCode::from_u16(code).expect("invalid fault status code");
+                let mut error = ApiError::new(status, error_code, summary);
+                error.details = details;
+                error.into_response()
+            }
+            Some(MockFault::Delay(delay)) => self.responder.respond(request).set_delay(delay),
+            Some(MockFault::Body(body)) => {
+                MockResponse::new(StatusCode::OK).set_body_raw(body, "application/xml")
+            }
+            None => self.responder.respond(request),
+        }
     }
 }
 
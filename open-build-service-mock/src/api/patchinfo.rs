@@ -0,0 +1,163 @@
+use std::time::SystemTime;
+
+use quick_xml::events::BytesText;
+use wiremock::Request;
+
+use crate::{
+    MockPackage, MockPackageOptions, MockProject, MockRevisionOptions, MockSourceFile, ObsMock,
+    random_md5,
+};
+
+use super::*;
+
+/// The template OBS creates patchinfos from.
+fn new_patchinfo(packager: &str, summary: &str) -> Vec<u8> {
+    let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 2);
+    xml.create_element("patchinfo")
+        .write_inner_content(|writer| {
+            writer
+                .create_element("packager")
+                .write_text_content(BytesText::new(packager))?;
+            writer
+                .create_element("category")
+                .write_text_content(BytesText::new("recommended"))?;
+            writer
+                .create_element("rating")
+                .write_text_content(BytesText::new("low"))?;
+            writer
+                .create_element("summary")
+                .write_text_content(BytesText::new(summary))?;
+            writer.create_element("description").write_empty()?;
+            Ok(())
+        })
+        .unwrap();
+    xml.into_inner().into_inner()
+}
+
+fn revision_options(mock: &ObsMock, comment: Option<String>) -> MockRevisionOptions {
+    MockRevisionOptions {
+        srcmd5: random_md5(),
+        version: None,
+        time: SystemTime::now(),
+        user: mock.auth().username().to_owned(),
+        comment,
+    }
+}
+
+pub(crate) fn do_createpatchinfo(
+    request: &Request,
+    project_name: &str,
+    project: &mut MockProject,
+    mock: &ObsMock,
+) -> MockResponse {
+    let package_name = find_query_param(request, "name")
+        .map_or_else(|| "patchinfo".to_owned(), |name| name.into_owned());
+    let comment = find_query_param(request, "comment").map(|comment| comment.into_owned());
+    let force = find_query_param(request, "force").is_some();
+
+    ensure!(
+        force || !project.packages.contains_key(&package_name),
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "patchinfo_file_exists".to_owned(),
+            format!(
+                "createpatchinfo command: the patchinfo {package_name} exists already. Either use force=1 re-create the _patchinfo or use updatepatchinfo for updating."
+            ),
+        )
+    );
+
+    let package = project
+        .packages
+        .entry(package_name.clone())
+        .or_insert_with(|| {
+            MockPackage::new_with_metadata(
+                project_name,
+                &package_name,
+                MockPackageOptions {
+                    user: mock.auth().username().to_owned(),
+                    ..Default::default()
+                },
+            )
+        });
+    package.commit_file(
+        MockSourceFile {
+            path: MockSourceFile::PATCHINFO_PATH.to_owned(),
+            contents: new_patchinfo(
+                mock.auth().username(),
+                comment.as_deref().unwrap_or_default(),
+            ),
+        },
+        revision_options(mock, comment),
+    );
+
+    let xml = build_status_xml("ok", Some("Ok".to_owned()), |writer| {
+        branch_data_xml(writer, "targetproject", project_name).unwrap();
+        branch_data_xml(writer, "targetpackage", &package_name).unwrap();
+        Ok(())
+    })
+    .unwrap();
+    MockResponse::new(StatusCode::OK).set_body_xml(xml)
+}
+
+/// Adds the other packages of the project to the patchinfo. Unlike OBS, this
+/// doesn't collect issues from the changes of the packages.
+pub(crate) fn do_updatepatchinfo(
+    project_name: &str,
+    package_name: &str,
+    mock: &ObsMock,
+    projects: &mut HashMap<String, MockProject>,
+) -> MockResponse {
+    let project = try_api!(
+        projects
+            .get_mut(project_name)
+            .ok_or_else(|| unknown_project(project_name.to_owned()))
+    );
+
+    let mut packages: Vec<_> = project
+        .packages
+        .iter()
+        .filter(|(_, package)| package.file(MockSourceFile::PATCHINFO_PATH).is_none())
+        .map(|(name, _)| name.clone())
+        .collect();
+    packages.sort();
+
+    let package = try_api!(
+        project
+            .packages
+            .get_mut(package_name)
+            .ok_or_else(|| unknown_package(package_name.to_owned()))
+    );
+    let not_patchinfo = || {
+        ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "invalid_package".to_owned(),
+            format!("{project_name}/{package_name} is not a patchinfo package"),
+        )
+    };
+    let mut patchinfo = try_api!(
+        package
+            .file(MockSourceFile::PATCHINFO_PATH)
+            .and_then(|contents| String::from_utf8(contents.to_vec()).ok())
+            .ok_or_else(not_patchinfo)
+    );
+    let end = try_api!(patchinfo.rfind("</patchinfo>").ok_or_else(not_patchinfo));
+
+    let added: String = packages
+        .iter()
+        .filter(|name| !patchinfo.contains(&format!("<package>{name}</package>")))
+        .map(|name| format!("  <package>{name}</package>\n"))
+        .collect();
+    if !added.is_empty() {
+        patchinfo.insert_str(end, &added);
+        package.commit_file(
+            MockSourceFile {
+                path: MockSourceFile::PATCHINFO_PATH.to_owned(),
+                contents: patchinfo.into_bytes(),
+            },
+            revision_options(mock, Some("updated via updatepatchinfo call".to_owned())),
+        );
+    }
+
+    MockResponse::new(StatusCode::OK)
+        .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+}
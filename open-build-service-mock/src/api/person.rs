@@ -0,0 +1,293 @@
+use quick_xml::events::BytesText;
+use serde::Deserialize;
+use wiremock::Request;
+
+use crate::{MockRoleHolder, ObsMock};
+
+use super::*;
+
+#[derive(Deserialize)]
+struct MetaPerson {
+    #[serde(rename = "@userid")]
+    userid: String,
+    #[serde(rename = "@role")]
+    role: String,
+}
+
+#[derive(Deserialize)]
+struct MetaGroup {
+    #[serde(rename = "@groupid")]
+    groupid: String,
+    #[serde(rename = "@role")]
+    role: String,
+}
+
+/// The roles given in project or package meta.
+#[derive(Deserialize)]
+pub(crate) struct MetaRoles {
+    #[serde(default, rename = "person")]
+    persons: Vec<MetaPerson>,
+    #[serde(default, rename = "group")]
+    groups: Vec<MetaGroup>,
+}
+
+impl MetaRoles {
+    pub(crate) fn into_roles(self) -> Vec<(MockRoleHolder, String)> {
+        let persons = self
+            .persons
+            .into_iter()
+            .map(|p| (MockRoleHolder::User(p.userid), p.role));
+        let groups = self
+            .groups
+            .into_iter()
+            .map(|g| (MockRoleHolder::Group(g.groupid), g.role));
+        persons.chain(groups).collect()
+    }
+}
+
+pub(crate) fn write_roles(
+    writer: &mut XMLWriter,
+    roles: &[(MockRoleHolder, String)],
+) -> std::io::Result<()> {
+    for (holder, role) in roles {
+        let (element, attribute, id) = match holder {
+            MockRoleHolder::User(user) => ("person", "userid", user),
+            MockRoleHolder::Group(group) => ("group", "groupid", group),
+        };
+        writer
+            .create_element(element)
+            .with_attributes([(attribute, id.as_str()), ("role", role.as_str())])
+            .write_empty()?;
+    }
+    Ok(())
+}
+
+pub(super) fn illegal_xpath(xpath: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::BAD_REQUEST,
+        "illegal_xpath_error".to_owned(),
+        format!("unsupported xpath: {xpath}"),
+    )
+}
+
+/// The value of an XPath string literal, quoted by either kind of quotes.
+pub(super) fn xpath_literal(literal: &str) -> Option<&str> {
+    literal
+        .strip_prefix('\'')
+        .and_then(|literal| literal.strip_suffix('\''))
+        .or_else(|| literal.strip_prefix('"')?.strip_suffix('"'))
+}
+
+/// Parses the only kinds of predicates the mock supports, i.e.
+/// `person[@userid='user']` or `group[@groupid='group' and @role='role']`.
+fn parse_role_xpath(xpath: &str) -> Result<(MockRoleHolder, Option<String>), ApiError> {
+    let (element, predicate) = xpath
+        .strip_suffix(']')
+        .and_then(|xpath| xpath.split_once('['))
+        .ok_or_else(|| illegal_xpath(xpath))?;
+
+    let mut id = None;
+    let mut role = None;
+    for condition in predicate.split(" and ") {
+        let (attribute, value) = condition
+            .split_once('=')
+            .and_then(|(attribute, value)| {
+                Some((attribute.strip_prefix('@')?, xpath_literal(value)?))
+            })
+            .ok_or_else(|| illegal_xpath(xpath))?;
+        match (element, attribute) {
+            ("person", "userid") | ("group", "groupid") => id = Some(value.to_owned()),
+            (_, "role") => role = Some(value.to_owned()),
+            _ => return Err(illegal_xpath(xpath)),
+        }
+    }
+
+    let id = id.ok_or_else(|| illegal_xpath(xpath))?;
+    let holder = match element {
+        "person" => MockRoleHolder::User(id),
+        _ => MockRoleHolder::Group(id),
+    };
+    Ok((holder, role))
+}
+
+pub(crate) struct PersonResponder {
+    mock: ObsMock,
+}
+
+impl PersonResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for PersonResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let login = request.url.path_segments().unwrap().next_back().unwrap();
+
+        let persons = self.mock.persons().read().unwrap();
+        let person = try_api!(persons.get(login).ok_or_else(|| ApiError::new(
+            StatusCode::NOT_FOUND,
+            "not_found".to_owned(),
+            format!("Couldn't find User with login = {login}"),
+        )));
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("person")
+            .write_inner_content(|writer| {
+                writer
+                    .create_element("login")
+                    .write_text_content(BytesText::new(login))?;
+                writer
+                    .create_element("email")
+                    .write_text_content(BytesText::new(&person.email))?;
+                writer
+                    .create_element("realname")
+                    .write_text_content(BytesText::new(&person.realname))?;
+                writer
+                    .create_element("state")
+                    .write_text_content(BytesText::new("confirmed"))?;
+                for globalrole in &person.globalroles {
+                    writer
+                        .create_element("globalrole")
+                        .write_text_content(BytesText::new(globalrole))?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) struct GroupResponder {
+    mock: ObsMock,
+}
+
+impl GroupResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for GroupResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let title = request.url.path_segments().unwrap().next_back().unwrap();
+
+        let groups = self.mock.groups().read().unwrap();
+        let group = try_api!(groups.get(title).ok_or_else(|| ApiError::new(
+            StatusCode::NOT_FOUND,
+            "not_found".to_owned(),
+            format!("Couldn't find Group '{title}'"),
+        )));
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("group")
+            .write_inner_content(|writer| {
+                writer
+                    .create_element("title")
+                    .write_text_content(BytesText::new(title))?;
+                if let Some(email) = &group.email {
+                    writer
+                        .create_element("email")
+                        .write_text_content(BytesText::new(email))?;
+                }
+                for maintainer in &group.maintainers {
+                    writer
+                        .create_element("maintainer")
+                        .with_attribute(("userid", maintainer.as_str()))
+                        .write_empty()?;
+                }
+                if !group.members.is_empty() {
+                    writer
+                        .create_element("person")
+                        .write_inner_content(|writer| {
+                            for member in &group.members {
+                                writer
+                                    .create_element("person")
+                                    .with_attribute(("userid", member.as_str()))
+                                    .write_empty()?;
+                            }
+                            Ok(())
+                        })?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+/// Responds to `/search/project/id` and `/search/package/id` for searches
+/// by role.
+pub(crate) struct SearchIdResponder {
+    mock: ObsMock,
+}
+
+impl SearchIdResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for SearchIdResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let kind = request.url.path_segments().unwrap().nth(1).unwrap();
+        let xpath = try_api!(
+            find_query_param(request, "match").ok_or_else(|| ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "missing_parameter".to_owned(),
+                "Missing parameter match".to_owned(),
+            ))
+        );
+        let (holder, role) = try_api!(parse_role_xpath(&xpath));
+        let matches = |roles: &[(MockRoleHolder, String)]| {
+            roles
+                .iter()
+                .any(|(h, r)| *h == holder && role.as_ref().is_none_or(|role| role == r))
+        };
+
+        let projects = self.mock.projects().read().unwrap();
+        let mut project_names: Vec<_> = projects.keys().collect();
+        project_names.sort();
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("collection")
+            .write_inner_content(|writer| {
+                for project_name in project_names {
+                    let project = &projects[project_name];
+                    if kind == "project" {
+                        if matches(&project.roles) {
+                            writer
+                                .create_element("project")
+                                .with_attribute(("name", project_name.as_str()))
+                                .write_empty()?;
+                        }
+                        continue;
+                    }
+
+                    let mut package_names: Vec<_> = project.packages.keys().collect();
+                    package_names.sort();
+                    for package_name in package_names {
+                        let meta = project.packages[package_name].meta();
+                        let Ok(roles) = quick_xml::de::from_reader::<_, MetaRoles>(meta) else {
+                            continue;
+                        };
+                        if matches(&roles.into_roles()) {
+                            writer
+                                .create_element("package")
+                                .with_attributes([
+                                    ("project", project_name.as_str()),
+                                    ("name", package_name.as_str()),
+                                ])
+                                .write_empty()?;
+                        }
+                    }
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
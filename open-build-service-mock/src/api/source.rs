@@ -6,12 +6,12 @@ use std::time::SystemTime;
 use http::StatusCode;
 use quick_xml::events::BytesText;
 use serde::{Deserialize, de::DeserializeOwned};
-use wiremock::ResponseTemplate;
-use wiremock::{Request, Respond};
+use wiremock::Request;
 
 use crate::{
     MockBranchOptions, MockEntry, MockLinkResolution, MockPackage, MockPackageOptions, MockProject,
-    MockRevision, MockRevisionOptions, MockSourceFile, MockSourceFileKey, ObsMock, ZERO_REV_SRCMD5,
+    MockProjectKind, MockRevision, MockRevisionOptions, MockSourceFile, MockSourceFileKey, ObsMock,
+    TEST_SIGNKEY, TEST_SIGNKEY_EXPIRES, TEST_SIGNKEY_FINGERPRINT, TEST_SSLCERT, ZERO_REV_SRCMD5,
     random_md5,
 };
 
@@ -94,7 +94,7 @@ fn source_listing_xml(
     Ok(())
 }
 
-fn parse_xml_request<T: DeserializeOwned>(request: &Request) -> Result<T, ApiError> {
+pub(super) fn parse_xml_request<T: DeserializeOwned>(request: &Request) -> Result<T, ApiError> {
     quick_xml::de::from_reader(BufReader::new(&request.body[..]))
         .map_err(|e| ApiError::new(StatusCode::BAD_REQUEST, "400".to_string(), e.to_string()))
 }
@@ -109,10 +109,8 @@ impl ProjectListingResponder {
     }
 }
 
-impl Respond for ProjectListingResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for ProjectListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let project_name = components.nth_back(0).unwrap();
 
@@ -123,21 +121,202 @@ impl Respond for ProjectListingResponder {
                 .ok_or_else(|| unknown_project(project_name.to_owned()))
         );
 
+        let deleted = find_query_param(request, "deleted").is_some_and(|v| v == "1");
+        let expand = find_query_param(request, "expand").is_some_and(|v| v == "1");
+
+        // Package names with the project they come from, the project's own
+        // packages shadowing the ones of linked projects.
+        let mut packages: Vec<(&str, &str, &MockPackage)> = Vec::new();
+        if !deleted {
+            let mut origins = vec![project_name];
+            if expand {
+                origins.extend(project.links.iter().map(String::as_str));
+            }
+            for origin in origins {
+                let Some(origin_project) = projects.get(origin) else {
+                    continue;
+                };
+                for (name, package) in &origin_project.packages {
+                    if !packages.iter().any(|(n, _, _)| n == name) {
+                        packages.push((name, origin, package));
+                    }
+                }
+            }
+        }
+
         let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        if find_query_param(request, "view").is_some_and(|v| v == "info") {
+            let parse = find_query_param(request, "parse").is_some_and(|v| v == "1");
+            xml.create_element("sourceinfolist")
+                .write_inner_content(|writer| {
+                    for (name, _, package) in &packages {
+                        write_sourceinfo(writer, name, package, parse)?;
+                    }
+                    Ok(())
+                })
+                .unwrap();
+            return MockResponse::new(StatusCode::OK).set_body_xml(xml);
+        }
+
+        let count = if deleted {
+            project.deleted_packages.len()
+        } else {
+            packages.len()
+        };
         xml.create_element("directory")
-            .with_attribute(("count", project.packages.len().to_string().as_str()))
+            .with_attribute(("count", count.to_string().as_str()))
             .write_inner_content(|writer| {
-                for package_name in project.packages.keys() {
+                if deleted {
+                    for package_name in &project.deleted_packages {
+                        writer
+                            .create_element("entry")
+                            .with_attribute(("name", package_name.as_str()))
+                            .write_empty()?;
+                    }
+                }
+                for (name, origin, _) in &packages {
+                    let mut entry = writer
+                        .create_element("entry")
+                        .with_attribute(("name", *name));
+                    if *origin != project_name {
+                        entry = entry.with_attribute(("originproject", *origin));
+                    }
+                    entry.write_empty()?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+fn write_sourceinfo(
+    writer: &mut XMLWriter,
+    package_name: &str,
+    package: &MockPackage,
+    parse: bool,
+) -> std::io::Result<()> {
+    let (rev, vrev, srcmd5) = match package.revisions.last() {
+        Some(rev) => (
+            package.revisions.len().to_string(),
+            rev.vrev.map_or_else(String::new, |vrev| vrev.to_string()),
+            rev.options.srcmd5.as_str(),
+        ),
+        None => ("0".to_owned(), "0".to_owned(), ZERO_REV_SRCMD5),
+    };
+    let linkinfo = package
+        .revisions
+        .last()
+        .and_then(|rev| rev.linkinfo.first());
+    let verifymd5 = match linkinfo.map(|l| &l.link_resolution) {
+        Some(MockLinkResolution::Available { xsrcmd5 }) => xsrcmd5.as_str(),
+        _ => srcmd5,
+    };
+
+    let mut sourceinfo = writer.create_element("sourceinfo").with_attributes([
+        ("package", package_name),
+        ("rev", &rev),
+        ("vrev", &vrev),
+        ("srcmd5", srcmd5),
+        ("verifymd5", verifymd5),
+    ]);
+    if let Some(linkinfo) = linkinfo {
+        sourceinfo = sourceinfo.with_attribute(("lsrcmd5", linkinfo.lsrcmd5.as_str()));
+    }
+
+    sourceinfo.write_inner_content(|writer| {
+        let mut recipes: Vec<&str> = package
+            .revisions
+            .last()
+            .map(|rev| {
+                rev.entries
+                    .keys()
+                    .map(String::as_str)
+                    .filter(|name| name.ends_with(".spec"))
+                    .collect()
+            })
+            .unwrap_or_default();
+        // Prefer the recipe named after the package, like OBS does.
+        recipes.sort_by_key(|name| (*name != format!("{package_name}.spec"), *name));
+        if let Some(recipe) = recipes.first() {
+            writer
+                .create_element("filename")
+                .write_text_content(BytesText::new(recipe))?;
+        }
+
+        if let Some(MockLinkResolution::Error { error }) = linkinfo.map(|l| &l.link_resolution) {
+            writer
+                .create_element("error")
+                .write_text_content(BytesText::new(error))?;
+        } else if parse {
+            let version = package
+                .revisions
+                .last()
+                .and_then(|rev| rev.options.version.as_deref());
+            if let Some(version) = version {
+                writer
+                    .create_element("name")
+                    .write_text_content(BytesText::new(package_name))?;
+                writer
+                    .create_element("version")
+                    .write_text_content(BytesText::new(version))?;
+                writer
+                    .create_element("release")
+                    .write_text_content(BytesText::new(&vrev))?;
+            } else {
+                writer
+                    .create_element("error")
+                    .write_text_content(BytesText::new("no recipe file"))?;
+            }
+        }
+
+        if let Some(linkinfo) = linkinfo {
+            writer
+                .create_element("linked")
+                .with_attributes([
+                    ("project", linkinfo.project.as_str()),
+                    ("package", &linkinfo.package),
+                ])
+                .write_empty()?;
+        }
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+pub(crate) struct SourceListingResponder {
+    mock: ObsMock,
+}
+
+impl SourceListingResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for SourceListingResponder {
+    fn respond(&self, _request: &Request) -> MockResponse {
+        let projects = self.mock.projects().read().unwrap();
+        let mut names: Vec<&String> = projects.keys().collect();
+        names.sort();
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("directory")
+            .with_attribute(("count", names.len().to_string().as_str()))
+            .write_inner_content(|writer| {
+                for name in names {
                     writer
                         .create_element("entry")
-                        .with_attribute(("name", package_name.as_str()))
+                        .with_attribute(("name", name.as_str()))
                         .write_empty()?;
                 }
                 Ok(())
             })
             .unwrap();
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
 
@@ -151,23 +330,307 @@ impl ProjectDeleteResponder {
     }
 }
 
-impl Respond for ProjectDeleteResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for ProjectDeleteResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let project_name = components.nth_back(0).unwrap();
 
         let mut projects = self.mock.projects().write().unwrap();
+        if let Some(project) = projects.get(project_name) {
+            try_api!(check_unlocked(project_name, project, None));
+        }
 
         match projects.remove(project_name) {
-            Some(_) => ResponseTemplate::new(StatusCode::OK)
+            Some(_) => MockResponse::new(StatusCode::OK)
                 .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap()),
             None => unknown_project(project_name.to_owned()).into_response(),
         }
     }
 }
 
+fn no_pubkey() -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        "404".to_owned(),
+        "no pubkey available".to_owned(),
+    )
+}
+
+pub(crate) struct ProjectPubkeyResponder {
+    mock: ObsMock,
+}
+
+impl ProjectPubkeyResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for ProjectPubkeyResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let project_name = components.nth_back(1).unwrap();
+
+        let projects = self.mock.projects().read().unwrap();
+        let project = try_api!(
+            projects
+                .get(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))
+        );
+        ensure!(project.signkey_expires.is_some(), no_pubkey());
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_raw(TEST_SIGNKEY.as_bytes().to_vec(), "text/plain")
+    }
+}
+
+pub(crate) struct ProjectKeyInfoResponder {
+    mock: ObsMock,
+}
+
+impl ProjectKeyInfoResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for ProjectKeyInfoResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let project_name = components.nth_back(1).unwrap();
+
+        let projects = self.mock.projects().read().unwrap();
+        let project = try_api!(
+            projects
+                .get(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))
+        );
+        let with_sslcert = find_query_param(request, "withsslcert").is_some_and(|v| v == "1");
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("keyinfo")
+            .with_attribute(("project", project_name))
+            .write_inner_content(|writer| {
+                let Some(expires) = project.signkey_expires else {
+                    return Ok(());
+                };
+
+                writer
+                    .create_element("pubkey")
+                    .with_attributes([
+                        ("keyid", "e4de9c29"),
+                        ("userid", "home:test OBS Project <home:test@example.com>"),
+                        ("algo", "rsa"),
+                        ("keysize", "2048"),
+                        ("expires", expires.to_string().as_str()),
+                        ("fingerprint", TEST_SIGNKEY_FINGERPRINT),
+                    ])
+                    .write_text_content(BytesText::new(TEST_SIGNKEY))?;
+
+                if with_sslcert {
+                    writer
+                        .create_element("sslcert")
+                        .with_attributes([
+                            ("serial", "0x76e5b3b9adb8a7a122e2e5eb88d802e0330c1a15"),
+                            (
+                                "issuer",
+                                "CN=home:test OBS Project, emailAddress=home:test@example.com",
+                            ),
+                            (
+                                "subject",
+                                "CN=home:test OBS Project, emailAddress=home:test@example.com",
+                            ),
+                            ("notbefore", "Oct 18 16:17:32 2026 GMT"),
+                            ("notafter", "Oct 15 16:17:32 2036 GMT"),
+                            ("keyid", "e4de9c29"),
+                            (
+                                "fingerprint",
+                                "ae16 2d92 cdf9 e1e1 f5df c1e6 396c 3a98 0d33 7542",
+                            ),
+                        ])
+                        .write_text_content(BytesText::new(TEST_SSLCERT))?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) struct ProjectSourceCommandResponder {
+    mock: ObsMock,
+}
+
+impl ProjectSourceCommandResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for ProjectSourceCommandResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let project_name = components.nth_back(0).unwrap();
+
+        let mut projects = self.mock.projects().write().unwrap();
+        let project = try_api!(
+            projects
+                .get_mut(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))
+        );
+
+        let cmd = try_api!(
+            find_query_param(request, "cmd").ok_or_else(|| ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "missing_parameter".to_string(),
+                "POST request without given cmd parameter".to_string()
+            ))
+        );
+
+        match cmd.as_ref() {
+            "createkey" => {
+                project.signkey_expires = Some(TEST_SIGNKEY_EXPIRES);
+            }
+            "extendkey" => {
+                ensure!(project.signkey_expires.is_some(), no_pubkey());
+                let days: u64 = try_api!(
+                    find_query_param(request, "days")
+                        .map_or(Ok(730), |days| days.parse())
+                        .map_err(|_| ApiError::new(
+                            StatusCode::BAD_REQUEST,
+                            "invalid_parameter".to_owned(),
+                            "days must be a number".to_owned()
+                        ))
+                );
+                let now = SystemTime::now()
+                    .duration_since(SystemTime::UNIX_EPOCH)
+                    .unwrap()
+                    .as_secs();
+                project.signkey_expires = Some(now + days * 24 * 60 * 60);
+            }
+            "createpatchinfo" => {
+                try_api!(check_unlocked(project_name, project, None));
+                return do_createpatchinfo(request, project_name, project, &self.mock);
+            }
+            "lock" | "unlock" => {
+                return do_project_lock(request, project_name, project, cmd == "lock");
+            }
+            "createmaintenanceincident" => {
+                ensure!(
+                    project.kind == MockProjectKind::Maintenance,
+                    ApiError::new(
+                        StatusCode::BAD_REQUEST,
+                        "incident_has_no_maintenance_project".to_owned(),
+                        "incident projects shall only create below maintenance projects".to_owned(),
+                    )
+                );
+                let roles = project.roles.clone();
+
+                let prefix = format!("{project_name}:");
+                let number = projects
+                    .keys()
+                    .filter_map(|name| name.strip_prefix(&prefix)?.parse::<u64>().ok())
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                let incident_name = format!("{prefix}{number}");
+                projects.insert(
+                    incident_name.clone(),
+                    MockProject {
+                        kind: MockProjectKind::MaintenanceIncident,
+                        roles,
+                        ..Default::default()
+                    },
+                );
+
+                let xml = build_status_xml("ok", Some("Ok".to_owned()), |writer| {
+                    branch_data_xml(writer, "targetproject", &incident_name).unwrap();
+                    Ok(())
+                })
+                .unwrap();
+                return MockResponse::new(StatusCode::OK).set_body_xml(xml);
+            }
+            _ => {
+                return ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    "illegal_request".to_string(),
+                    "invalid_command".to_string(),
+                )
+                .into_response();
+            }
+        }
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+    }
+}
+
+pub(crate) struct ProjectConfigResponder {
+    mock: ObsMock,
+}
+
+impl ProjectConfigResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for ProjectConfigResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let project_name = components.nth_back(1).unwrap();
+
+        let projects = self.mock.projects().read().unwrap();
+        let project = try_api!(
+            projects
+                .get(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))
+        );
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_raw(project.config.clone().into_bytes(), "text/plain")
+    }
+}
+
+pub(crate) struct ProjectConfigPlacementResponder {
+    mock: ObsMock,
+}
+
+impl ProjectConfigPlacementResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for ProjectConfigPlacementResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let project_name = components.nth_back(1).unwrap();
+
+        let mut projects = self.mock.projects().write().unwrap();
+        let project = try_api!(
+            projects
+                .get_mut(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))
+        );
+
+        try_api!(check_unlocked(project_name, project, None));
+
+        let config = try_api!(String::from_utf8(request.body.clone()).map_err(|_| {
+            ApiError::new(
+                StatusCode::BAD_REQUEST,
+                "400".to_owned(),
+                "config is not valid UTF-8".to_owned(),
+            )
+        }));
+        project.config = config;
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+    }
+}
+
 pub(crate) struct ProjectMetaResponder {
     mock: ObsMock,
 }
@@ -178,8 +641,8 @@ impl ProjectMetaResponder {
     }
 }
 
-impl Respond for ProjectMetaResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
+impl MockRespond for ProjectMetaResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let project_name = components.nth_back(1).unwrap();
 
@@ -191,41 +654,115 @@ impl Respond for ProjectMetaResponder {
         );
 
         let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
-        xml.create_element("project")
-            .with_attribute(("name", project_name))
-            .write_inner_content(|writer| {
-                for (repo, arches) in &project.repos {
-                    let mut repository_xml = writer
-                        .create_element("repository")
-                        .with_attribute(("name", repo.as_str()));
-                    if project.rebuild != Default::default() {
-                        repository_xml = repository_xml
-                            .with_attribute(("rebuild", project.rebuild.to_string().as_str()));
-                    }
-                    if project.block != Default::default() {
-                        repository_xml = repository_xml
-                            .with_attribute(("block", project.block.to_string().as_str()));
-                    }
+        write_project_meta(&mut xml, project_name, project).unwrap();
 
-                    repository_xml.write_inner_content(|writer| {
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) fn write_project_meta(
+    writer: &mut XMLWriter,
+    project_name: &str,
+    project: &MockProject,
+) -> std::io::Result<()> {
+    let mut project_xml = writer
+        .create_element("project")
+        .with_attribute(("name", project_name));
+    if project.kind != Default::default() {
+        project_xml = project_xml.with_attribute(("kind", project.kind.to_string().as_str()));
+    }
+
+    project_xml.write_inner_content(|writer| {
+        writer.create_element("title").write_empty()?;
+        writer.create_element("description").write_empty()?;
+        write_roles(writer, &project.roles)?;
+
+        if project.locked {
+            writer
+                .create_element("lock")
+                .write_inner_content(|writer| {
+                    writer.create_element("enable").write_empty()?;
+                    Ok(())
+                })?;
+        }
+
+        if !project.maintains.is_empty() {
+            writer
+                .create_element("maintenance")
+                .write_inner_content(|writer| {
+                    for maintained in &project.maintains {
                         writer
-                            .create_element("path")
-                            .with_attributes([("project", project_name), ("repository", repo)])
+                            .create_element("maintains")
+                            .with_attribute(("project", maintained.as_str()))
                             .write_empty()?;
+                    }
+                    Ok(())
+                })?;
+        }
 
-                        for arch in arches.keys() {
-                            writer
-                                .create_element("arch")
-                                .write_text_content(BytesText::new(arch))?;
-                        }
-                        Ok(())
-                    })?;
+        for (repo, arches) in &project.repos {
+            let mut repository_xml = writer
+                .create_element("repository")
+                .with_attribute(("name", repo.as_str()));
+            if project.rebuild != Default::default() {
+                repository_xml = repository_xml
+                    .with_attribute(("rebuild", project.rebuild.to_string().as_str()));
+            }
+            if project.block != Default::default() {
+                repository_xml =
+                    repository_xml.with_attribute(("block", project.block.to_string().as_str()));
+            }
+
+            repository_xml.write_inner_content(|writer| {
+                writer
+                    .create_element("path")
+                    .with_attributes([("project", project_name), ("repository", repo)])
+                    .write_empty()?;
+
+                for arch in arches.keys() {
+                    writer
+                        .create_element("arch")
+                        .write_text_content(BytesText::new(arch))?;
                 }
                 Ok(())
-            })
-            .unwrap();
+            })?;
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+/// Stores the roles given in the new meta, as the only part of it that the
+/// mock doesn't keep elsewhere.
+pub(crate) struct ProjectMetaPlacementResponder {
+    mock: ObsMock,
+}
+
+impl ProjectMetaPlacementResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for ProjectMetaPlacementResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let mut components = request.url.path_segments().unwrap();
+        let project_name = components.nth_back(1).unwrap();
+
+        let mut projects = self.mock.projects().write().unwrap();
+        let project = try_api!(
+            projects
+                .get_mut(project_name)
+                .ok_or_else(|| unknown_project(project_name.to_owned()))
+        );
+
+        try_api!(check_unlocked(project_name, project, None));
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        let meta: MetaRoles = try_api!(parse_xml_request(request));
+        project.roles = meta.into_roles();
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
     }
 }
 
@@ -239,8 +776,8 @@ impl PackageSourceHistoryResponder {
     }
 }
 
-impl Respond for PackageSourceHistoryResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
+impl MockRespond for PackageSourceHistoryResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(1).unwrap();
         let project_name = components.nth_back(0).unwrap();
@@ -308,7 +845,7 @@ impl Respond for PackageSourceHistoryResponder {
             })
             .unwrap();
 
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
 
@@ -322,10 +859,8 @@ impl PackageSourceListingResponder {
     }
 }
 
-impl Respond for PackageSourceListingResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for PackageSourceListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(0).unwrap();
         let project_name = components.nth_back(0).unwrap();
@@ -344,6 +879,13 @@ impl Respond for PackageSourceListingResponder {
                 .ok_or_else(|| unknown_package(package_name.to_owned()))
         );
 
+        if find_query_param(request, "view").is_some_and(|v| v == "info") {
+            let parse = find_query_param(request, "parse").is_some_and(|v| v == "1");
+            let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+            write_sourceinfo(&mut xml, package_name, package, parse).unwrap();
+            return MockResponse::new(StatusCode::OK).set_body_xml(xml);
+        }
+
         let list_meta = match find_query_param(request, "meta").as_deref() {
             Some("1") => true,
             None | Some("0") => false,
@@ -394,14 +936,14 @@ impl Respond for PackageSourceListingResponder {
                 .write_empty()
                 .unwrap();
 
-            return ResponseTemplate::new(StatusCode::OK).set_body_xml(xml);
+            return MockResponse::new(StatusCode::OK).set_body_xml(xml);
         }
 
         // -1 to skip the zero revision (see above).
         let rev = &revisions[rev_id - 1];
         let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
         source_listing_xml(&mut xml, package_name, package, rev_id, rev).unwrap();
-        ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
     }
 }
 
@@ -415,10 +957,8 @@ impl PackageSourceFileResponder {
     }
 }
 
-impl Respond for PackageSourceFileResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for PackageSourceFileResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let file_name = components.nth_back(0).unwrap();
         let package_name = components.nth_back(0).unwrap();
@@ -439,23 +979,16 @@ impl Respond for PackageSourceFileResponder {
         );
 
         if file_name == "_meta" {
-            let entry = package
-                .meta_revisions
-                .last()
-                .unwrap()
-                .entries
-                .get(MockSourceFile::META_PATH)
-                .unwrap();
-            let meta = package
-                .files
-                .get(&MockSourceFileKey::borrowed(
-                    MockSourceFile::META_PATH,
-                    &entry.md5,
-                ))
-                .unwrap();
-            ResponseTemplate::new(200).set_body_raw(meta.clone(), "application/xml")
+            MockResponse::new(200).set_body_raw(package.meta(), "application/xml")
         } else {
-            match package.revisions.last() {
+            // Revisions can be given by number or by srcmd5.
+            let rev = match find_query_param(request, "rev") {
+                Some(rev_arg) => package.revisions.iter().enumerate().find_map(|(i, rev)| {
+                    (rev_arg == (i + 1).to_string() || rev_arg == rev.options.srcmd5).then_some(rev)
+                }),
+                None => package.revisions.last(),
+            };
+            match rev {
                 Some(rev) => {
                     let entry = try_api!(
                         rev.entries
@@ -466,7 +999,7 @@ impl Respond for PackageSourceFileResponder {
                         .files
                         .get(&MockSourceFileKey::borrowed(file_name, &entry.md5))
                         .unwrap();
-                    ResponseTemplate::new(200)
+                    MockResponse::new(200)
                         .set_body_raw(contents.clone(), "application/octet-stream")
                 }
                 None => source_file_not_found(file_name).into_response(),
@@ -497,10 +1030,8 @@ impl PackageSourcePlacementResponder {
     }
 }
 
-impl Respond for PackageSourcePlacementResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for PackageSourcePlacementResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let file_name = components.nth_back(0).unwrap();
         let package_name = components.nth_back(0).unwrap();
@@ -515,28 +1046,45 @@ impl Respond for PackageSourcePlacementResponder {
                 .ok_or_else(|| unknown_project(project_name.to_owned()))
         );
 
+        try_api!(check_unlocked(project_name, project, Some(package_name)));
+
         if file_name == "_meta" {
             // TODO: parse file, return errors if attributes don't match (the
             // API crate doesn't add these at all, so leaving this out for now
             // is relatively low-risk)
 
-            project
-                .packages
-                .entry(package_name.to_owned())
-                .or_insert_with(|| {
-                    MockPackage::new_with_metadata(
-                        project_name,
-                        package_name,
-                        MockPackageOptions {
-                            meta_srcmd5: random_md5(),
-                            time: SystemTime::now(),
-                            user: self.mock.auth().username().to_owned(),
-                            ..Default::default()
-                        },
-                    )
-                });
+            project.deleted_packages.remove(package_name);
+            match project.packages.get_mut(package_name) {
+                Some(package) => package.push_meta(
+                    MockSourceFile {
+                        path: file_name.to_owned(),
+                        contents: request.body.clone(),
+                    },
+                    MockRevisionOptions {
+                        srcmd5: random_md5(),
+                        time: SystemTime::now(),
+                        user: self.mock.auth().username().to_owned(),
+                        ..Default::default()
+                    },
+                ),
+                None => {
+                    project.packages.insert(
+                        package_name.to_owned(),
+                        MockPackage::new_with_metadata(
+                            project_name,
+                            package_name,
+                            MockPackageOptions {
+                                meta_srcmd5: random_md5(),
+                                time: SystemTime::now(),
+                                user: self.mock.auth().username().to_owned(),
+                                ..Default::default()
+                            },
+                        ),
+                    );
+                }
+            }
 
-            ResponseTemplate::new(StatusCode::OK)
+            MockResponse::new(StatusCode::OK)
                 .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
         } else {
             let package = try_api!(
@@ -565,7 +1113,7 @@ impl Respond for PackageSourcePlacementResponder {
                     })
                     .unwrap();
 
-                ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+                MockResponse::new(StatusCode::OK).set_body_xml(xml)
             } else {
                 ApiError::new(
                     StatusCode::MISDIRECTED_REQUEST,
@@ -595,7 +1143,7 @@ fn do_commit(
     comment: Option<Cow<'_, str>>,
     mock: &ObsMock,
     projects: &mut HashMap<String, MockProject>,
-) -> ResponseTemplate {
+) -> MockResponse {
     let project = try_api!(
         projects
             .get_mut(project_name)
@@ -650,7 +1198,7 @@ fn do_commit(
             })
             .unwrap();
 
-        return ResponseTemplate::new(StatusCode::OK).set_body_xml(xml);
+        return MockResponse::new(StatusCode::OK).set_body_xml(xml);
     }
 
     let options = MockRevisionOptions {
@@ -667,10 +1215,14 @@ fn do_commit(
     let rev = package.revisions.last().unwrap();
     let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
     source_listing_xml(&mut xml, package_name, package, rev_id, rev).unwrap();
-    ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+    MockResponse::new(StatusCode::OK).set_body_xml(xml)
 }
 
-fn branch_data_xml(xml: &mut XMLWriter, name: &str, value: &str) -> quick_xml::Result<()> {
+pub(crate) fn branch_data_xml(
+    xml: &mut XMLWriter,
+    name: &str,
+    value: &str,
+) -> quick_xml::Result<()> {
     xml.create_element("data")
         .with_attribute(("name", name))
         .write_text_content(BytesText::new(value))?;
@@ -685,7 +1237,10 @@ fn project_meta_enum_error() -> ApiError {
     )
 }
 
-fn parse_project_meta_enum_param<T>(request: &Request, name: &str) -> Result<Option<T>, ApiError>
+pub(super) fn parse_project_meta_enum_param<T>(
+    request: &Request,
+    name: &str,
+) -> Result<Option<T>, ApiError>
 where
     for<'a> T: TryFrom<&'a str>,
 {
@@ -701,7 +1256,7 @@ fn do_branch(
     comment: Option<Cow<'_, str>>,
     mock: &ObsMock,
     projects: &mut HashMap<String, MockProject>,
-) -> ResponseTemplate {
+) -> MockResponse {
     let target_project_name = find_query_param(request, "target_project").unwrap_or_else(|| {
         Cow::Owned(format!(
             "home:{}:branches:{}",
@@ -794,6 +1349,8 @@ fn do_branch(
                 "branch target package already exists: {target_project_name}/{target_package_name}"
             )
         )
+        .with_data("targetproject", target_project_name.to_string())
+        .with_data("targetpackage", target_package_name.to_string())
     );
 
     target_project
@@ -808,13 +1365,11 @@ fn do_branch(
         Ok(())
     })
     .unwrap();
-    ResponseTemplate::new(StatusCode::OK).set_body_xml(xml)
+    MockResponse::new(StatusCode::OK).set_body_xml(xml)
 }
 
-impl Respond for PackageSourceCommandResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for PackageSourceCommandResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(0).unwrap();
         let project_name = components.nth_back(0).unwrap();
@@ -831,6 +1386,12 @@ impl Respond for PackageSourceCommandResponder {
 
         let comment = find_query_param(request, "comment");
 
+        if matches!(cmd.as_ref(), "commitfilelist" | "updatepatchinfo")
+            && let Some(project) = projects.get(project_name)
+        {
+            try_api!(check_unlocked(project_name, project, Some(package_name)));
+        }
+
         match cmd.as_ref() {
             "commitfilelist" => do_commit(
                 request,
@@ -848,6 +1409,17 @@ impl Respond for PackageSourceCommandResponder {
                 &self.mock,
                 &mut projects,
             ),
+            "updatepatchinfo" => {
+                do_updatepatchinfo(project_name, package_name, &self.mock, &mut projects)
+            }
+            "lock" | "unlock" => do_package_lock(
+                request,
+                project_name,
+                package_name,
+                cmd == "lock",
+                &self.mock,
+                &mut projects,
+            ),
             _ => ApiError::new(
                 StatusCode::NOT_FOUND,
                 "illegal_request".to_string(),
@@ -868,10 +1440,8 @@ impl PackageSourceDeleteResponder {
     }
 }
 
-impl Respond for PackageSourceDeleteResponder {
-    fn respond(&self, request: &Request) -> ResponseTemplate {
-        try_api!(check_auth(self.mock.auth(), request));
-
+impl MockRespond for PackageSourceDeleteResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
         let mut components = request.url.path_segments().unwrap();
         let package_name = components.nth_back(0).unwrap();
         let project_name = components.nth_back(0).unwrap();
@@ -883,10 +1453,12 @@ impl Respond for PackageSourceDeleteResponder {
                 .ok_or_else(|| unknown_project(project_name.to_owned()))
         );
 
+        try_api!(check_unlocked(project_name, project, Some(package_name)));
         ensure!(
             project.packages.remove(package_name).is_some(),
             unknown_package(package_name.to_owned())
         );
+        project.deleted_packages.insert(package_name.to_owned());
 
         for arches in project.repos.values_mut() {
             for repo in arches.values_mut() {
@@ -894,7 +1466,7 @@ impl Respond for PackageSourceDeleteResponder {
             }
         }
 
-        ResponseTemplate::new(StatusCode::OK)
+        MockResponse::new(StatusCode::OK)
             .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
     }
 }
@@ -0,0 +1,509 @@
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use quick_xml::events::BytesText;
+use wiremock::Request;
+
+use crate::{
+    MockToken, MockTokenKind, MockTrigger, MockWorkflowRun, MockWorkflowRunStatus, ObsMock,
+};
+
+use super::*;
+
+fn unknown_token(id: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::NOT_FOUND,
+        "not_found".to_owned(),
+        format!("Couldn't find Token with 'id'={id}"),
+    )
+}
+
+pub(super) fn missing_parameter(name: &str) -> ApiError {
+    ApiError::new(
+        StatusCode::BAD_REQUEST,
+        "missing_parameter".to_owned(),
+        format!("Missing parameter '{name}'"),
+    )
+}
+
+/// Checks that the tokens of `login` are requested by that user.
+fn token_owner<'r>(mock: &ObsMock, request: &'r Request) -> Result<&'r str, ApiError> {
+    let login = request.url.path_segments().unwrap().nth(1).unwrap();
+    if login != mock.auth().username() {
+        return Err(ApiError::new(
+            StatusCode::FORBIDDEN,
+            "no_permission".to_owned(),
+            format!("No permission to manage the tokens of {login}"),
+        ));
+    }
+    Ok(login)
+}
+
+fn ensure_package_exists(mock: &ObsMock, project: &str, package: &str) -> Result<(), ApiError> {
+    let projects = mock.projects().read().unwrap();
+    let packages = &projects
+        .get(project)
+        .ok_or_else(|| unknown_project(project.to_owned()))?
+        .packages;
+    if !packages.contains_key(package) {
+        return Err(unknown_package(package.to_owned()));
+    }
+    Ok(())
+}
+
+/// Returns the token the request is authenticated with, which has to be of
+/// the given kind.
+fn trigger_token(
+    mock: &ObsMock,
+    request: &Request,
+    kind: MockTokenKind,
+) -> Result<MockToken, ApiError> {
+    let invalid_token =
+        |summary: String| ApiError::new(StatusCode::FORBIDDEN, "invalid_token".to_owned(), summary);
+    let string = request
+        .headers
+        .get(AUTHORIZATION)
+        .and_then(|auth| auth.to_str().ok())
+        .and_then(|auth| auth.strip_prefix("Token "))
+        .map(str::trim);
+    let token = mock
+        .tokens()
+        .into_iter()
+        .find(|token| Some(token.string.as_str()) == string)
+        .ok_or_else(|| invalid_token("No valid token found".to_owned()))?;
+    if token.kind != kind {
+        return Err(invalid_token(format!(
+            "Token of kind {} can't be used for {kind}",
+            token.kind
+        )));
+    }
+    Ok(token)
+}
+
+fn mark_triggered(mock: &ObsMock, id: u64) {
+    if let Some(token) = mock
+        .inner
+        .tokens
+        .write()
+        .unwrap()
+        .iter_mut()
+        .find(|token| token.id == id)
+    {
+        token.triggered_at = Some(SystemTime::now());
+    }
+}
+
+pub(crate) struct PersonTokenListingResponder {
+    mock: ObsMock,
+}
+
+impl PersonTokenListingResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for PersonTokenListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let login = try_api!(token_owner(&self.mock, request));
+        let tokens: Vec<MockToken> = self
+            .mock
+            .tokens()
+            .into_iter()
+            .filter(|token| token.owner == login)
+            .collect();
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("directory")
+            .with_attribute(("count", tokens.len().to_string().as_str()))
+            .write_inner_content(|writer| {
+                for token in &tokens {
+                    let mut entry_xml = writer.create_element("entry").with_attributes([
+                        ("id", token.id.to_string().as_str()),
+                        ("string", &token.string),
+                        ("kind", &token.kind.to_string()),
+                        ("description", &token.description),
+                    ]);
+                    if let Some(triggered_at) = token.triggered_at {
+                        entry_xml =
+                            entry_xml.with_attribute(("triggered_at", &*format_time(triggered_at)));
+                    }
+                    if let Some((project, package)) = &token.package {
+                        entry_xml = entry_xml
+                            .with_attributes([("project", project.as_str()), ("package", package)]);
+                    }
+                    entry_xml.write_empty()?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) struct PersonTokenCreationResponder {
+    mock: ObsMock,
+}
+
+impl PersonTokenCreationResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for PersonTokenCreationResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        try_api!(token_owner(&self.mock, request));
+
+        let cmd =
+            try_api!(find_query_param(request, "cmd").ok_or_else(|| missing_parameter("cmd")));
+        ensure!(
+            cmd == "create",
+            ApiError::new(
+                StatusCode::NOT_FOUND,
+                "illegal_request".to_owned(),
+                "invalid_command".to_owned(),
+            )
+        );
+
+        let operation =
+            find_query_param(request, "operation").unwrap_or(Cow::Borrowed("runservice"));
+        let kind = try_api!(
+            MockTokenKind::from_str(&operation)
+                .ok()
+                .filter(|kind| *kind != MockTokenKind::Rss)
+                .ok_or_else(|| ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "invalid_operation".to_owned(),
+                    format!("Invalid operation '{operation}'"),
+                ))
+        );
+        if kind == MockTokenKind::Workflow {
+            ensure!(
+                find_query_param(request, "scm_token").is_some(),
+                missing_parameter("scm_token")
+            );
+        }
+
+        let package = match (
+            find_query_param(request, "project"),
+            find_query_param(request, "package"),
+        ) {
+            (Some(project), Some(package)) => {
+                try_api!(ensure_package_exists(&self.mock, &project, &package));
+                Some((project.into_owned(), package.into_owned()))
+            }
+            (None, None) => None,
+            (None, Some(_)) => return missing_parameter("project").into_response(),
+            (Some(_), None) => return missing_parameter("package").into_response(),
+        };
+
+        let string = random_md5();
+        let description = find_query_param(request, "description").unwrap_or_default();
+        let id = self
+            .mock
+            .add_token_with(string.clone(), kind, description.into_owned(), package);
+
+        let xml = build_status_xml("ok", Some("Ok".to_owned()), |writer| {
+            writer
+                .create_element("data")
+                .with_attribute(("name", "token"))
+                .write_text_content(BytesText::new(&string))?;
+            writer
+                .create_element("data")
+                .with_attribute(("name", "id"))
+                .write_text_content(BytesText::new(&id.to_string()))?;
+            Ok(())
+        })
+        .unwrap();
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) struct PersonTokenDeleteResponder {
+    mock: ObsMock,
+}
+
+impl PersonTokenDeleteResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for PersonTokenDeleteResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let login = try_api!(token_owner(&self.mock, request));
+        let id = request.url.path_segments().unwrap().next_back().unwrap();
+
+        let owned = self
+            .mock
+            .tokens()
+            .iter()
+            .any(|token| token.id.to_string() == id && token.owner == login);
+        ensure!(
+            owned && self.mock.delete_token(id.parse().unwrap()),
+            unknown_token(id)
+        );
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", Some("Ok".to_owned()), |_| Ok(())).unwrap())
+    }
+}
+
+/// Responds to `/trigger/<operation>`, which only accepts tokens of the
+/// matching kind.
+pub(crate) struct TriggerResponder {
+    mock: ObsMock,
+}
+
+impl TriggerResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for TriggerResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let operation = request.url.path_segments().unwrap().next_back().unwrap();
+        let kind = MockTokenKind::from_str(operation).unwrap();
+
+        let token = try_api!(trigger_token(&self.mock, request, kind));
+
+        let (project, package) = match &token.package {
+            Some(package) => package.clone(),
+            None => (
+                try_api!(
+                    find_query_param(request, "project")
+                        .ok_or_else(|| missing_parameter("project"))
+                )
+                .into_owned(),
+                try_api!(
+                    find_query_param(request, "package")
+                        .ok_or_else(|| missing_parameter("package"))
+                )
+                .into_owned(),
+            ),
+        };
+        try_api!(ensure_package_exists(&self.mock, &project, &package));
+
+        let params = request
+            .url
+            .query_pairs()
+            .filter(|(key, _)| key != "project" && key != "package")
+            .map(|(key, value)| (key.into_owned(), value.into_owned()))
+            .collect();
+
+        mark_triggered(&self.mock, token.id);
+        self.mock.inner.triggers.write().unwrap().push(MockTrigger {
+            kind,
+            token_id: token.id,
+            project,
+            package,
+            params,
+        });
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", None, |_| Ok(())).unwrap())
+    }
+}
+
+/// The generic event type OBS maps the webhook event of an SCM to.
+fn generic_event_type(scm_vendor: &str, event: &str) -> Option<&'static str> {
+    match (scm_vendor, event) {
+        ("github" | "gitea", "pull_request") => Some("pull_request"),
+        ("github" | "gitea", "push") => Some("push"),
+        ("gitlab", "Merge Request Hook") => Some("pull_request"),
+        ("gitlab", "Push Hook") => Some("push"),
+        ("gitlab", "Tag Push Hook") => Some("tag_push"),
+        _ => None,
+    }
+}
+
+fn write_workflow_run(writer: &mut XMLWriter, run: &MockWorkflowRun) -> std::io::Result<()> {
+    writer
+        .create_element("workflow_run")
+        .with_attribute(("id", run.id.to_string().as_str()))
+        .write_inner_content(|writer| {
+            let mut elements = vec![
+                ("status", run.status.to_string()),
+                ("hook_event", run.hook_event.clone()),
+                ("generic_event_type", run.generic_event_type.clone()),
+                ("scm_vendor", run.scm_vendor.clone()),
+                ("request_payload", run.request_payload.clone()),
+            ];
+            if let Some(response_body) = &run.response_body {
+                elements.push(("response_body", response_body.clone()));
+            }
+            for (name, value) in elements {
+                writer
+                    .create_element(name)
+                    .write_text_content(BytesText::new(&value))?;
+            }
+            Ok(())
+        })?;
+    Ok(())
+}
+
+pub(crate) struct WorkflowTriggerResponder {
+    mock: ObsMock,
+}
+
+impl WorkflowTriggerResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for WorkflowTriggerResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let token = try_api!(trigger_token(&self.mock, request, MockTokenKind::Workflow));
+        let id = try_api!(find_query_param(request, "id").ok_or_else(|| missing_parameter("id")));
+        ensure!(
+            id == token.id.to_string(),
+            ApiError::new(
+                StatusCode::FORBIDDEN,
+                "invalid_token".to_owned(),
+                format!("Token id {id} doesn't match the token"),
+            )
+        );
+
+        let event = [
+            ("github", "X-GitHub-Event"),
+            ("gitlab", "X-Gitlab-Event"),
+            ("gitea", "X-Gitea-Event"),
+        ]
+        .into_iter()
+        .find_map(|(scm_vendor, header)| {
+            let event = request.headers.get(header)?.to_str().ok()?;
+            Some((scm_vendor, event))
+        });
+        let (scm_vendor, event) = try_api!(event.ok_or_else(|| ApiError::new(
+            StatusCode::BAD_REQUEST,
+            "bad_request".to_owned(),
+            "Only GitHub, GitLab and Gitea are supported".to_owned(),
+        )));
+        let generic_event_type =
+            try_api!(
+                generic_event_type(scm_vendor, event).ok_or_else(|| ApiError::new(
+                    StatusCode::BAD_REQUEST,
+                    "bad_request".to_owned(),
+                    format!("Event not supported: {event}"),
+                ))
+            );
+
+        mark_triggered(&self.mock, token.id);
+        let mut runs = self.mock.inner.workflow_runs.write().unwrap();
+        let run_id = runs.len() as u64 + 1;
+        runs.push(MockWorkflowRun {
+            id: run_id,
+            token_id: token.id,
+            status: MockWorkflowRunStatus::Running,
+            scm_vendor: scm_vendor.to_owned(),
+            hook_event: event.to_owned(),
+            generic_event_type: generic_event_type.to_owned(),
+            request_payload: String::from_utf8_lossy(&request.body).into_owned(),
+            response_body: None,
+        });
+
+        MockResponse::new(StatusCode::OK)
+            .set_body_xml(build_status_xml("ok", None, |_| Ok(())).unwrap())
+    }
+}
+
+pub(crate) struct WorkflowRunListingResponder {
+    mock: ObsMock,
+}
+
+impl WorkflowRunListingResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for WorkflowRunListingResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let token_id = try_api!(
+            find_query_param(request, "workflow_token_id")
+                .ok_or_else(|| missing_parameter("workflow_token_id"))
+        );
+        let token = try_api!(
+            self.mock
+                .tokens()
+                .into_iter()
+                .find(|token| token.id.to_string() == token_id
+                    && token.kind == MockTokenKind::Workflow)
+                .ok_or_else(|| unknown_token(&token_id))
+        );
+        ensure!(
+            find_query_param(request, "pr_mr").is_none()
+                && find_query_param(request, "commit").is_none(),
+            ApiError::new(
+                StatusCode::MISDIRECTED_REQUEST,
+                "unsupported".to_owned(),
+                "Operation not supported by the OBS mock server".to_owned(),
+            )
+        );
+        let status = find_query_param(request, "status");
+        let event_type = find_query_param(request, "event_type");
+
+        let runs: Vec<_> = self
+            .mock
+            .workflow_runs()
+            .into_iter()
+            .filter(|run| {
+                run.token_id == token.id
+                    && status
+                        .as_ref()
+                        .is_none_or(|status| *status == run.status.to_string())
+                    && event_type
+                        .as_ref()
+                        .is_none_or(|event_type| *event_type == run.generic_event_type)
+            })
+            .collect();
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        xml.create_element("workflow_runs")
+            .with_attribute(("count", runs.len().to_string().as_str()))
+            .write_inner_content(|writer| {
+                for run in &runs {
+                    write_workflow_run(writer, run)?;
+                }
+                Ok(())
+            })
+            .unwrap();
+
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
+
+pub(crate) struct WorkflowRunResponder {
+    mock: ObsMock,
+}
+
+impl WorkflowRunResponder {
+    pub fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl MockRespond for WorkflowRunResponder {
+    fn respond(&self, request: &Request) -> MockResponse {
+        let id = request.url.path_segments().unwrap().next_back().unwrap();
+        let run = try_api!(
+            self.mock
+                .workflow_runs()
+                .into_iter()
+                .find(|run| run.id.to_string() == id)
+                .ok_or_else(|| ApiError::new(
+                    StatusCode::NOT_FOUND,
+                    "not_found".to_owned(),
+                    format!("Couldn't find WorkflowRun with 'id'={id}"),
+                ))
+        );
+
+        let mut xml = XMLWriter::new_with_indent(Default::default(), b' ', 8);
+        write_workflow_run(&mut xml, &run).unwrap();
+        MockResponse::new(StatusCode::OK).set_body_xml(xml)
+    }
+}
@@ -1,36 +1,101 @@
 use std::{
     borrow::Cow,
-    collections::HashMap,
-    sync::{Arc, RwLock},
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        Arc, Mutex, OnceLock, RwLock,
+        atomic::{AtomicU64, Ordering},
+    },
     time::{Duration, SystemTime},
 };
 
 use api::{
     ArchListingResponder, BuildBinaryFileResponder, BuildBinaryListResponder,
     BuildHistoryResponder, BuildJobHistoryResponder, BuildLogResponder,
-    BuildPackageStatusResponder, BuildResultsResponder, PackageSourceCommandResponder,
-    PackageSourceDeleteResponder, PackageSourceFileResponder, PackageSourceHistoryResponder,
-    PackageSourceListingResponder, PackageSourcePlacementResponder, ProjectBuildCommandResponder,
-    ProjectDeleteResponder, ProjectListingResponder, ProjectMetaResponder, RepoListingResponder,
+    BuildPackageStatusResponder, BuildResultsResponder, CommentCreationResponder,
+    CommentDeleteResponder, CommentListingResponder, GroupResponder, MaintenanceBranchResponder,
+    PackageSourceCommandResponder, PackageSourceDeleteResponder, PackageSourceFileResponder,
+    PackageSourceHistoryResponder, PackageSourceListingResponder, PackageSourcePlacementResponder,
+    PersonResponder, PersonTokenCreationResponder, PersonTokenDeleteResponder,
+    PersonTokenListingResponder, ProjectBuildCommandResponder, ProjectConfigPlacementResponder,
+    ProjectConfigResponder, ProjectDeleteResponder, ProjectKeyInfoResponder,
+    ProjectListingResponder, ProjectMetaPlacementResponder, ProjectMetaResponder,
+    ProjectPubkeyResponder, ProjectSourceCommandResponder, RepoListingResponder,
+    RequestCreationResponder, SearchIdResponder, SearchProjectResponder, SourceListingResponder,
+    TriggerResponder, WorkflowRunListingResponder, WorkflowRunResponder, WorkflowTriggerResponder,
 };
 
 use md5::{Digest, Md5};
 use strum_macros::{Display, EnumString};
 use wiremock::{
     Mock, MockServer,
-    http::Url,
+    http::{Method, Url},
     matchers::{method, path_regex},
 };
 
 mod api;
 
-use crate::api::{BasicAuth, XMLWriter};
+mod transport;
+pub use transport::MockTransport;
+
+use crate::api::{
+    AuthenticatedResponder, BasicAuth, FaultInjectingResponder, MockRespond, MockResponse, Route,
+    WiremockResponder, XMLWriter,
+};
 
 pub const ADMIN_USER: &str = "Admin";
 
+// Base URL of mocks without a server, which never gets resolved.
+const IN_PROCESS_URI: &str = "http://obs-mock.invalid/";
+
 // MD5 of the empty string, used as the srcmd5 of the "zero revision".
 pub const ZERO_REV_SRCMD5: &str = "d41d8cd98f00b204e9800998ecf8427e";
 
+/// The signing key served for projects with a key, see
+/// [`ObsMock::set_project_signkey`].
+pub const TEST_SIGNKEY: &str = "-----BEGIN PGP PUBLIC KEY BLOCK-----\n\
+\n\
+mQENBGrU8RYBCACYAzFpyj2j5LPTTSVPUAsVCGEMXvEmDwr1+LaHAy12cqyKcM1s\n\
+SgesYuFPmKweV+Q6qxwwgGomIpP93mPcUHgZRLEs/7wvPabMtx/7KgZI793slJS7\n\
+gXorwr76c+FzObESfXQ3hSASfC0UMZ6lsIhK0oPz2HiFFpAobdSiEbzBRFTMI+Be\n\
+jd+ZK3s1grRcDDLnb3amwZt1ZWS0/dJsVs0EvzrOtWxGhInLStIqW/8xwF+yMyPE\n\
+1o9e1gSyetHBMhQ3iyMp2UmPBwz+PNpPW24TRfw9Syr9KSJbdI+Vtr1jg7xuFiHz\n\
+Ji2/EI7CU6rfft2XXHm5TD+T6YG17LRRR4jfABEBAAG0LWhvbWU6dGVzdCBPQlMg\n\
+UHJvamVjdCA8aG9tZTp0ZXN0QGV4YW1wbGUuY29tPokBVAQTAQoAPhYhBF5PSqlp\n\
+TXbkeEyVXPAvIsjk3pwpBQJq1PEWAhsDBQkRUBaqBQsJCAcCBhUKCQgLAgQWAgMB\n\
+Ah4BAheAAAoJEPAvIsjk3pwpNrkH/3sxqXq7jlybTBroSLyWcRLy4V/qlrLaRCJH\n\
+bBp5fPIbe1txlNZR1bAS6qylUOOfAxURJyH+FDDfIrjXdUlWlJyoLDt+kWAuRddM\n\
+w2VFAnm+D0SmoZtFd6St4++hJKvv1jWGoLJYrfyYnLpEFJPMn1qgh8yBdGXc+9eS\n\
+QS4NoAYhXqL3mwZWu8AkskBlcvBjlGztdVyNHbO3EM/WMZQgwJ7ChIi0rrZg2JvP\n\
+UiQrQsFqplnnxn+MKo7PJluiL1FV6CaT3kHUhmG9cVBwHNutg9rR55eq8xg3HK/z\n\
+ezG4jOnxMWCuZ4RnNPBleIUhH4x9/cjlS4D8dcgtV26GcyzcxxI=\n\
+=RJ4t\n\
+-----END PGP PUBLIC KEY BLOCK-----\n\
+";
+pub const TEST_SIGNKEY_FINGERPRINT: &str = "5e4f 4aa9 694d 76e4 784c 955c f02f 22c8 e4de 9c29";
+pub const TEST_SIGNKEY_EXPIRES: u64 = 2082801600;
+pub const TEST_SSLCERT: &str = "-----BEGIN CERTIFICATE-----\n\
+MIIDbTCCAlWgAwIBAgIUduWzua24p6Ei4uXriNgC4DMMGhUwDQYJKoZIhvcNAQEL\n\
+BQAwRjEeMBwGA1UEAwwVaG9tZTp0ZXN0IE9CUyBQcm9qZWN0MSQwIgYJKoZIhvcN\n\
+AQkBFhVob21lOnRlc3RAZXhhbXBsZS5jb20wHhcNMjYxMDE4MTYxNzMyWhcNMzYx\n\
+MDE1MTYxNzMyWjBGMR4wHAYDVQQDDBVob21lOnRlc3QgT0JTIFByb2plY3QxJDAi\n\
+BgkqhkiG9w0BCQEWFWhvbWU6dGVzdEBleGFtcGxlLmNvbTCCASIwDQYJKoZIhvcN\n\
+AQEBBQADggEPADCCAQoCggEBAMuh4fVBSiP9zjdiyh21lFokgR4YEMYdLxbAg34+\n\
+M64x/RU4oIwRYq3Tc+C/lT+ztzLdT3qfJMxSnyUqojsocq348D3WR5EMKTvNtFH/\n\
++CImRXfXxJqTTCmML4BH5wSUs8kIFApCzpqIgXIBT5AOkympIZfHLUEXZcqmaili\n\
+Q3dOCO0w49UVQZdVhgQ57u88iQIrHDDs3jR4L4sYudqv/wpZAEbO1/ZMe2RijXpd\n\
+MeyPgOo0ak5/k+zTV+1xwRRjFoATFgxSCy7xjenli2hahvqJ2a08KCnVkefHMHBr\n\
+DwcNW3/99pZqI0RyWdAuWCEfdeQ+b82uO5rMtjvEf/trm4cCAwEAAaNTMFEwHQYD\n\
+VR0OBBYEFCXx2R9e78RNETCSr9Lg7x7oa1spMB8GA1UdIwQYMBaAFCXx2R9e78RN\n\
+ETCSr9Lg7x7oa1spMA8GA1UdEwEB/wQFMAMBAf8wDQYJKoZIhvcNAQELBQADggEB\n\
+AGLK3iuEsxpMGgSilBDh+RhJDV3K5PEbLHVcdLhtZkOUIKa/ZLe5W2GML35ufO0s\n\
+99RtZFd5FT9+98QckUXjzN5hAUiw+slFDA3oT+4Rli0HxKauDwOwhVDap9YCTwU5\n\
+1QKP0Ce8Of6gJ1SlKUqKoIRnn4wNrYagyBSNDO5rNfhGJaI9blLCWwjTo7COR2SE\n\
+52d0l0URBbDCJjzAKEpuq3bmCyadRQ5l4+5dwIeKIWyebkJP0FTJmGeovjqpAn3q\n\
+v0MiuP68s2IsgK8vKrW1r/PkaxrQ1Bt6Vv4aFmCH53SQo5N6zrIZFDPSmMB3R26b\n\
+lgF5WHQ8kdbf+G3yZai9hpA=\n\
+-----END CERTIFICATE-----\n\
+";
+
 pub fn random_md5() -> String {
     let md5bytes: [u8; 16] = rand::random();
     base16ct::lower::encode_string(&md5bytes)
@@ -67,6 +132,7 @@ pub struct MockSourceFile {
 impl MockSourceFile {
     const LINK_PATH: &'static str = "_link";
     const META_PATH: &'static str = "_meta";
+    const PATCHINFO_PATH: &'static str = "_patchinfo";
 
     pub fn new_metadata(
         project: &str,
@@ -305,6 +371,36 @@ impl MockPackage {
         }
     }
 
+    /// The contents of the latest meta revision.
+    fn meta(&self) -> &[u8] {
+        let entry = self
+            .meta_revisions
+            .last()
+            .unwrap()
+            .entries
+            .get(MockSourceFile::META_PATH)
+            .unwrap();
+        self.files
+            .get(&MockSourceFileKey::borrowed(
+                MockSourceFile::META_PATH,
+                &entry.md5,
+            ))
+            .unwrap()
+    }
+
+    fn push_meta(&mut self, meta: MockSourceFile, options: MockRevisionOptions) {
+        let (key, contents) = meta.into_key_and_contents();
+        let entry = MockEntry::from_key(&key, options.time);
+        self.files.insert(key, contents);
+
+        self.meta_revisions.push(MockRevision {
+            vrev: None,
+            options,
+            entries: [(MockSourceFile::META_PATH.to_owned(), entry)].into(),
+            linkinfo: vec![],
+        });
+    }
+
     pub fn new_branched(
         origin_project_name: String,
         origin_package_name: String,
@@ -388,6 +484,29 @@ impl MockPackage {
         }
     }
 
+    /// The contents of a file in the latest revision.
+    fn file<'a>(&'a self, path: &'a str) -> Option<&'a [u8]> {
+        let entry = self.revisions.last()?.entries.get(path)?;
+        self.files
+            .get(&MockSourceFileKey::borrowed(path, &entry.md5))
+            .map(Vec::as_slice)
+    }
+
+    /// Adds a revision changing only the given file.
+    fn commit_file(&mut self, file: MockSourceFile, options: MockRevisionOptions) {
+        let mut entries = self
+            .revisions
+            .last()
+            .map_or_else(HashMap::new, |rev| rev.entries.clone());
+        let (key, contents) = file.into_key_and_contents();
+        entries.insert(
+            key.path.clone().into_owned(),
+            MockEntry::from_key(&key, options.time),
+        );
+        self.files.insert(key, contents);
+        self.add_revision(options, entries);
+    }
+
     fn add_revision(&mut self, options: MockRevisionOptions, entries: HashMap<String, MockEntry>) {
         let vrev = self
             .latest_vrevs
@@ -587,9 +706,30 @@ pub enum MockBlockMode {
     Never,
 }
 
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Display, EnumString, Default)]
+#[strum(serialize_all = "snake_case")]
+pub enum MockProjectKind {
+    #[default]
+    Standard,
+    Maintenance,
+    MaintenanceIncident,
+    MaintenanceRelease,
+}
+
 #[derive(Default)]
 struct MockProject {
     packages: HashMap<String, MockPackage>,
+    deleted_packages: HashSet<String>,
+    config: String,
+    /// Expiry of the signing key, if the project has one.
+    signkey_expires: Option<u64>,
+    roles: Vec<(MockRoleHolder, String)>,
+    kind: MockProjectKind,
+    locked: bool,
+    /// For maintenance projects, the projects maintained using them.
+    maintains: Vec<String>,
+    /// Projects this one links to, whose packages it inherits.
+    links: Vec<String>,
     repos: HashMap<String, ArchMap<MockRepository>>,
 
     rebuild: MockRebuildMode,
@@ -633,10 +773,156 @@ fn get_package<'p>(project: &'p mut MockProject, name: &str) -> &'p mut MockPack
         .unwrap_or_else(|| panic!("Unknown package: {name}"))
 }
 
+/// A failure to return instead of (or in addition to) the regular response,
+/// see [`ObsMock::inject_faults`].
+#[derive(Clone, Debug)]
+pub enum MockFault {
+    /// Respond with the given HTTP status and a plain text body, optionally
+    /// asking the client to retry after some seconds.
+    Status { code: u16, retry_after: Option<u64> },
+    /// Respond with the given HTTP status and an OBS `<status>` error body.
+    ApiError {
+        code: u16,
+        error_code: String,
+        summary: String,
+        details: Option<String>,
+    },
+    /// Delay the regular response.
+    Delay(Duration),
+    /// Respond successfully with the given body instead of the regular
+    /// response, e.g. to make a resource look concurrently modified.
+    Body(Vec<u8>),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum MockTokenKind {
+    Runservice,
+    Rebuild,
+    Release,
+    Workflow,
+    Rss,
+}
+
+#[derive(Clone, Debug)]
+pub struct MockToken {
+    pub id: u64,
+    pub string: String,
+    pub kind: MockTokenKind,
+    pub owner: String,
+    pub description: String,
+    /// The package the token is scoped to, if any.
+    pub package: Option<(String, String)>,
+    pub triggered_at: Option<SystemTime>,
+}
+
+/// A call of one of the `/trigger` endpoints.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockTrigger {
+    pub kind: MockTokenKind,
+    pub token_id: u64,
+    pub project: String,
+    pub package: String,
+    /// The other query parameters given, e.g. `repository`.
+    pub params: Vec<(String, String)>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Display, EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum MockWorkflowRunStatus {
+    Running,
+    Success,
+    Fail,
+}
+
+/// A run of the workflows, as recorded when `/trigger/workflow` is called.
+/// The mock doesn't run the workflows, so runs stay running unless changed
+/// using [`ObsMock::set_workflow_run_status`].
+#[derive(Clone, Debug)]
+pub struct MockWorkflowRun {
+    pub id: u64,
+    pub token_id: u64,
+    pub status: MockWorkflowRunStatus,
+    pub scm_vendor: String,
+    pub hook_event: String,
+    /// `pull_request`, `push` or `tag_push`.
+    pub generic_event_type: String,
+    pub request_payload: String,
+    pub response_body: Option<String>,
+}
+
+/// A user or group given a role in a project or package.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MockRoleHolder {
+    User(String),
+    Group(String),
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MockPerson {
+    pub email: String,
+    pub realname: String,
+    pub globalroles: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MockGroup {
+    pub email: Option<String>,
+    pub maintainers: Vec<String>,
+    pub members: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MockCommentTarget {
+    Project(String),
+    Package(String, String),
+    Request(u64),
+}
+
+#[derive(Clone, Debug)]
+pub struct MockComment {
+    pub id: u64,
+    pub target: MockCommentTarget,
+    pub who: String,
+    pub when: SystemTime,
+    pub parent: Option<u64>,
+    pub body: String,
+}
+
+/// An action of a request, whose source and target are given as project and
+/// optionally package.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MockRequestAction {
+    pub action_type: String,
+    pub source: Option<(String, Option<String>)>,
+    pub target: Option<(String, Option<String>)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MockRequest {
+    pub id: u64,
+    pub creator: String,
+    pub created: SystemTime,
+    pub description: String,
+    pub actions: Vec<MockRequestAction>,
+}
+
 struct Inner {
-    server: MockServer,
+    server: Option<MockServer>,
+    routes: OnceLock<Vec<Route>>,
     auth: BasicAuth,
+    tokens: RwLock<Vec<MockToken>>,
+    next_token_id: AtomicU64,
+    triggers: RwLock<Vec<MockTrigger>>,
+    workflow_runs: RwLock<Vec<MockWorkflowRun>>,
+    sessions: RwLock<HashSet<String>>,
+    faults: Mutex<VecDeque<MockFault>>,
     projects: RwLock<ProjectMap>,
+    comments: RwLock<Vec<MockComment>>,
+    next_comment_id: AtomicU64,
+    persons: RwLock<HashMap<String, MockPerson>>,
+    groups: RwLock<HashMap<String, MockGroup>>,
+    requests: RwLock<Vec<MockRequest>>,
 }
 
 #[derive(Clone)]
@@ -646,156 +932,543 @@ pub struct ObsMock {
 
 impl ObsMock {
     pub async fn start(username: &str, password: &str) -> Self {
+        let mock = Self::new(username, password, Some(MockServer::start().await));
+        let server = mock.inner.server.as_ref().unwrap();
+        for route in mock.routes() {
+            Mock::given(method(route.method.clone()))
+                .and(path_regex(route.path))
+                .respond_with(WiremockResponder(route.responder.clone()))
+                .mount(server)
+                .await;
+        }
+
+        mock
+    }
+
+    /// Creates a mock without a server, to serve requests in-process using
+    /// [`ObsMock::transport`].
+    pub fn in_process(username: &str, password: &str) -> Self {
+        Self::new(username, password, None)
+    }
+
+    fn new(username: &str, password: &str, server: Option<MockServer>) -> Self {
         let inner = Inner {
             auth: BasicAuth::new(username, password),
-            server: MockServer::start().await,
+            server,
+            routes: OnceLock::new(),
+            tokens: RwLock::new(Vec::new()),
+            next_token_id: AtomicU64::new(1),
+            triggers: RwLock::new(Vec::new()),
+            workflow_runs: RwLock::new(Vec::new()),
+            sessions: RwLock::new(HashSet::new()),
+            faults: Mutex::new(VecDeque::new()),
             projects: RwLock::new(HashMap::new()),
+            comments: RwLock::new(Vec::new()),
+            next_comment_id: AtomicU64::new(1),
+            persons: RwLock::new(HashMap::new()),
+            groups: RwLock::new(HashMap::new()),
+            requests: RwLock::new(Vec::new()),
         };
 
-        let server = Self {
+        let mock = Self {
             inner: Arc::new(inner),
         };
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/source/[^/]+$"))
-            .respond_with(ProjectListingResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("DELETE"))
-            .and(path_regex("^/source/[^/]+$"))
-            .respond_with(ProjectDeleteResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/source/[^/]+/_meta$"))
-            .respond_with(ProjectMetaResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/source/[^/]+/[^/]+$"))
-            .respond_with(PackageSourceListingResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("POST"))
-            .and(path_regex("^/source/[^/]+/[^/]+$"))
-            .respond_with(PackageSourceCommandResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("DELETE"))
-            .and(path_regex("^/source/[^/]+/[^/]+$"))
-            .respond_with(PackageSourceDeleteResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/source/[^/]+/[^/]+/_history$"))
-            .respond_with(PackageSourceHistoryResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/source/[^/]+/[^/]+/[^/]+$"))
-            .respond_with(PackageSourceFileResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("PUT"))
-            .and(path_regex("^/source/[^/]+/[^/]+/[^/]+$"))
-            .respond_with(PackageSourcePlacementResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("POST"))
-            .and(path_regex("^/build/[^/]+$"))
-            .respond_with(ProjectBuildCommandResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/_result$"))
-            .respond_with(BuildResultsResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/_result$"))
-            .respond_with(BuildResultsResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+$"))
-            .respond_with(RepoListingResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("/build/[^/]+/[^/]+$"))
-            .respond_with(ArchListingResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/[^/]+/[^/]+/_jobhistory$"))
-            .respond_with(BuildJobHistoryResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/[^/]+/[^/]+/[^/]+$"))
-            .respond_with(BuildBinaryListResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/[^/]+/[^/]+/[^/]+/_history$"))
-            .respond_with(BuildHistoryResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/[^/]+/[^/]+/[^/]+/_log$"))
-            .respond_with(BuildLogResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/[^/]+/[^/]+/[^/]+/_status$"))
-            .respond_with(BuildPackageStatusResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        Mock::given(method("GET"))
-            .and(path_regex("^/build/[^/]+/[^/]+/[^/]+/[^/]+/[^/]+$"))
-            .respond_with(BuildBinaryFileResponder::new(server.clone()))
-            .mount(&server.inner.server)
-            .await;
-
-        server
+        // The responders need a handle to the mock, so the routes can only
+        // be created afterwards.
+        let routes = vec![
+            mock.route(
+                Method::GET,
+                "^/source/?$",
+                SourceListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/source/?$",
+                MaintenanceBranchResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+$",
+                ProjectListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::DELETE,
+                "^/source/[^/]+$",
+                ProjectDeleteResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/source/[^/]+$",
+                ProjectSourceCommandResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/_pubkey$",
+                ProjectPubkeyResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/_keyinfo$",
+                ProjectKeyInfoResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/_meta$",
+                ProjectMetaResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::PUT,
+                "^/source/[^/]+/_meta$",
+                ProjectMetaPlacementResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/_config$",
+                ProjectConfigResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::PUT,
+                "^/source/[^/]+/_config$",
+                ProjectConfigPlacementResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/[^/]+$",
+                PackageSourceListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/source/[^/]+/[^/]+$",
+                PackageSourceCommandResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::DELETE,
+                "^/source/[^/]+/[^/]+$",
+                PackageSourceDeleteResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/[^/]+/_history$",
+                PackageSourceHistoryResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/source/[^/]+/[^/]+/[^/]+$",
+                PackageSourceFileResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::PUT,
+                "^/source/[^/]+/[^/]+/[^/]+$",
+                PackageSourcePlacementResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/comments/(project/[^/]+|package/[^/]+/[^/]+|request/[0-9]+)$",
+                CommentListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/comments/(project/[^/]+|package/[^/]+/[^/]+|request/[0-9]+)$",
+                CommentCreationResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::DELETE,
+                "^/comment/[0-9]+$",
+                CommentDeleteResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/person/[^/]+$",
+                PersonResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/person/[^/]+/token$",
+                PersonTokenListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/person/[^/]+/token$",
+                PersonTokenCreationResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::DELETE,
+                "^/person/[^/]+/token/[0-9]+$",
+                PersonTokenDeleteResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/trigger/(runservice|rebuild|release)$",
+                TriggerResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/trigger/workflow$",
+                WorkflowTriggerResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/workflow_runs$",
+                WorkflowRunListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/workflow_runs/[0-9]+$",
+                WorkflowRunResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/group/[^/]+$",
+                GroupResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/search/(project|package)/id$",
+                SearchIdResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/search/project$",
+                SearchProjectResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/request$",
+                RequestCreationResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::POST,
+                "^/build/[^/]+$",
+                ProjectBuildCommandResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/_result$",
+                BuildResultsResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+$",
+                RepoListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "/build/[^/]+/[^/]+$",
+                ArchListingResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/[^/]+/[^/]+/_jobhistory$",
+                BuildJobHistoryResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/[^/]+/[^/]+/[^/]+$",
+                BuildBinaryListResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/[^/]+/[^/]+/[^/]+/_history$",
+                BuildHistoryResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/[^/]+/[^/]+/[^/]+/_log$",
+                BuildLogResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/[^/]+/[^/]+/[^/]+/_status$",
+                BuildPackageStatusResponder::new(mock.clone()),
+            ),
+            mock.route(
+                Method::GET,
+                "^/build/[^/]+/[^/]+/[^/]+/[^/]+/[^/]+$",
+                BuildBinaryFileResponder::new(mock.clone()),
+            ),
+        ];
+        let _ = mock.inner.routes.set(routes);
+        mock
     }
 
     pub fn uri(&self) -> Url {
-        self.inner.server.uri().parse().expect("uri is not a Url")
+        match &self.inner.server {
+            Some(server) => server.uri().parse().expect("uri is not a Url"),
+            None => IN_PROCESS_URI.parse().unwrap(),
+        }
+    }
+
+    /// A transport serving requests directly using the mock's responders,
+    /// without going through the wiremock server.
+    pub fn transport(&self) -> MockTransport {
+        MockTransport::new(self.clone())
+    }
+
+    /// A client authenticated as the mock's user that serves requests
+    /// in-process. As the client implements [`ObsApi`], this is a reference
+    /// in-memory implementation sharing all state with the mock.
+    ///
+    /// [`ObsApi`]: open_build_service_api::ObsApi
+    pub fn client(&self) -> open_build_service_api::Client {
+        open_build_service_api::Client::builder(self.uri())
+            .basic_auth(
+                self.auth().username().to_owned(),
+                self.auth().password().to_owned(),
+            )
+            .session_cookies(true)
+            .transport(self.transport())
+            .build()
+            .expect("Failed to create in-process client")
+    }
+
+    fn routes(&self) -> &[Route] {
+        self.inner.routes.get().expect("routes are set on creation")
+    }
+
+    fn respond(&self, request: &wiremock::Request) -> MockResponse {
+        self.routes()
+            .iter()
+            .find(|route| route.matches(request))
+            .map(|route| route.responder.respond(request))
+            .unwrap_or_else(|| MockResponse::new(http::StatusCode::NOT_FOUND))
     }
 
     pub fn auth(&self) -> &BasicAuth {
         &self.inner.auth
     }
 
+    fn route<R: MockRespond + 'static>(
+        &self,
+        method: Method,
+        path: &'static str,
+        responder: R,
+    ) -> Route {
+        Route::new(
+            method,
+            path,
+            FaultInjectingResponder::new(
+                self.clone(),
+                AuthenticatedResponder::new(self.clone(), responder),
+            ),
+        )
+    }
+
+    /// Accept the given API token in `Authorization: Token ...` headers. The
+    /// token belongs to the mock's user, and isn't scoped to a package.
+    pub fn add_token(&self, token: String) {
+        self.add_token_with(token, MockTokenKind::Runservice, String::new(), None);
+    }
+
+    /// Adds an API token of the mock's user, returning its id.
+    pub fn add_token_with(
+        &self,
+        token: String,
+        kind: MockTokenKind,
+        description: String,
+        package: Option<(String, String)>,
+    ) -> u64 {
+        let id = self.inner.next_token_id.fetch_add(1, Ordering::Relaxed);
+        self.inner.tokens.write().unwrap().push(MockToken {
+            id,
+            string: token,
+            kind,
+            owner: self.auth().username().to_owned(),
+            description,
+            package,
+            triggered_at: None,
+        });
+        id
+    }
+
+    pub fn tokens(&self) -> Vec<MockToken> {
+        self.inner.tokens.read().unwrap().clone()
+    }
+
+    fn delete_token(&self, id: u64) -> bool {
+        let mut tokens = self.inner.tokens.write().unwrap();
+        let len = tokens.len();
+        tokens.retain(|token| token.id != id);
+        tokens.len() != len
+    }
+
+    pub fn triggers(&self) -> Vec<MockTrigger> {
+        self.inner.triggers.read().unwrap().clone()
+    }
+
+    pub fn workflow_runs(&self) -> Vec<MockWorkflowRun> {
+        self.inner.workflow_runs.read().unwrap().clone()
+    }
+
+    pub fn set_workflow_run_status(
+        &self,
+        id: u64,
+        status: MockWorkflowRunStatus,
+        response_body: Option<String>,
+    ) {
+        let mut runs = self.inner.workflow_runs.write().unwrap();
+        let run = runs
+            .iter_mut()
+            .find(|run| run.id == id)
+            .unwrap_or_else(|| panic!("Unknown workflow run: {id}"));
+        run.status = status;
+        run.response_body = response_body;
+    }
+
+    /// Number of active sessions. A new session is created every time a client
+    /// authenticates using credentials rather than a session cookie.
+    pub fn session_count(&self) -> usize {
+        self.inner.sessions.read().unwrap().len()
+    }
+
+    /// Forget all sessions, so clients have to authenticate again.
+    pub fn expire_sessions(&self) {
+        self.inner.sessions.write().unwrap().clear();
+    }
+
+    /// Apply the fault to the next `count` requests, after any faults that are
+    /// already pending.
+    pub fn inject_faults(&self, fault: MockFault, count: usize) {
+        let mut faults = self.inner.faults.lock().unwrap();
+        faults.extend(std::iter::repeat_n(fault, count));
+    }
+
     fn projects(&self) -> &RwLock<ProjectMap> {
         &self.inner.projects
     }
 
+    /// Adds a comment, returning its id.
+    pub fn add_comment(
+        &self,
+        target: MockCommentTarget,
+        who: String,
+        body: String,
+        parent: Option<u64>,
+    ) -> u64 {
+        let id = self.inner.next_comment_id.fetch_add(1, Ordering::Relaxed);
+        self.inner.comments.write().unwrap().push(MockComment {
+            id,
+            target,
+            who,
+            when: SystemTime::now(),
+            parent,
+            body,
+        });
+        id
+    }
+
+    pub fn comments(&self, target: &MockCommentTarget) -> Vec<MockComment> {
+        self.inner
+            .comments
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|comment| &comment.target == target)
+            .cloned()
+            .collect()
+    }
+
+    pub fn add_person(&self, login: String, person: MockPerson) {
+        self.inner.persons.write().unwrap().insert(login, person);
+    }
+
+    pub fn add_group(&self, title: String, group: MockGroup) {
+        self.inner.groups.write().unwrap().insert(title, group);
+    }
+
+    fn persons(&self) -> &RwLock<HashMap<String, MockPerson>> {
+        &self.inner.persons
+    }
+
+    fn groups(&self) -> &RwLock<HashMap<String, MockGroup>> {
+        &self.inner.groups
+    }
+
+    fn delete_comment(&self, id: u64) -> bool {
+        let mut comments = self.inner.comments.write().unwrap();
+        let len = comments.len();
+        comments.retain(|comment| comment.id != id);
+        comments.len() != len
+    }
+
     pub fn add_project(&self, project_name: String) {
         let mut projects = self.inner.projects.write().unwrap();
         projects.entry(project_name).or_default();
     }
 
+    pub fn set_project_config(&self, project_name: &str, config: String) {
+        let mut projects = self.inner.projects.write().unwrap();
+        let project = get_project(&mut projects, project_name);
+        project.config = config;
+    }
+
+    /// Gives the project the fixed [`TEST_SIGNKEY`].
+    pub fn set_project_signkey(&self, project_name: &str) {
+        let mut projects = self.inner.projects.write().unwrap();
+        get_project(&mut projects, project_name).signkey_expires = Some(TEST_SIGNKEY_EXPIRES);
+    }
+
+    pub fn add_project_role(&self, project_name: &str, holder: MockRoleHolder, role: String) {
+        let mut projects = self.inner.projects.write().unwrap();
+        let project = get_project(&mut projects, project_name);
+        if !project.roles.contains(&(holder.clone(), role.clone())) {
+            project.roles.push((holder, role));
+        }
+    }
+
+    pub fn project_roles(&self, project_name: &str) -> Vec<(MockRoleHolder, String)> {
+        let mut projects = self.inner.projects.write().unwrap();
+        get_project(&mut projects, project_name).roles.clone()
+    }
+
+    /// Makes the project a maintenance project for the given projects, which
+    /// are then the ones packages are branched from by `mbranch`.
+    pub fn set_project_maintenance(&self, project_name: &str, maintains: Vec<String>) {
+        let mut projects = self.inner.projects.write().unwrap();
+        let project = get_project(&mut projects, project_name);
+        project.kind = MockProjectKind::Maintenance;
+        project.maintains = maintains;
+    }
+
+    pub fn project_kind(&self, project_name: &str) -> MockProjectKind {
+        let mut projects = self.inner.projects.write().unwrap();
+        get_project(&mut projects, project_name).kind
+    }
+
+    pub fn requests(&self) -> Vec<MockRequest> {
+        self.inner.requests.read().unwrap().clone()
+    }
+
+    fn add_request(
+        &self,
+        creator: String,
+        description: String,
+        actions: Vec<MockRequestAction>,
+    ) -> MockRequest {
+        let mut requests = self.inner.requests.write().unwrap();
+        let request = MockRequest {
+            id: requests.len() as u64 + 1,
+            creator,
+            created: SystemTime::now(),
+            description,
+            actions,
+        };
+        requests.push(request.clone());
+        request
+    }
+
+    pub fn project_config(&self, project_name: &str) -> String {
+        let mut projects = self.inner.projects.write().unwrap();
+        get_project(&mut projects, project_name).config.clone()
+    }
+
+    /// Makes `project_name` link to `linked_project`, so expanded listings
+    /// include the packages of the latter.
+    pub fn add_project_link(&self, project_name: &str, linked_project: String) {
+        let mut projects = self.inner.projects.write().unwrap();
+        let project = get_project(&mut projects, project_name);
+        project.links.push(linked_project);
+    }
+
     pub fn set_project_modes(
         &self,
         project_name: &str,
@@ -833,23 +1506,16 @@ impl ObsMock {
         let package = get_package(project, package_name);
 
         let meta = MockSourceFile::new_metadata(project_name, package_name, &options.disabled);
-        let (key, contents) = meta.into_key_and_contents();
-        package.files.insert(key.clone(), contents);
-
-        let meta_entry = MockEntry::from_key(&key, options.time);
-
-        package.meta_revisions.push(MockRevision {
-            vrev: None,
-            options: MockRevisionOptions {
+        package.push_meta(
+            meta,
+            MockRevisionOptions {
                 srcmd5: options.meta_srcmd5,
                 version: None,
                 time: options.time,
                 user: options.user,
                 comment: None,
             },
-            entries: [(MockSourceFile::META_PATH.to_owned(), meta_entry)].into(),
-            linkinfo: vec![],
-        });
+        );
     }
 
     pub fn add_package_files(
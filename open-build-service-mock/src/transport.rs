@@ -0,0 +1,55 @@
+use futures::future::BoxFuture;
+use futures::prelude::*;
+use http_body_util::BodyExt;
+use open_build_service_api::reqwest::{self, ResponseBuilderExt};
+use open_build_service_api::{Error, Transport};
+
+use crate::ObsMock;
+
+/// A [`Transport`] serving requests in-process using the mock's responders,
+/// so no socket is needed. Create one using [`ObsMock::transport`].
+///
+/// Responses are returned immediately: delays (including
+/// [`MockFault::Delay`](crate::MockFault::Delay)) and request timeouts are
+/// ignored.
+#[derive(Clone)]
+pub struct MockTransport {
+    mock: ObsMock,
+}
+
+impl MockTransport {
+    pub(crate) fn new(mock: ObsMock) -> Self {
+        Self { mock }
+    }
+}
+
+impl Transport for MockTransport {
+    fn execute(
+        &self,
+        mut request: reqwest::Request,
+    ) -> BoxFuture<'_, Result<reqwest::Response, Error>> {
+        async move {
+            let body = match request.body_mut().take() {
+                Some(body) => body.collect().await?.to_bytes().to_vec(),
+                None => Vec::new(),
+            };
+            let request = wiremock::Request {
+                url: request.url().clone(),
+                method: request.method().clone(),
+                headers: request.headers().clone(),
+                body,
+            };
+
+            let response = self.mock.respond(&request);
+            let mut builder = http::Response::builder()
+                .status(response.status)
+                .url(request.url);
+            for (name, value) in response.headers {
+                builder = builder.header(name, value);
+            }
+            let response = builder.body(response.body).expect("mock response is valid");
+            Ok(response.into())
+        }
+        .boxed()
+    }
+}